@@ -0,0 +1,97 @@
+use std::{
+    io,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+/// Something to do with an entry outside of xf
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Open with the default application for its type
+    Open,
+    /// Show it in the file manager, selected where the platform allows
+    Reveal,
+}
+
+impl Action {
+    /// Run the action on `path` without waiting for the application to exit
+    pub fn run<P: AsRef<Path>>(self, path: P) -> io::Result<()> {
+        let path = dunce::canonicalize(path)?;
+        self.command(&path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map(|_| ())
+    }
+
+    /// Command that performs the action, `open -R` on macOS, `explorer
+    /// /select,` on windows, and `xdg-open` elsewhere
+    ///
+    /// `xdg-open` can't select an entry so revealing opens its parent
+    /// directory instead.
+    pub fn command(self, path: &Path) -> Command {
+        #[cfg(target_os = "macos")]
+        {
+            let mut command = Command::new("open");
+            if self == Self::Reveal {
+                command.arg("-R");
+            }
+            command.arg(path);
+            command
+        }
+
+        #[cfg(windows)]
+        {
+            let mut command = Command::new("explorer");
+            match self {
+                Self::Open => command.arg(path),
+                Self::Reveal => command.arg(format!("/select,{}", path.display())),
+            };
+            command
+        }
+
+        #[cfg(not(any(target_os = "macos", windows)))]
+        {
+            let mut command = Command::new("xdg-open");
+            match self {
+                Self::Open => command.arg(path),
+                Self::Reveal => command.arg(path.parent().unwrap_or(path)),
+            };
+            command
+        }
+    }
+}
+
+/// Open `path` with the default application for its type
+pub fn open<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    Action::Open.run(path)
+}
+
+/// Show `path` in the file manager
+pub fn reveal<P: AsRef<Path>>(path: P) -> io::Result<()> {
+    Action::Reveal.run(path)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(not(any(target_os = "macos", windows)))]
+    #[test]
+    fn commands() {
+        let path = Path::new("/tmp/project/notes.txt");
+        let args = |action: Action| {
+            let command = action.command(path);
+            let mut parts = vec![command.get_program().to_string_lossy().to_string()];
+            parts.extend(
+                command
+                    .get_args()
+                    .map(|arg| arg.to_string_lossy().to_string()),
+            );
+            parts
+        };
+        assert_eq!(args(Action::Open), ["xdg-open", "/tmp/project/notes.txt"]);
+        assert_eq!(args(Action::Reveal), ["xdg-open", "/tmp/project"]);
+    }
+}
@@ -0,0 +1,270 @@
+use std::{
+    io::{self, Write},
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use owo_colors::OwoColorize;
+
+use crate::{style::Colorizer, FileSystem};
+
+/// Time spent in each phase of a recursive walk, see [`Report::run`]
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Report {
+    /// Opening directories and reading their entries
+    pub readdir: Duration,
+    /// Reading the metadata and permissions of entries
+    pub stat: Duration,
+    pub filter: Duration,
+    pub sort: Duration,
+    /// Styling every entry the way the grid format would
+    pub render: Duration,
+    pub total: Duration,
+    pub directories: usize,
+    pub entries: usize,
+    /// Directories and entries that couldn't be read
+    pub errors: usize,
+}
+
+impl Report {
+    /// Time a [`FileSystem::walk`] of the file system with its [`Timings`]
+    ///
+    /// The walk doesn't use the directory cache so every directory is read.
+    pub fn run(file_system: &FileSystem, colorizer: &Colorizer) -> Self {
+        let timings = Arc::new(Timings::default());
+        let file_system = FileSystem {
+            cache: None,
+            timings: Some(timings.clone()),
+            ..file_system.clone()
+        };
+
+        let start = Instant::now();
+        // Failed reads are counted by the timings
+        let walked = file_system.walk().unwrap_or_default();
+
+        let started = Instant::now();
+        let mut sink = io::sink();
+        for entry in &walked {
+            let _ = writeln!(sink, "{}", colorizer.file(entry));
+        }
+        let render = started.elapsed();
+
+        Self {
+            readdir: timings.readdir(),
+            stat: timings.stat(),
+            filter: timings.filter(),
+            sort: timings.sort(),
+            render,
+            total: start.elapsed(),
+            directories: timings.directories(),
+            entries: walked.len(),
+            errors: timings.errors(),
+        }
+    }
+
+    /// Print a table of every phase with its share of the total time
+    pub fn print<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(
+            writer,
+            "{}",
+            format!("{:<12}{:>12}{:>8}", "phase", "time", "share").dimmed()
+        )?;
+        for (phase, time) in [
+            ("readdir", self.readdir),
+            ("stat", self.stat),
+            ("filter", self.filter),
+            ("sort", self.sort),
+            ("render", self.render),
+        ] {
+            let share = match self.total.is_zero() {
+                true => 0.0,
+                false => time.as_secs_f64() / self.total.as_secs_f64() * 100.0,
+            };
+            writeln!(
+                writer,
+                "{phase:<12}{:>12}{share:>7.1}%",
+                format!("{time:.2?}")
+            )?;
+        }
+        writeln!(
+            writer,
+            "{}{:>12}",
+            format!("{:<12}", "total").bold(),
+            format!("{:.2?}", self.total)
+        )?;
+        writeln!(
+            writer,
+            "{} directories, {} entries, {} errors",
+            self.directories, self.entries, self.errors
+        )
+    }
+}
+
+/// Time a [`FileSystem`] spends reading directories during a normal listing,
+/// see [`FileSystem::with_timings`]
+///
+/// Nothing is walked on its own, the phases are added up as the listing reads
+/// directories. Reads on other threads are
+/// added as well, so the phases can add up to more than the wall time.
+#[derive(Debug, Default)]
+pub struct Timings {
+    readdir: AtomicU64,
+    stat: AtomicU64,
+    filter: AtomicU64,
+    sort: AtomicU64,
+    entries: AtomicUsize,
+    directories: AtomicUsize,
+    errors: AtomicUsize,
+}
+
+impl Timings {
+    pub fn add_readdir(&self, time: Duration) {
+        self.readdir
+            .fetch_add(time.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    /// Reading the metadata and permissions of entries
+    pub fn add_stat(&self, time: Duration) {
+        self.stat
+            .fetch_add(time.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    pub fn add_filter(&self, time: Duration) {
+        self.filter
+            .fetch_add(time.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    pub fn add_sort(&self, time: Duration) {
+        self.sort
+            .fetch_add(time.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    pub fn add_entries(&self, entries: usize) {
+        self.entries.fetch_add(entries, Ordering::Relaxed);
+    }
+
+    pub fn add_directories(&self, directories: usize) {
+        self.directories.fetch_add(directories, Ordering::Relaxed);
+    }
+
+    /// Directories and entries that couldn't be read
+    pub fn add_errors(&self, errors: usize) {
+        self.errors.fetch_add(errors, Ordering::Relaxed);
+    }
+
+    pub fn readdir(&self) -> Duration {
+        Duration::from_nanos(self.readdir.load(Ordering::Relaxed))
+    }
+
+    pub fn stat(&self) -> Duration {
+        Duration::from_nanos(self.stat.load(Ordering::Relaxed))
+    }
+
+    pub fn filter(&self) -> Duration {
+        Duration::from_nanos(self.filter.load(Ordering::Relaxed))
+    }
+
+    pub fn sort(&self) -> Duration {
+        Duration::from_nanos(self.sort.load(Ordering::Relaxed))
+    }
+
+    pub fn entries(&self) -> usize {
+        self.entries.load(Ordering::Relaxed)
+    }
+
+    pub fn directories(&self) -> usize {
+        self.directories.load(Ordering::Relaxed)
+    }
+
+    pub fn errors(&self) -> usize {
+        self.errors.load(Ordering::Relaxed)
+    }
+
+    /// `walked 12,304 entries in 0.41s (readdir 0.08s, stat 0.22s, render 0.11s)`
+    ///
+    /// Render is whatever part of `total` isn't spent reading, which includes
+    /// filtering and sorting.
+    pub fn footer(&self, total: Duration) -> String {
+        let render = total.saturating_sub(self.readdir() + self.stat());
+        format!(
+            "walked {} entries in {:.2}s (readdir {:.2}s, stat {:.2}s, render {:.2}s)",
+            thousands(self.entries()),
+            total.as_secs_f64(),
+            self.readdir().as_secs_f64(),
+            self.stat().as_secs_f64(),
+            render.as_secs_f64()
+        )
+    }
+}
+
+/// Number with a `,` between every group of three digits
+fn thousands(value: usize) -> String {
+    let digits = value.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{sort::Natural, testing::TempDir};
+
+    #[test]
+    fn report() {
+        let base = TempDir::new("bench");
+        std::fs::create_dir_all(base.join("dir")).unwrap();
+        std::fs::write(base.join("dir").join("b"), "").unwrap();
+        std::fs::write(base.join("a"), "").unwrap();
+        std::fs::write(base.join(".hidden"), "").unwrap();
+
+        let file_system = FileSystem::from(base.path())
+            .with_sorter(Natural)
+            .with_cache(8);
+        // A warm cache doesn't hide any reads
+        file_system.walk().unwrap();
+        let report = Report::run(&file_system, &Colorizer::default_theme());
+        assert_eq!(
+            (report.directories, report.entries, report.errors),
+            (2, 3, 0)
+        );
+        assert!(
+            report.readdir + report.stat + report.filter + report.sort + report.render
+                <= report.total
+        );
+
+        // Directories that can't be read are counted as errors
+        let missing = FileSystem::from(base.join("dir"));
+        std::fs::remove_dir_all(base.join("dir")).unwrap();
+        let report = Report::run(&missing, &Colorizer::default_theme());
+        assert_eq!(
+            (report.directories, report.entries, report.errors),
+            (0, 0, 1)
+        );
+    }
+
+    #[test]
+    fn timing_footer() {
+        assert_eq!(thousands(0), "0");
+        assert_eq!(thousands(999), "999");
+        assert_eq!(thousands(12_304), "12,304");
+        assert_eq!(thousands(1_000_000), "1,000,000");
+
+        let timings = Timings::default();
+        timings.add_entries(12_304);
+        timings.add_readdir(Duration::from_millis(80));
+        timings.add_stat(Duration::from_millis(220));
+        assert_eq!(
+            timings.footer(Duration::from_millis(410)),
+            "walked 12,304 entries in 0.41s (readdir 0.08s, stat 0.22s, render 0.11s)"
+        );
+    }
+}
@@ -0,0 +1,225 @@
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use hashbrown::HashMap;
+
+use crate::Entry;
+
+/// Least recently used cache of directory listings
+///
+/// Listings are keyed by the directory's path and are only reused while the
+/// directory's modified time is unchanged. Adding, removing, or renaming a
+/// child updates that time which invalidates the cached listing.
+///
+/// The cached entries are already filtered and sorted, so the
+/// [`crate::FileSystem`] starts a new cache when its filters or sorter change.
+#[derive(Debug, Clone)]
+pub struct DirCache {
+    capacity: usize,
+    listings: HashMap<PathBuf, (SystemTime, Vec<Entry>)>,
+    order: VecDeque<PathBuf>,
+}
+
+impl DirCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            listings: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.listings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.listings.is_empty()
+    }
+
+    /// Cached listing for the directory if it hasn't been modified since it was
+    /// stored
+    pub fn get(&mut self, path: &Path, modified: SystemTime) -> Option<Vec<Entry>> {
+        match self.listings.get(path) {
+            Some((stored, entries)) if *stored == modified => {
+                let entries = entries.clone();
+                self.touch(path);
+                Some(entries)
+            }
+            Some(_) => {
+                self.invalidate(path);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Store a listing, evicting the least recently used listing when full
+    pub fn insert(&mut self, path: &Path, modified: SystemTime, entries: Vec<Entry>) {
+        if self.listings.contains_key(path) {
+            self.touch(path);
+        } else {
+            if self.listings.len() >= self.capacity {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.listings.remove(&oldest);
+                }
+            }
+            self.order.push_back(path.to_path_buf());
+        }
+        self.listings
+            .insert(path.to_path_buf(), (modified, entries));
+    }
+
+    pub fn invalidate(&mut self, path: &Path) {
+        if self.listings.remove(path).is_some() {
+            self.order.retain(|p| p != path);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.listings.clear();
+        self.order.clear();
+    }
+
+    fn touch(&mut self, path: &Path) {
+        if let Some(index) = self.order.iter().position(|p| p == path) {
+            if let Some(path) = self.order.remove(index) {
+                self.order.push_back(path);
+            }
+        }
+    }
+}
+
+/// A slice of a directory's sorted entries, see [`crate::FileSystem::entries_page`]
+#[derive(Debug, Clone)]
+pub struct Page {
+    pub entries: Vec<Entry>,
+    /// Index of the first entry in the page
+    pub offset: usize,
+    /// Number of entries in the whole directory
+    pub total: usize,
+}
+
+/// Filtered and sorted listing of a single directory that pages are sliced
+/// from
+///
+/// Like [`DirCache`] the listing is reused while the directory's modified time
+/// is unchanged.
+#[derive(Debug, Default)]
+pub(crate) struct PageIndex {
+    listing: Option<(SystemTime, Vec<Entry>)>,
+}
+
+impl PageIndex {
+    /// Page of the listing, calling `read` when there is no listing for this
+    /// modified time yet
+    pub(crate) fn page<F, E>(
+        &mut self,
+        modified: SystemTime,
+        offset: usize,
+        limit: usize,
+        read: F,
+    ) -> Result<Page, E>
+    where
+        F: FnOnce() -> Result<Vec<Entry>, E>,
+    {
+        let entries = match self.listing.as_ref() {
+            Some((stored, entries)) if *stored == modified => entries,
+            _ => &self.listing.insert((modified, read()?)).1,
+        };
+        let start = offset.min(entries.len());
+        let end = start.saturating_add(limit).min(entries.len());
+        Ok(Page {
+            entries: entries[start..end].to_vec(),
+            offset: start,
+            total: entries.len(),
+        })
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.listing = None;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        sort::{Natural, Reverse},
+        testing::TempDir,
+        FileSystem, Hidden,
+    };
+
+    #[test]
+    fn entries_page() {
+        let base = TempDir::new("page");
+        for i in 0..10 {
+            std::fs::write(base.join(format!("file{i}")), "").unwrap();
+        }
+
+        let file_system = FileSystem::from(base.path()).with_sorter(Natural);
+        let names = |offset, limit| {
+            let page = file_system.entries_page(offset, limit).unwrap();
+            let names = page
+                .entries
+                .iter()
+                .map(|e| e.file_name().to_string())
+                .collect::<Vec<_>>();
+            (names, page.total)
+        };
+        assert_eq!(
+            names(0, 3),
+            (vec!["file0".into(), "file1".into(), "file2".into()], 10)
+        );
+        assert_eq!(names(8, 5), (vec!["file8".into(), "file9".into()], 10));
+        assert_eq!(names(20, 5).0.len(), 0);
+
+        let all = file_system.entries().unwrap();
+        let pages = (0..4)
+            .flat_map(|i| file_system.entries_page(i * 3, 3).unwrap().entries)
+            .collect::<Vec<_>>();
+        assert_eq!(all, pages);
+    }
+
+    #[test]
+    fn changing_sorter_and_filter() {
+        let base = TempDir::new("dir-cache");
+        for name in ["a", "b", ".c"] {
+            std::fs::write(base.join(name), "").unwrap();
+        }
+        let names = |file_system: &FileSystem| {
+            file_system
+                .entries()
+                .unwrap()
+                .iter()
+                .map(|e| e.file_name().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let cached = FileSystem::from(base.path())
+            .with_sorter(Natural)
+            .with_cache(4);
+        assert_eq!(names(&cached), ["a", "b"]);
+
+        let reversed = cached.clone().with_sorter(Reverse(Natural));
+        assert_eq!(names(&reversed), ["b", "a"]);
+
+        let hidden = reversed.with_filter(Hidden::default());
+        assert_eq!(names(&hidden), [".c"]);
+
+        // Clones don't write their own order into the original's cache
+        let mut clone = cached.clone();
+        clone.set_sorter(Reverse(Natural));
+        assert_eq!(names(&clone), ["b", "a"]);
+        assert_eq!(names(&cached), ["a", "b"]);
+        let clone = cached.clone().with_dereference(true);
+        assert_eq!(names(&clone), ["a", "b"]);
+        assert_eq!(cached.cache.as_ref().unwrap().lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,30 @@
+use std::time::SystemTime;
+
+/// Source of the current time for dates relative to now, e.g. whether a date
+/// is in this year or how long ago it was
+///
+/// Reading it on every use instead of once keeps long running sessions right
+/// after midnight or the new year.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> SystemTime;
+}
+
+/// The system clock
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// A clock stopped at a single time, for reproducible output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedClock(pub SystemTime);
+
+impl Clock for FixedClock {
+    fn now(&self) -> SystemTime {
+        self.0
+    }
+}
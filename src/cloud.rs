@@ -0,0 +1,93 @@
+use std::fs::Metadata;
+
+const FILE_ATTRIBUTE_OFFLINE: u32 = 0x0000_1000;
+const FILE_ATTRIBUTE_RECALL_ON_OPEN: u32 = 0x0004_0000;
+const FILE_ATTRIBUTE_PINNED: u32 = 0x0008_0000;
+const FILE_ATTRIBUTE_UNPINNED: u32 = 0x0010_0000;
+const FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS: u32 = 0x0040_0000;
+
+/// Sync state of a cloud file placeholder, e.g. OneDrive files on demand
+///
+/// Sizes of online only files are the size in the cloud rather than on disk,
+/// and reading them triggers a download.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloudStatus {
+    /// Only a placeholder is on disk and the contents are downloaded on access
+    OnlineOnly,
+    /// Downloaded, but may be freed up to an online only file again
+    Available,
+    /// Always kept on this device
+    Pinned,
+}
+
+impl CloudStatus {
+    /// Status from windows file attributes, `None` when the file isn't managed
+    /// by a cloud provider
+    pub fn from_attributes(attributes: u32) -> Option<Self> {
+        if attributes & FILE_ATTRIBUTE_PINNED != 0 {
+            Some(Self::Pinned)
+        } else if attributes
+            & (FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS
+                | FILE_ATTRIBUTE_RECALL_ON_OPEN
+                | FILE_ATTRIBUTE_OFFLINE)
+            != 0
+        {
+            Some(Self::OnlineOnly)
+        } else if attributes & FILE_ATTRIBUTE_UNPINNED != 0 {
+            Some(Self::Available)
+        } else {
+            None
+        }
+    }
+
+    /// Status of a file from its metadata, always `None` outside of windows
+    #[cfg(windows)]
+    pub fn of(meta: &Metadata) -> Option<Self> {
+        use std::os::windows::fs::MetadataExt;
+        Self::from_attributes(meta.file_attributes())
+    }
+
+    #[cfg(not(windows))]
+    pub fn of(_meta: &Metadata) -> Option<Self> {
+        None
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::OnlineOnly => "online-only",
+            Self::Available => "available",
+            Self::Pinned => "pinned",
+        }
+    }
+
+    /// Single column glyph shown in the cloud column
+    pub fn glyph(&self) -> char {
+        match self {
+            Self::OnlineOnly => '☁',
+            Self::Available => '○',
+            Self::Pinned => '●',
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn status_from_attributes() {
+        assert_eq!(CloudStatus::from_attributes(0x20), None);
+        assert_eq!(
+            CloudStatus::from_attributes(0x20 | FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS),
+            Some(CloudStatus::OnlineOnly)
+        );
+        assert_eq!(
+            CloudStatus::from_attributes(FILE_ATTRIBUTE_UNPINNED),
+            Some(CloudStatus::Available)
+        );
+        assert_eq!(
+            CloudStatus::from_attributes(FILE_ATTRIBUTE_PINNED | FILE_ATTRIBUTE_OFFLINE),
+            Some(CloudStatus::Pinned)
+        );
+    }
+}
@@ -0,0 +1,225 @@
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use regex::Regex;
+
+use crate::{filter::Filter, Entry};
+
+/// Places a `CODEOWNERS` file is looked for in a repository, in priority order
+pub const LOCATIONS: [&str; 3] = ["CODEOWNERS", ".github/CODEOWNERS", "docs/CODEOWNERS"];
+
+#[derive(Debug, Clone)]
+struct Rule {
+    /// Pattern as written with the leading and trailing `/` removed
+    pattern: String,
+    /// Whether the pattern only matches relative to the root
+    anchored: bool,
+    regex: Regex,
+    owners: Vec<String>,
+}
+
+/// Owners of the paths in a repository, parsed from its `CODEOWNERS` file
+///
+/// Patterns follow the gitignore rules GitHub uses and the last matching
+/// pattern wins. A pattern matching a directory also matches everything in it.
+#[derive(Debug, Clone, Default)]
+pub struct CodeOwners {
+    root: PathBuf,
+    rules: Vec<Rule>,
+}
+
+impl CodeOwners {
+    /// Parse the `CODEOWNERS` file of the nearest directory above `path` that
+    /// has one, `None` when there isn't any
+    pub fn find(path: &Path) -> Result<Option<Self>, String> {
+        let path = std::fs::canonicalize(path).map_err(|e| e.to_string())?;
+        for dir in path.ancestors() {
+            for location in LOCATIONS {
+                let file = dir.join(location);
+                if file.is_file() {
+                    let content = std::fs::read_to_string(&file).map_err(|e| e.to_string())?;
+                    let owners =
+                        Self::from_str(&content).map_err(|e| format!("{}: {e}", file.display()))?;
+                    return Ok(Some(owners.root(dir)));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Directory the patterns are relative to
+    pub fn root<P: Into<PathBuf>>(mut self, root: P) -> Self {
+        self.root = root.into();
+        self
+    }
+
+    /// Owners of a path, either absolute or relative to the root
+    ///
+    /// Empty when no pattern matches or the matching pattern has no owners.
+    pub fn owners(&self, path: &Path) -> &[String] {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.regex.is_match(&relative))
+            .map(|rule| rule.owners.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Whether a rule for `owner` could match something inside of `dir`
+    fn owns_within(&self, dir: &Path, owner: &str) -> bool {
+        let relative = dir.strip_prefix(&self.root).unwrap_or(dir);
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        let dir = relative
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>();
+        self.rules.iter().any(|rule| {
+            rule.owners.iter().any(|o| o.eq_ignore_ascii_case(owner))
+                && (!rule.anchored
+                    || could_contain(&rule.pattern.split('/').collect::<Vec<_>>(), &dir))
+        })
+    }
+}
+
+/// Whether an anchored pattern could match the directory made of `dir`
+/// segments or anything inside of it
+fn could_contain(pattern: &[&str], dir: &[&str]) -> bool {
+    match (pattern, dir) {
+        // The rest of the pattern can match below the directory, or the
+        // pattern already matched one of its parents
+        (_, []) | ([], _) => true,
+        (["**", rest @ ..], [_, below @ ..]) => {
+            could_contain(rest, dir) || could_contain(pattern, below)
+        }
+        ([segment, rest @ ..], [name, below @ ..]) => {
+            segment_matches(segment.as_bytes(), name.as_bytes()) && could_contain(rest, below)
+        }
+    }
+}
+
+/// Match a single path segment against a pattern segment with `*` and `?`
+fn segment_matches(pattern: &[u8], name: &[u8]) -> bool {
+    match (pattern, name) {
+        ([], []) => true,
+        ([b'*', rest @ ..], _) => {
+            segment_matches(rest, name)
+                || (!name.is_empty() && segment_matches(pattern, &name[1..]))
+        }
+        ([b'?', rest @ ..], [_, below @ ..]) => segment_matches(rest, below),
+        ([p, rest @ ..], [n, below @ ..]) => p == n && segment_matches(rest, below),
+        _ => false,
+    }
+}
+
+impl FromStr for CodeOwners {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut code_owners = CodeOwners::default();
+        for line in s.lines() {
+            let line = line.split_once('#').map_or(line, |(line, _)| line).trim();
+            let mut parts = line.split_whitespace();
+            let Some(pattern) = parts.next() else {
+                continue;
+            };
+
+            let trimmed = pattern.trim_start_matches('/').trim_end_matches('/');
+            // Patterns with a slash anywhere but the end are relative to the root
+            let anchored = pattern.trim_end_matches('/').contains('/');
+            let body = regex::escape(trimmed)
+                .replace(r"\*\*/", "\u{1}")
+                .replace(r"\*\*", "\u{0}")
+                .replace(r"\*", "[^/]*")
+                .replace(r"\?", "[^/]")
+                .replace('\u{0}', ".*")
+                .replace('\u{1}', "(.*/)?");
+            let prefix = if anchored { "" } else { "(.*/)?" };
+            let regex = Regex::new(&format!("^{prefix}{body}(/.*)?$"))
+                .map_err(|e| format!("invalid pattern '{pattern}': {e}"))?;
+
+            code_owners.rules.push(Rule {
+                pattern: trimmed.to_string(),
+                anchored,
+                regex,
+                owners: parts.map(str::to_string).collect(),
+            });
+        }
+        Ok(code_owners)
+    }
+}
+
+/// Keep entries owned by an owner, e.g. `@org/team`, and directories that may
+/// contain something they own
+#[derive(Debug, Clone)]
+pub struct OwnedBy {
+    code_owners: CodeOwners,
+    owner: String,
+}
+
+impl OwnedBy {
+    pub fn new<S: ToString>(code_owners: CodeOwners, owner: S) -> Self {
+        Self {
+            code_owners,
+            owner: owner.to_string(),
+        }
+    }
+}
+
+impl Filter for OwnedBy {
+    fn keep(&self, entry: &Entry) -> bool {
+        let owned = self
+            .code_owners
+            .owners(entry.path())
+            .iter()
+            .any(|owner| owner.eq_ignore_ascii_case(&self.owner));
+        owned || (entry.is_dir() && self.code_owners.owns_within(entry.path(), &self.owner))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn match_owners() {
+        let owners = CodeOwners::from_str(
+            "
+# Default owners
+*       @org/everyone
+*.rs    @org/rust # inline comment
+/docs/  @org/docs
+apps/**/web  @org/web
+build/
+",
+        )
+        .unwrap();
+
+        let of = |path: &str| owners.owners(Path::new(path)).to_vec();
+        assert_eq!(of("README.md"), ["@org/everyone"]);
+        assert_eq!(of("src/lib.rs"), ["@org/rust"]);
+        assert_eq!(of("docs"), ["@org/docs"]);
+        assert_eq!(of("docs/guide/intro.rs"), ["@org/docs"]);
+        assert_eq!(of("nested/docs/file"), ["@org/everyone"]);
+        assert_eq!(of("apps/shop/web/index.ts"), ["@org/web"]);
+        assert_eq!(of("apps/web"), ["@org/web"]);
+        assert!(of("build/out.rs").is_empty());
+
+        assert!(owners.owns_within(Path::new("apps"), "@org/web"));
+        assert!(owners.owns_within(Path::new("apps/shop"), "@org/web"));
+        assert!(owners.owns_within(Path::new("apps/shop/web"), "@org/web"));
+        assert!(!owners.owns_within(Path::new("lib/shop"), "@org/web"));
+        assert!(!owners.owns_within(Path::new("docsite"), "@org/docs"));
+        assert!(owners.owns_within(Path::new("docs/guide"), "@org/docs"));
+
+        let single = CodeOwners::from_str("apps/*/w?b @org/web").unwrap();
+        assert!(single.owns_within(Path::new("apps/shop"), "@org/web"));
+        assert!(single.owns_within(Path::new("apps/shop/web"), "@org/web"));
+        assert!(!single.owns_within(Path::new("apps/shop/api"), "@org/web"));
+        assert!(!owners.owns_within(Path::new("src"), "@org/docs"));
+        assert!(owners.owns_within(Path::new("src"), "@org/rust"));
+    }
+}
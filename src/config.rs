@@ -0,0 +1,473 @@
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use owo_colors::Style;
+
+use crate::{
+    format::Branches,
+    project::find_root,
+    sort::Chain,
+    style::{GroupMatch, SymlinkSize, ThemeGroup},
+};
+
+/// User configuration loaded from `<config dir>/xf/config.toml`, with a
+/// `.xf.toml` in the listed directory taking precedence
+///
+/// ```toml
+/// [sort]
+/// order = "dir,-modified"
+///
+/// [sort.weights]
+/// "README*" = -100
+/// "*.lock" = 10
+///
+/// [theme.ROOT]
+/// fg = "red"
+/// owner = "root"
+///
+/// [root]
+/// markers = [".git", "go.mod"]
+///
+/// [symlinks]
+/// size = "target"
+/// marker = "@"
+///
+/// [tree]
+/// branches = "ascii"
+///
+/// [profile.go]
+/// markers = ["go.mod"]
+///
+/// [profile.go.theme.GO_MODULE]
+/// fg = "yellow"
+/// filenames = ["go.mod", "go.sum"]
+/// ```
+#[derive(Default, Debug, Clone, PartialEq)]
+pub struct Config {
+    /// Sort order in the same format as `--sort`
+    pub sort: Option<Chain>,
+    /// Glob and weight pairs used by [`crate::sort::Weighted`] in the order
+    /// they are defined
+    pub weights: Vec<(String, i64)>,
+    /// Groups added in front of the default theme, see
+    /// [`crate::style::Colorizer::themed`]
+    pub theme: Vec<ThemeGroup>,
+    /// Markers used to find the project root for `--root`, see
+    /// [`crate::project::find_root`]
+    pub root_markers: Option<Vec<String>>,
+    /// What the size column shows for symbolic links
+    pub symlink_size: Option<SymlinkSize>,
+    /// Size column text for symbolic links, see
+    /// [`crate::style::Colorizer::symlink_marker`]
+    pub symlink_marker: Option<String>,
+    /// Glyphs used to draw the tree format
+    pub branches: Option<Branches>,
+    /// Project profiles added to, or replacing, the [`Profile::builtin`] ones
+    pub profiles: Vec<Profile>,
+}
+
+impl Config {
+    /// Location of the user's config file
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("xf").join("config.toml"))
+    }
+
+    /// Load the user's config file, falling back to the default config when it
+    /// doesn't exist
+    pub fn load() -> Result<Self, String> {
+        match Self::path() {
+            Some(path) if path.exists() => Self::try_from(path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Load the `.xf.toml` of a directory, `None` when it doesn't have one
+    pub fn load_local(dir: &Path) -> Result<Option<Self>, String> {
+        let path = dir.join(".xf.toml");
+        match path.exists() {
+            true => Self::try_from(path).map(Some),
+            false => Ok(None),
+        }
+    }
+
+    /// Settings of `local` override this config, its weights and theme groups
+    /// are matched first
+    pub fn merge(mut self, local: Config) -> Self {
+        self.weights.splice(0..0, local.weights);
+        self.theme.splice(0..0, local.theme);
+        self.sort = local.sort.or(self.sort);
+        self.root_markers = local.root_markers.or(self.root_markers);
+        self.symlink_size = local.symlink_size.or(self.symlink_size);
+        self.symlink_marker = local.symlink_marker.or(self.symlink_marker);
+        self.branches = local.branches.or(self.branches);
+        self.profiles.splice(0..0, local.profiles);
+        self
+    }
+
+    /// Theme groups of every profile that applies to `dir`, with the user's
+    /// profiles taking the place of built in ones with the same name
+    pub fn profile_theme(&self, dir: &Path) -> Vec<ThemeGroup> {
+        let mut profiles = self.profiles.clone();
+        for builtin in Profile::builtin() {
+            if !profiles.iter().any(|profile| profile.name == builtin.name) {
+                profiles.push(builtin);
+            }
+        }
+        profiles
+            .into_iter()
+            .filter(|profile| profile.detect(dir))
+            .flat_map(|profile| profile.theme)
+            .collect()
+    }
+}
+
+/// Theme groups enabled when the listed directory is inside a certain kind of
+/// project, e.g. dimming `target` in a cargo project
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    /// Files marking the project type, found in the listed directory or any of
+    /// its ancestors
+    pub markers: Vec<String>,
+    /// Groups matched after the user's theme and before the default one
+    pub theme: Vec<ThemeGroup>,
+}
+
+impl Profile {
+    pub fn new<S: ToString, I: IntoIterator<Item = M>, M: ToString>(name: S, markers: I) -> Self {
+        Self {
+            name: name.to_string(),
+            markers: markers.into_iter().map(|m| m.to_string()).collect(),
+            theme: Vec::new(),
+        }
+    }
+
+    pub fn group<S: ToString, I: IntoIterator<Item = GroupMatch>>(
+        mut self,
+        name: S,
+        matchers: I,
+        style: Style,
+    ) -> Self {
+        self.theme.push(ThemeGroup {
+            name: name.to_string(),
+            matchers: matchers.into_iter().collect(),
+            style,
+            icon: None,
+        });
+        self
+    }
+
+    /// Cargo, npm, and python profiles highlighting manifests and dimming
+    /// build output and dependencies
+    pub fn builtin() -> Vec<Self> {
+        let manifest = Style::default().yellow().bold();
+        let output = Style::default().dimmed();
+        vec![
+            Profile::new("cargo", ["Cargo.toml"])
+                .group(
+                    "CARGO_MANIFEST",
+                    [GroupMatch::filenames([
+                        "Cargo.toml",
+                        "build.rs",
+                        "rust-toolchain.toml",
+                    ])],
+                    manifest,
+                )
+                .group("CARGO_OUTPUT", [GroupMatch::filenames(["target"])], output),
+            Profile::new("npm", ["package.json"])
+                .group(
+                    "NPM_MANIFEST",
+                    [GroupMatch::filenames(["package.json", "tsconfig.json"])],
+                    manifest,
+                )
+                .group(
+                    "NPM_OUTPUT",
+                    [GroupMatch::filenames(["node_modules", "dist"])],
+                    output,
+                ),
+            Profile::new("python", ["pyproject.toml", "setup.py", "requirements.txt"])
+                .group(
+                    "PYTHON_MANIFEST",
+                    [GroupMatch::filenames([
+                        "pyproject.toml",
+                        "setup.py",
+                        "setup.cfg",
+                        "requirements.txt",
+                    ])],
+                    manifest,
+                )
+                .group(
+                    "PYTHON_OUTPUT",
+                    [
+                        GroupMatch::filenames([
+                            "__pycache__",
+                            ".venv",
+                            "venv",
+                            ".pytest_cache",
+                            ".mypy_cache",
+                            ".tox",
+                        ]),
+                        GroupMatch::extensions(["pyc"]),
+                    ],
+                    output,
+                ),
+        ]
+    }
+
+    /// Whether `dir` is inside a project of this type
+    pub fn detect(&self, dir: &Path) -> bool {
+        find_root(dir, &self.markers).is_some()
+    }
+}
+
+impl TryFrom<PathBuf> for Config {
+    type Error = String;
+
+    fn try_from(value: PathBuf) -> Result<Self, Self::Error> {
+        let content = std::fs::read_to_string(&value).map_err(|e| e.to_string())?;
+        Self::from_str(content.as_str()).map_err(|e| format!("{}: {e}", value.display()))
+    }
+}
+
+impl FromStr for Config {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let table = s.parse::<toml::Table>().map_err(|e| e.to_string())?;
+        let mut config = Config::default();
+
+        if let Some(sort) = table.get("sort") {
+            let sort = sort.as_table().ok_or("'sort' must be a table")?;
+            if let Some(order) = sort.get("order") {
+                let order = order.as_str().ok_or("'sort.order' must be a string")?;
+                config.sort =
+                    Some(Chain::from_str(order).map_err(|e| format!("'sort.order': {e}"))?);
+            }
+            if let Some(weights) = sort.get("weights") {
+                let weights = weights.as_table().ok_or("'sort.weights' must be a table")?;
+                for (pattern, weight) in weights {
+                    let weight = weight
+                        .as_integer()
+                        .ok_or_else(|| format!("weight for '{pattern}' must be an integer"))?;
+                    config.weights.push((pattern.clone(), weight));
+                }
+            }
+        }
+
+        if let Some(root) = table.get("root") {
+            let root = root.as_table().ok_or("'root' must be a table")?;
+            if let Some(markers) = root.get("markers") {
+                let markers = markers
+                    .as_array()
+                    .ok_or("'root.markers' must be an array")?
+                    .iter()
+                    .map(|marker| marker.as_str().map(str::to_string))
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or("'root.markers' must only contain strings")?;
+                config.root_markers = Some(markers);
+            }
+        }
+
+        if let Some(symlinks) = table.get("symlinks") {
+            let symlinks = symlinks.as_table().ok_or("'symlinks' must be a table")?;
+            if let Some(size) = symlinks.get("size") {
+                let size = size.as_str().ok_or("'symlinks.size' must be a string")?;
+                config.symlink_size = Some(SymlinkSize::from_str(size)?);
+            }
+            if let Some(marker) = symlinks.get("marker") {
+                let marker = marker
+                    .as_str()
+                    .ok_or("'symlinks.marker' must be a string")?;
+                config.symlink_marker = Some(marker.to_string());
+            }
+        }
+
+        if let Some(tree) = table.get("tree") {
+            let tree = tree.as_table().ok_or("'tree' must be a table")?;
+            if let Some(branches) = tree.get("branches") {
+                let branches = branches
+                    .as_str()
+                    .ok_or("'tree.branches' must be a string")?;
+                config.branches = Some(Branches::from_str(branches)?);
+            }
+        }
+
+        if let Some(profiles) = table.get("profile") {
+            let profiles = profiles.as_table().ok_or("'profile' must be a table")?;
+            for (name, profile) in profiles {
+                let profile = profile
+                    .as_table()
+                    .ok_or_else(|| format!("'profile.{name}' must be a table"))?;
+                let markers = profile
+                    .get("markers")
+                    .ok_or_else(|| format!("'profile.{name}.markers' is required"))?
+                    .as_array()
+                    .ok_or_else(|| format!("'profile.{name}.markers' must be an array"))?
+                    .iter()
+                    .map(|marker| marker.as_str())
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(|| format!("'profile.{name}.markers' must only contain strings"))?;
+
+                let mut parsed = Profile::new(name, markers);
+                if let Some(theme) = profile.get("theme") {
+                    let theme = theme
+                        .as_table()
+                        .ok_or_else(|| format!("'profile.{name}.theme' must be a table"))?;
+                    for (group_name, group) in theme {
+                        let group = group.as_table().ok_or_else(|| {
+                            format!("'profile.{name}.theme.{group_name}' must be a table")
+                        })?;
+                        parsed.theme.push(ThemeGroup::from_toml(group_name, group)?);
+                    }
+                }
+                config.profiles.push(parsed);
+            }
+        }
+
+        if let Some(theme) = table.get("theme") {
+            let theme = theme.as_table().ok_or("'theme' must be a table")?;
+            for (name, group) in theme {
+                let group = group
+                    .as_table()
+                    .ok_or_else(|| format!("'theme.{name}' must be a table"))?;
+                config.theme.push(ThemeGroup::from_toml(name, group)?);
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn parse_weights() {
+        let config = Config::from_str(
+            r#"
+[sort.weights]
+"README*" = -100
+"*.lock" = 10
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.weights,
+            vec![("README*".to_string(), -100), ("*.lock".to_string(), 10)]
+        );
+
+        assert!(Config::from_str("[sort.weights]\n\"*.rs\" = \"high\"").is_err());
+        assert_eq!(
+            Config::from_str("[root]\nmarkers = [\"go.mod\"]")
+                .unwrap()
+                .root_markers,
+            Some(vec!["go.mod".to_string()])
+        );
+
+        let config = Config::from_str("[symlinks]\nsize = \"target\"\nmarker = \"@\"").unwrap();
+        assert_eq!(config.symlink_size, Some(SymlinkSize::Target));
+        assert_eq!(config.symlink_marker.as_deref(), Some("@"));
+        assert!(Config::from_str("[symlinks]\nsize = \"both\"").is_err());
+
+        assert_eq!(
+            Config::from_str("[tree]\nbranches = \"+,`,!\"")
+                .unwrap()
+                .branches,
+            Some(Branches::new("+", "`", "!"))
+        );
+
+        assert_eq!(Config::from_str("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn parse_sort_order() {
+        let config = Config::from_str("[sort]\norder = \"-size, name\"").unwrap();
+        let order = config.sort.unwrap();
+        assert_eq!(order, "-size,name".parse().unwrap());
+        assert_eq!(order.to_string(), "-size,name");
+        assert!(Config::from_str("[sort]\norder = \"colour\"").is_err());
+
+        let local =
+            Config::from_str("[sort]\norder = \"none\"\n[sort.weights]\n\"*.md\" = 1").unwrap();
+        let merged = Config::from_str("[sort.weights]\n\"*.rs\" = 2")
+            .unwrap()
+            .merge(local);
+        assert_eq!(merged.sort, Some(Chain::new()));
+        assert_eq!(
+            merged.weights,
+            vec![("*.md".to_string(), 1), ("*.rs".to_string(), 2)]
+        );
+    }
+
+    #[test]
+    fn parse_theme() {
+        use crate::style::GroupMatch;
+
+        let config = Config::from_str(
+            r##"
+[theme.ROOT]
+fg = "red"
+owner = ["root"]
+
+[theme.WORLD_WRITABLE]
+bg = "#ffff00"
+permission = ["o+w", "ug+x"]
+"##,
+        )
+        .unwrap();
+
+        assert_eq!(config.theme.len(), 2);
+        assert_eq!(config.theme[0].name, "ROOT");
+        assert_eq!(config.theme[0].matchers, vec![GroupMatch::owners(["root"])]);
+        assert_eq!(
+            config.theme[1].matchers,
+            vec![GroupMatch::Permission([0o002, 0o110].into())]
+        );
+
+        assert!(Config::from_str("[theme.BAD]\nfg = \"rainbow\"").is_err());
+        assert!(Config::from_str("[theme.BAD]\npermission = \"1777\"").is_err());
+    }
+
+    #[test]
+    fn detect_profiles() {
+        let base = TempDir::new("profile");
+        std::fs::create_dir_all(base.join("src")).unwrap();
+        std::fs::write(base.join("go.mod"), "").unwrap();
+        std::fs::write(base.join("package.json"), "{}").unwrap();
+
+        let config = Config::from_str(
+            r#"
+[profile.go]
+markers = ["go.mod"]
+
+[profile.go.theme.GO_MODULE]
+fg = "yellow"
+filenames = ["go.mod"]
+
+[profile.npm]
+markers = ["package.json"]
+"#,
+        )
+        .unwrap();
+        let names = |dir: &Path| {
+            config
+                .profile_theme(dir)
+                .into_iter()
+                .map(|group| group.name)
+                .collect::<Vec<_>>()
+        };
+        // The user's npm profile replaces the built in one
+        assert_eq!(names(&base.join("src")), ["GO_MODULE"]);
+        assert!(Config::default()
+            .profile_theme(&base)
+            .iter()
+            .any(|group| group.name == "NPM_OUTPUT"));
+
+        assert!(Config::from_str("[profile.go]\ntheme = {}").is_err());
+    }
+}
@@ -0,0 +1,169 @@
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::Path,
+};
+
+/// Number of bytes sniffed from the start of a file
+pub const SNIFF_SIZE: usize = 8192;
+
+/// Default largest file in bytes that is read for searching or counting lines
+pub const MAX_SIZE: u64 = 1024 * 1024;
+
+/// Extensions that are treated as binary when the file can't be read
+const BINARY_EXTENSIONS: &[&str] = &[
+    "exe", "dll", "so", "dylib", "a", "lib", "o", "obj", "rlib", "pdb", "bin", "class", "jar",
+    "wasm", "zip", "gz", "tgz", "xz", "bz2", "7z", "rar", "zst", "png", "jpg", "jpeg", "gif",
+    "webp", "avif", "ico", "bmp", "tiff", "pdf", "mp3", "mp4", "mov", "mkv", "wav", "flac", "ogg",
+    "ttf", "otf", "woff", "woff2", "sqlite", "db",
+];
+
+/// Text encoding detected from a byte order mark or the byte layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl Encoding {
+    /// Encoding of the bytes along with the length of the byte order mark
+    ///
+    /// Without a BOM, UTF-16LE is still detected when every other byte of the
+    /// sniffed block is NUL, which is how mostly ASCII files written by
+    /// PowerShell and other windows tools look.
+    pub fn detect(bytes: &[u8]) -> (Self, usize) {
+        match bytes {
+            [0xef, 0xbb, 0xbf, ..] => (Self::Utf8, 3),
+            [0xff, 0xfe, ..] => (Self::Utf16Le, 2),
+            [0xfe, 0xff, ..] => (Self::Utf16Be, 2),
+            _ => {
+                let sniff = &bytes[..bytes.len().min(SNIFF_SIZE)];
+                if sniff.len() >= 2
+                    && sniff
+                        .chunks_exact(2)
+                        .all(|pair| pair[0] != 0 && pair[1] == 0)
+                {
+                    (Self::Utf16Le, 0)
+                } else {
+                    (Self::Utf8, 0)
+                }
+            }
+        }
+    }
+}
+
+/// Decode text in any of the supported [`Encoding`]s
+///
+/// Returns `None` when the bytes look like binary data.
+pub fn decode(bytes: &[u8]) -> Option<String> {
+    let (encoding, bom) = Encoding::detect(bytes);
+    let bytes = &bytes[bom..];
+    match encoding {
+        Encoding::Utf8 => {
+            if bytes[..bytes.len().min(SNIFF_SIZE)].contains(&0) {
+                return None;
+            }
+            Some(String::from_utf8_lossy(bytes).into_owned())
+        }
+        Encoding::Utf16Le | Encoding::Utf16Be => {
+            let units = bytes
+                .chunks_exact(2)
+                .map(|pair| match encoding {
+                    Encoding::Utf16Le => u16::from_le_bytes([pair[0], pair[1]]),
+                    _ => u16::from_be_bytes([pair[0], pair[1]]),
+                })
+                .collect::<Vec<_>>();
+            Some(String::from_utf16_lossy(&units))
+        }
+    }
+}
+
+/// Whether the file looks like binary data
+///
+/// Sniffs the first [`SNIFF_SIZE`] bytes for a NUL byte, ignoring UTF-16 text,
+/// and falls back to the extension when the file can't be read.
+pub fn is_binary(path: &Path) -> bool {
+    let mut buffer = [0u8; SNIFF_SIZE];
+    match File::open(path).and_then(|mut file| file.read(&mut buffer)) {
+        Ok(read) => {
+            let sniff = &buffer[..read];
+            Encoding::detect(sniff).0 == Encoding::Utf8 && sniff.contains(&0)
+        }
+        Err(_) => path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| BINARY_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+            .unwrap_or_default(),
+    }
+}
+
+/// Decoded contents of a text file up to `max_size` bytes, see [`decode`]
+///
+/// Returns `None` for binary files and files larger than `max_size`.
+pub fn read_text(path: &Path, max_size: u64) -> io::Result<Option<String>> {
+    let file = File::open(path)?;
+    if file.metadata()?.len() > max_size {
+        return Ok(None);
+    }
+
+    let mut bytes = Vec::new();
+    file.take(max_size).read_to_end(&mut bytes)?;
+    Ok(decode(&bytes))
+}
+
+/// Number of lines in a text file up to `max_size` bytes
+///
+/// A trailing line without a newline is still counted.
+pub fn count_lines(path: &Path, max_size: u64) -> Option<usize> {
+    let text = read_text(path, max_size).ok()??;
+    Some(text.lines().count())
+}
+
+/// Map `f` over the paths across all available threads, keeping the order
+pub fn parallel_map<T, F>(paths: &[&Path], f: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(&Path) -> T + Sync,
+{
+    if paths.is_empty() {
+        return Vec::new();
+    }
+
+    let threads = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+    let chunk = paths.len().div_ceil(threads);
+    let f = &f;
+
+    std::thread::scope(|scope| {
+        paths
+            .chunks(chunk)
+            .map(|paths| scope.spawn(move || paths.iter().map(|p| f(p)).collect::<Vec<_>>()))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("worker thread panicked"))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn decode_encodings() {
+        let utf16 = "serde = 1\r\n"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect::<Vec<_>>();
+        assert_eq!(decode(&utf16).as_deref(), Some("serde = 1\r\n"));
+
+        let mut bom = vec![0xff, 0xfe];
+        bom.extend_from_slice(&utf16);
+        assert_eq!(decode(&bom).as_deref(), Some("serde = 1\r\n"));
+
+        assert_eq!(decode(b"\xef\xbb\xbfserde").as_deref(), Some("serde"));
+        assert_eq!(decode(b"\x7fELF\x02\x01\x00\x00"), None);
+    }
+}
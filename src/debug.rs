@@ -0,0 +1,188 @@
+use std::{
+    fs::{self, Metadata},
+    io::{self, Write},
+    path::Path,
+    time::SystemTime,
+};
+
+use chrono::{DateTime, Local};
+use owo_colors::OwoColorize;
+
+use crate::{filter::Filter, style::Colorizer, Entry};
+
+/// Print everything xf knows about a single entry, for `--debug-entry`
+///
+/// The entry is read without following symbolic links unless `dereference`
+/// is set, the same way a directory listing reads it. Each filter is listed
+/// with whether it would keep the entry.
+pub fn print_entry<W: Write>(
+    writer: &mut W,
+    path: &Path,
+    dereference: bool,
+    colorizer: &Colorizer,
+    filters: &[(&str, &dyn Filter)],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = std::path::absolute(path)?;
+    let mut entry = Entry::with_metadata(path.clone(), fs::symlink_metadata(&path)?)?;
+    if dereference {
+        entry = entry.dereference();
+    }
+    let meta = entry.metadata();
+
+    section(writer, "entry")?;
+    field(writer, "path", entry.path().display())?;
+    field(
+        writer,
+        "canonical",
+        fs::canonicalize(&path)
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|err| format!("error: {err}")),
+    )?;
+    field(writer, "type", format!("{:?}", entry.etype()))?;
+    field(writer, "symlink", meta.is_symlink())?;
+    if let Some(target) = entry.link_target() {
+        field(writer, "link target", target.display())?;
+        field(writer, "broken link", entry.is_broken_link())?;
+    }
+
+    section(writer, "metadata")?;
+    field(writer, "len", meta.len())?;
+    field(writer, "allocated", optional(entry.allocated_size()))?;
+    field(writer, "readonly", meta.permissions().readonly())?;
+    platform_metadata(writer, meta)?;
+    field(writer, "hard links", entry.hard_links())?;
+    field(
+        writer,
+        "file id",
+        optional(
+            entry
+                .file_id()
+                .map(|id| format!("{}:{}", id.device, id.inode)),
+        ),
+    )?;
+
+    section(writer, "timestamps")?;
+    field(writer, "modified", timestamp(meta.modified()))?;
+    field(writer, "accessed", timestamp(meta.accessed()))?;
+    field(writer, "created", timestamp(meta.created()))?;
+
+    section(writer, "permissions")?;
+    let permissions = entry.permissions();
+    field(writer, "source", permission_source(&path))?;
+    field(
+        writer,
+        "mode",
+        format!("{permissions} ({:03o})", permissions.mode()),
+    )?;
+    field(writer, "user", &permissions.user().name)?;
+    field(writer, "group", &permissions.group().name)?;
+    field(writer, "executable", entry.is_executable())?;
+
+    section(writer, "attributes")?;
+    let attributes = permissions.attributes();
+    field(writer, "hidden", entry.is_hidden())?;
+    field(writer, "hidden attribute", attributes.hidden)?;
+    field(writer, "system", attributes.system)?;
+    field(writer, "readonly attribute", attributes.readonly)?;
+    field(writer, "archive", attributes.archivable)?;
+    field(
+        writer,
+        "cloud",
+        entry
+            .cloud_status()
+            .map(|status| status.name())
+            .unwrap_or("none"),
+    )?;
+    field(writer, "binary", entry.is_binary())?;
+
+    section(writer, "style")?;
+    field(
+        writer,
+        "group",
+        colorizer.classify(&entry).unwrap_or("none"),
+    )?;
+    field(writer, "rendered", colorizer.file(&entry))?;
+
+    section(writer, "filters")?;
+    let entries = std::slice::from_ref(&entry);
+    let mut kept = true;
+    for (name, filter) in filters {
+        filter.prepare(entries);
+        let keep = filter.keep(&entry);
+        kept &= keep;
+        field(writer, name, verdict(keep))?;
+    }
+    field(writer, "listed", verdict(kept))?;
+    Ok(())
+}
+
+fn section<W: Write>(writer: &mut W, title: &str) -> io::Result<()> {
+    writeln!(writer, "{}", title.bold())
+}
+
+fn field<W: Write, D: std::fmt::Display>(writer: &mut W, name: &str, value: D) -> io::Result<()> {
+    writeln!(writer, "  {:<20}{value}", name.dimmed())
+}
+
+fn optional<D: ToString>(value: Option<D>) -> String {
+    value
+        .map(|value| value.to_string())
+        .unwrap_or("unsupported".to_string())
+}
+
+fn verdict(keep: bool) -> String {
+    match keep {
+        true => "kept".green().to_string(),
+        false => "discarded".red().to_string(),
+    }
+}
+
+/// Local time along with the raw seconds since the unix epoch
+fn timestamp(time: io::Result<SystemTime>) -> String {
+    match time {
+        Ok(time) => {
+            let raw = time
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map(|since| format!("{}.{:09}", since.as_secs(), since.subsec_nanos()))
+                .unwrap_or("before epoch".to_string());
+            format!("{} ({raw})", DateTime::<Local>::from(time).to_rfc3339())
+        }
+        Err(err) => format!("unsupported: {err}"),
+    }
+}
+
+/// Where [`crate::permission::Perms`] are read from for a path
+fn permission_source(path: &Path) -> &'static str {
+    match (cfg!(windows), fs::metadata(path).is_ok()) {
+        (true, _) => "access control list",
+        (false, true) => "mode bits of the target",
+        (false, false) => "mode bits of the link, target is missing",
+    }
+}
+
+#[cfg(unix)]
+fn platform_metadata<W: Write>(writer: &mut W, meta: &Metadata) -> io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+    field(writer, "st_mode", format!("{:o}", meta.mode()))?;
+    field(writer, "uid", meta.uid())?;
+    field(writer, "gid", meta.gid())?;
+    field(writer, "dev", meta.dev())?;
+    field(writer, "ino", meta.ino())?;
+    field(writer, "blocks", meta.blocks())?;
+    field(writer, "block size", meta.blksize())
+}
+
+#[cfg(windows)]
+fn platform_metadata<W: Write>(writer: &mut W, meta: &Metadata) -> io::Result<()> {
+    use std::os::windows::fs::MetadataExt;
+    field(
+        writer,
+        "file attributes",
+        format!("{:#010x}", meta.file_attributes()),
+    )
+}
+
+#[cfg(not(any(unix, windows)))]
+fn platform_metadata<W: Write>(_writer: &mut W, _meta: &Metadata) -> io::Result<()> {
+    Ok(())
+}
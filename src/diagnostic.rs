@@ -0,0 +1,37 @@
+use std::{fmt::Display, io, path::PathBuf};
+
+use serde::Serialize;
+
+/// Why a recursive listing didn't descend into a directory
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum Diagnostic {
+    /// A followed link points back at one of its own ancestors, the target is
+    /// relative to the link, `../..`
+    Cycle { target: PathBuf },
+    /// The directory is at the max depth
+    MaxDepth,
+    /// The directory couldn't be read
+    Unreadable { reason: String },
+}
+
+impl Diagnostic {
+    pub fn unreadable(err: &(dyn std::error::Error + 'static)) -> Self {
+        let reason = match err.downcast_ref::<io::Error>().map(|e| e.kind()) {
+            Some(io::ErrorKind::PermissionDenied) => "permission denied".to_string(),
+            Some(io::ErrorKind::NotFound) => "not found".to_string(),
+            _ => err.to_string(),
+        };
+        Self::Unreadable { reason }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Cycle { target } => write!(f, "cycle back to {}", target.display()),
+            Self::MaxDepth => f.write_str("max depth reached"),
+            Self::Unreadable { reason } => f.write_str(reason),
+        }
+    }
+}
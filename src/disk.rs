@@ -0,0 +1,445 @@
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use owo_colors::{colors::xterm::Gray, OwoColorize, Style};
+
+use crate::{
+    style::{humansize, Spacer},
+    text,
+};
+
+/// A mounted volume, a drive letter on windows and a mount point elsewhere
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct Disk {
+    pub root: PathBuf,
+    pub label: String,
+    pub file_system: String,
+    /// Total size in bytes
+    pub total: u64,
+    /// Bytes available to the current user
+    pub free: u64,
+}
+
+impl Disk {
+    pub fn used(&self) -> u64 {
+        self.total.saturating_sub(self.free)
+    }
+
+    /// Fraction of the disk that is in use from `0.0` to `1.0`
+    pub fn usage(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.used() as f64 / self.total as f64
+        }
+    }
+}
+
+/// All of the drives/volumes available on the system
+pub fn drives() -> io::Result<Vec<Disk>> {
+    #[cfg(target_os = "windows")]
+    return win32::drives();
+
+    #[cfg(target_os = "linux")]
+    return unix::mounts();
+
+    #[cfg(target_os = "macos")]
+    return unix::mounts();
+}
+
+/// Space of the file system that contains `path`
+///
+/// `root` is set to `path` and the label and file system are left empty.
+pub fn usage<P: AsRef<Path>>(path: P) -> io::Result<Disk> {
+    #[cfg(target_os = "windows")]
+    let (total, free) = win32::disk_free_space(path.as_ref())?;
+
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    let (total, free) = unix::statvfs(path.as_ref())?;
+
+    Ok(Disk {
+        root: path.as_ref().to_path_buf(),
+        total,
+        free,
+        ..Default::default()
+    })
+}
+
+/// Colored bar showing how full a disk is
+pub fn usage_bar(disk: &Disk, width: usize) -> String {
+    let usage = disk.usage();
+    let filled = ((usage * width as f64).round() as usize).min(width);
+    let style = if usage >= 0.9 {
+        Style::default().red()
+    } else if usage >= 0.75 {
+        Style::default().yellow()
+    } else {
+        Style::default().green()
+    };
+    format!(
+        "{}{}",
+        "━".repeat(filled).style(style),
+        "━".repeat(width - filled).dimmed()
+    )
+}
+
+/// Footer line summarizing the space of a disk
+pub fn usage_footer(disk: &Disk) -> String {
+    format!(
+        "{} {} used, {} free of {} ({}%)",
+        usage_bar(disk, 20),
+        humansize(disk.used()),
+        humansize(disk.free),
+        humansize(disk.total),
+        (disk.usage() * 100.0).round() as u64,
+    )
+}
+
+/// Print a table of drives with their label, file system, and usage
+pub fn print_drives<W: io::Write>(out: &mut W, disks: &[Disk]) -> io::Result<()> {
+    let root_width = disks
+        .iter()
+        .map(|d| text::width(&d.root.display().to_string()))
+        .max()
+        .unwrap_or_default();
+    let label_width = disks
+        .iter()
+        .map(|d| text::width(&d.label))
+        .max()
+        .unwrap_or_default();
+    let fs_width = disks
+        .iter()
+        .map(|d| d.file_system.len())
+        .max()
+        .unwrap_or_default();
+
+    for disk in disks {
+        let root = disk.root.display().to_string();
+        let used = humansize(disk.used());
+        let total = humansize(disk.total);
+        writeln!(
+            out,
+            "{}{}  {}{}  {}{}  {} {}{} / {}{} {:>3}%",
+            root.blue(),
+            (0..root_width.saturating_sub(text::width(&root))).spacer(),
+            disk.label,
+            (0..label_width.saturating_sub(text::width(&disk.label))).spacer(),
+            disk.file_system.fg::<Gray>(),
+            (0..fs_width.saturating_sub(disk.file_system.len())).spacer(),
+            usage_bar(disk, 20),
+            (0..4usize.saturating_sub(used.len())).spacer(),
+            used.fg::<Gray>(),
+            (0..4usize.saturating_sub(total.len())).spacer(),
+            total.fg::<Gray>(),
+            (disk.usage() * 100.0).round() as u64,
+        )?;
+    }
+    Ok(())
+}
+
+/// Recursive sizes of directories, like `du`
+///
+/// Every directory below the first one asked for is remembered, so a tree
+/// that asks for each nested directory in turn only walks the disk once.
+/// Symbolic links are counted by their own length and never followed.
+#[derive(Default, Debug)]
+pub struct DirSizes(Mutex<HashMap<PathBuf, u64>>);
+
+impl DirSizes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Total size in bytes of the files below `path`, unreadable directories
+    /// count as empty
+    ///
+    /// The directories directly below `path` are walked in parallel.
+    pub fn size(&self, path: &Path) -> u64 {
+        if let Some(size) = self.cached(path) {
+            return size;
+        }
+        let (files, dirs) = read_sizes(path);
+        let dirs = dirs.iter().map(PathBuf::as_path).collect::<Vec<_>>();
+        let size = files
+            + crate::content::parallel_map(&dirs, |dir| self.walk(dir))
+                .into_iter()
+                .sum::<u64>();
+        self.remember(path, size)
+    }
+
+    fn walk(&self, path: &Path) -> u64 {
+        if let Some(size) = self.cached(path) {
+            return size;
+        }
+        let (files, dirs) = read_sizes(path);
+        let size = files + dirs.iter().map(|dir| self.walk(dir)).sum::<u64>();
+        self.remember(path, size)
+    }
+
+    fn cached(&self, path: &Path) -> Option<u64> {
+        self.0.lock().ok()?.get(path).copied()
+    }
+
+    fn remember(&self, path: &Path, size: u64) -> u64 {
+        if let Ok(mut sizes) = self.0.lock() {
+            sizes.insert(path.to_path_buf(), size);
+        }
+        size
+    }
+}
+
+/// Total size of the files directly in `path` along with its directories
+fn read_sizes(path: &Path) -> (u64, Vec<PathBuf>) {
+    let mut files = 0;
+    let mut dirs = Vec::new();
+    for entry in std::fs::read_dir(path).into_iter().flatten().flatten() {
+        match entry.metadata() {
+            Ok(meta) if meta.is_dir() => dirs.push(entry.path()),
+            Ok(meta) => files += meta.len(),
+            Err(_) => {}
+        }
+    }
+    (files, dirs)
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+mod unix {
+    use std::{ffi::CString, io, os::unix::ffi::OsStrExt, path::Path};
+
+    use super::Disk;
+
+    /// Size and free space of the file system containing `path`
+    pub fn statvfs(path: &Path) -> io::Result<(u64, u64)> {
+        let path = CString::new(path.as_os_str().as_bytes())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+        if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        #[allow(clippy::unnecessary_cast)]
+        Ok((
+            stat.f_blocks as u64 * stat.f_frsize as u64,
+            stat.f_bavail as u64 * stat.f_frsize as u64,
+        ))
+    }
+
+    /// Virtual file systems that don't represent storage
+    #[cfg(target_os = "linux")]
+    const PSEUDO: &[&str] = &[
+        "proc",
+        "sysfs",
+        "devtmpfs",
+        "devpts",
+        "tmpfs",
+        "cgroup",
+        "cgroup2",
+        "securityfs",
+        "pstore",
+        "debugfs",
+        "tracefs",
+        "configfs",
+        "fusectl",
+        "mqueue",
+        "hugetlbfs",
+        "binfmt_misc",
+        "autofs",
+        "bpf",
+        "nsfs",
+        "efivarfs",
+        "rpc_pipefs",
+        "squashfs",
+    ];
+
+    /// Label of a block device from `/dev/disk/by-label`
+    #[cfg(target_os = "linux")]
+    fn label(device: &str) -> Option<String> {
+        let device = dunce::canonicalize(device).ok()?;
+        std::fs::read_dir("/dev/disk/by-label")
+            .ok()?
+            .filter_map(Result::ok)
+            .find(|link| dunce::canonicalize(link.path()).ok().as_ref() == Some(&device))
+            .map(|link| link.file_name().to_string_lossy().replace("\\x20", " "))
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn mounts() -> io::Result<Vec<Disk>> {
+        let mut disks = Vec::new();
+        for line in std::fs::read_to_string("/proc/mounts")?.lines() {
+            let mut parts = line.split_whitespace();
+            let (Some(device), Some(root), Some(file_system)) =
+                (parts.next(), parts.next(), parts.next())
+            else {
+                continue;
+            };
+
+            if PSEUDO.contains(&file_system) {
+                continue;
+            }
+
+            // Spaces in mount points are octal escaped
+            let root = root.replace("\\040", " ");
+            let Ok((total, free)) = statvfs(Path::new(&root)) else {
+                continue;
+            };
+            if total == 0 {
+                continue;
+            }
+
+            disks.push(Disk {
+                label: label(device).unwrap_or_else(|| device.to_string()),
+                root: root.into(),
+                file_system: file_system.to_string(),
+                total,
+                free,
+            });
+        }
+        Ok(disks)
+    }
+
+    #[cfg(target_os = "macos")]
+    pub fn mounts() -> io::Result<Vec<Disk>> {
+        use std::ffi::CStr;
+
+        let mut buffer: *mut libc::statfs = std::ptr::null_mut();
+        let count = unsafe { libc::getmntinfo(&mut buffer, libc::MNT_NOWAIT) };
+        if count <= 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mounts = unsafe { std::slice::from_raw_parts(buffer, count as usize) };
+        Ok(mounts
+            .iter()
+            .filter(|m| m.f_blocks > 0)
+            .map(|m| {
+                let root = unsafe { CStr::from_ptr(m.f_mntonname.as_ptr()) };
+                let device = unsafe { CStr::from_ptr(m.f_mntfromname.as_ptr()) };
+                let file_system = unsafe { CStr::from_ptr(m.f_fstypename.as_ptr()) };
+                Disk {
+                    root: root.to_string_lossy().to_string().into(),
+                    label: device.to_string_lossy().to_string(),
+                    file_system: file_system.to_string_lossy().to_string(),
+                    total: m.f_blocks * m.f_bsize as u64,
+                    free: m.f_bavail * m.f_bsize as u64,
+                }
+            })
+            .collect())
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod win32 {
+    use std::{io, os::windows::ffi::OsStrExt, path::Path};
+
+    use windows::{
+        core::PCWSTR,
+        Win32::Storage::FileSystem::{
+            GetDiskFreeSpaceExW, GetLogicalDrives, GetVolumeInformationW,
+        },
+    };
+
+    use super::Disk;
+
+    fn from_wide(buffer: &[u16]) -> String {
+        String::from_utf16_lossy(
+            &buffer[..buffer.iter().position(|v| *v == 0).unwrap_or(buffer.len())],
+        )
+    }
+
+    pub fn disk_free_space(path: &Path) -> io::Result<(u64, u64)> {
+        let wide = path
+            .as_os_str()
+            .encode_wide()
+            .chain([0])
+            .collect::<Vec<_>>();
+        let mut free = 0u64;
+        let mut total = 0u64;
+        unsafe {
+            GetDiskFreeSpaceExW(
+                PCWSTR::from_raw(wide.as_ptr()),
+                Some(std::ptr::addr_of_mut!(free)),
+                Some(std::ptr::addr_of_mut!(total)),
+                None,
+            )
+        }
+        .map_err(io::Error::other)?;
+        Ok((total, free))
+    }
+
+    pub fn drives() -> io::Result<Vec<Disk>> {
+        let mask = unsafe { GetLogicalDrives() };
+        if mask == 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut disks = Vec::new();
+        for (i, letter) in ('A'..='Z').enumerate() {
+            if mask & (1 << i) == 0 {
+                continue;
+            }
+
+            let root = format!("{letter}:\\");
+            let wide = root.encode_utf16().chain([0]).collect::<Vec<_>>();
+
+            let mut label = [0u16; 261];
+            let mut file_system = [0u16; 261];
+            // Drives without media, e.g. empty card readers, fail here and are
+            // still listed without a label
+            let _ = unsafe {
+                GetVolumeInformationW(
+                    PCWSTR::from_raw(wide.as_ptr()),
+                    Some(&mut label),
+                    None,
+                    None,
+                    None,
+                    Some(&mut file_system),
+                )
+            };
+
+            let mut free = 0u64;
+            let mut total = 0u64;
+            let _ = unsafe {
+                GetDiskFreeSpaceExW(
+                    PCWSTR::from_raw(wide.as_ptr()),
+                    Some(std::ptr::addr_of_mut!(free)),
+                    Some(std::ptr::addr_of_mut!(total)),
+                    None,
+                )
+            };
+
+            disks.push(Disk {
+                root: root.into(),
+                label: from_wide(&label),
+                file_system: from_wide(&file_system),
+                total,
+                free,
+            });
+        }
+        Ok(disks)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn recursive_dir_sizes() {
+        let base = TempDir::new("dir-sizes");
+        std::fs::create_dir_all(base.join("a").join("b")).unwrap();
+        std::fs::create_dir_all(base.join("empty")).unwrap();
+        std::fs::write(base.join("top"), "x".repeat(100)).unwrap();
+        std::fs::write(base.join("a").join("f"), "x".repeat(20)).unwrap();
+        std::fs::write(base.join("a").join("b").join("g"), "x".repeat(3)).unwrap();
+
+        let sizes = DirSizes::new();
+        assert_eq!(sizes.size(&base), 123);
+        assert_eq!(sizes.cached(&base.join("a")), Some(23));
+        assert_eq!(sizes.size(&base.join("a").join("b")), 3);
+        assert_eq!(sizes.size(&base.join("empty")), 0);
+    }
+}
@@ -0,0 +1,145 @@
+use std::{
+    io::Read,
+    path::Path,
+    process::{Command, Stdio},
+    time::{Duration, Instant},
+};
+
+use crate::content;
+
+/// A list column filled in with the output of a command run for each entry,
+/// `git log -1 --format=%ar {}`
+///
+/// `{}` is replaced with the quoted path of the entry, or the path is added
+/// to the end when there is no `{}`. Commands run through the shell in the
+/// directory of the entry, across all available threads.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecColumn {
+    command: String,
+    timeout: Duration,
+}
+
+impl ExecColumn {
+    pub fn new<S: Into<String>>(command: S) -> Self {
+        Self {
+            command: command.into(),
+            timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// Kill commands that run longer than `timeout`, defaults to 5 seconds
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// First line the command prints for `path`, `None` when it fails, times
+    /// out, or prints nothing
+    pub fn run(&self, path: &Path) -> Option<String> {
+        let mut child = self
+            .command(path)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+
+        // Read on another thread so a command that prints a lot doesn't block
+        // on a full pipe while waiting for it to exit
+        let mut stdout = child.stdout.take()?;
+        let reader = std::thread::spawn(move || {
+            let mut out = Vec::new();
+            stdout.read_to_end(&mut out).map(|_| out)
+        });
+
+        let deadline = Instant::now() + self.timeout;
+        let status = loop {
+            match child.try_wait().ok()? {
+                Some(status) => break status,
+                None if Instant::now() >= deadline => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return None;
+                }
+                None => std::thread::sleep(Duration::from_millis(5)),
+            }
+        };
+        let out = reader.join().ok()?.ok()?;
+        if !status.success() {
+            return None;
+        }
+        String::from_utf8_lossy(&out)
+            .lines()
+            .next()
+            .map(|line| line.trim_end().to_string())
+            .filter(|line| !line.is_empty())
+    }
+
+    /// Output for each path in order, see [`ExecColumn::run`]
+    pub fn run_all(&self, paths: &[&Path]) -> Vec<Option<String>> {
+        content::parallel_map(paths, |path| self.run(path))
+    }
+
+    fn command(&self, path: &Path) -> Command {
+        let quoted = quote(&path.display().to_string());
+        let script = match self.command.contains("{}") {
+            true => self.command.replace("{}", &quoted),
+            false => format!("{} {quoted}", self.command),
+        };
+
+        #[cfg(windows)]
+        let mut command = {
+            let mut command = Command::new("cmd");
+            command.arg("/C").arg(script);
+            command
+        };
+        #[cfg(not(windows))]
+        let mut command = {
+            let mut command = Command::new("sh");
+            command.arg("-c").arg(script);
+            command
+        };
+        if let Some(parent) = path.parent().filter(|parent| parent.is_dir()) {
+            command.current_dir(parent);
+        }
+        command
+    }
+}
+
+/// `text` quoted as a single argument for the shell
+#[cfg(not(windows))]
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+#[cfg(windows)]
+fn quote(text: &str) -> String {
+    format!("\"{text}\"")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[cfg(unix)]
+    #[test]
+    fn first_line_of_output() {
+        let base = TempDir::new("exec");
+        let file = base.join("it's here.txt");
+        std::fs::write(&file, "first\nsecond\n").unwrap();
+
+        assert_eq!(
+            ExecColumn::new("cat {}").run(&file).as_deref(),
+            Some("first")
+        );
+        assert_eq!(ExecColumn::new("wc -c <").run(&file).as_deref(), Some("13"));
+        assert_eq!(ExecColumn::new("false").run(&file), None);
+        assert_eq!(
+            ExecColumn::new("sleep 5; echo {}")
+                .timeout(Duration::from_millis(50))
+                .run(&file),
+            None
+        );
+    }
+}
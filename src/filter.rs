@@ -1,10 +1,48 @@
-use std::fmt::Debug;
-
-use crate::Entry;
+use std::{
+    fmt::Debug,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use hashbrown::HashMap;
+
+use crate::{content, git::Repos, Entry};
+
+/// Relative cost of evaluating a filter
+///
+/// Combinators such as [`And`] and [`All`] evaluate cheaper filters first so
+/// expensive ones only see the entries that are left.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Cost {
+    /// Only looks at the name, type, or already loaded metadata of the entry
+    #[default]
+    Name,
+    /// Queries the file system or other external state, e.g. git status
+    Metadata,
+    /// Reads the contents of the file
+    Content,
+}
 
-pub trait Filter {
+/// Decides which entries are listed
+///
+/// Filters are shared between threads, so any internal state needs to be
+/// synchronized.
+pub trait Filter: Send + Sync {
     fn keep(&self, entry: &Entry) -> bool;
 
+    #[inline]
+    fn cost(&self) -> Cost {
+        Cost::Name
+    }
+
+    /// Called with every entry of a directory before any of them are kept or
+    /// discarded
+    ///
+    /// Expensive filters can use this to do their work for the whole directory
+    /// at once, e.g. in parallel.
+    #[inline]
+    fn prepare(&self, _entries: &[Entry]) {}
+
     #[inline]
     fn discard(&self, entry: &Entry) -> bool {
         !self.keep(entry)
@@ -38,6 +76,34 @@ impl<F: Filter> Binary for F {
     }
 }
 
+impl<F: Filter + ?Sized> Filter for Box<F> {
+    fn keep(&self, entry: &Entry) -> bool {
+        (**self).keep(entry)
+    }
+
+    fn cost(&self) -> Cost {
+        (**self).cost()
+    }
+
+    fn prepare(&self, entries: &[Entry]) {
+        (**self).prepare(entries)
+    }
+}
+
+impl<F: Filter + ?Sized> Filter for Arc<F> {
+    fn keep(&self, entry: &Entry) -> bool {
+        (**self).keep(entry)
+    }
+
+    fn cost(&self) -> Cost {
+        (**self).cost()
+    }
+
+    fn prepare(&self, entries: &[Entry]) {
+        (**self).prepare(entries)
+    }
+}
+
 impl Filter for () {
     #[inline]
     fn keep(&self, _entry: &Entry) -> bool {
@@ -91,6 +157,90 @@ impl Filter for Dot {
     }
 }
 
+/// Keep directories and files whose contents look like text
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextOnly;
+
+impl Filter for TextOnly {
+    fn keep(&self, entry: &Entry) -> bool {
+        !entry.is_binary()
+    }
+
+    fn cost(&self) -> Cost {
+        Cost::Content
+    }
+}
+
+/// Keep files whose contents look like binary data
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BinaryOnly;
+
+impl Filter for BinaryOnly {
+    fn keep(&self, entry: &Entry) -> bool {
+        entry.is_binary()
+    }
+
+    fn cost(&self) -> Cost {
+        Cost::Content
+    }
+}
+
+/// Keep entries with the windows system attribute, nothing matches elsewhere
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct System;
+
+impl Filter for System {
+    fn keep(&self, entry: &Entry) -> bool {
+        entry.permissions().attributes().system
+    }
+}
+
+/// Keep entries with the windows readonly attribute, or without any write
+/// permission on other platforms
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadOnly;
+
+impl Filter for ReadOnly {
+    fn keep(&self, entry: &Entry) -> bool {
+        entry.permissions().attributes().readonly || entry.metadata().permissions().readonly()
+    }
+}
+
+/// Keep entries with the windows archive attribute, nothing matches elsewhere
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Archive;
+
+impl Filter for Archive {
+    fn keep(&self, entry: &Entry) -> bool {
+        entry.permissions().attributes().archivable
+    }
+}
+
+/// Match file names against a shell style glob, `README*` or `*.rs`
+///
+/// `*` matches any run of characters and `?` matches a single character.
+#[derive(Debug, Clone)]
+pub struct Glob(regex::Regex);
+
+impl Glob {
+    pub fn new<S: AsRef<str>>(pattern: S) -> Result<Self, regex::Error> {
+        let pattern = regex::escape(pattern.as_ref())
+            .replace(r"\*", ".*")
+            .replace(r"\?", ".");
+        Ok(Self(regex::Regex::new(format!("^{pattern}$").as_str())?))
+    }
+
+    pub fn is_match(&self, name: &str) -> bool {
+        self.0.is_match(name)
+    }
+}
+
+impl Filter for Glob {
+    fn keep(&self, entry: &Entry) -> bool {
+        self.is_match(entry.file_name())
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Match(regex::Regex);
 
@@ -106,6 +256,254 @@ impl Filter for Match {
     }
 }
 
+/// Keep files tracked by git and directories containing them
+///
+/// Entries outside of a git work tree are discarded.
+#[derive(Debug, Default)]
+pub struct GitTracked(Repos);
+
+impl Clone for GitTracked {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl Filter for GitTracked {
+    fn keep(&self, entry: &Entry) -> bool {
+        self.0
+            .status(entry.path())
+            .is_some_and(|status| status.tracked)
+    }
+
+    fn cost(&self) -> Cost {
+        Cost::Metadata
+    }
+}
+
+/// Keep files that aren't tracked or ignored by git and directories containing
+/// them
+///
+/// Entries outside of a git work tree are discarded.
+#[derive(Debug, Default)]
+pub struct GitUntracked(Repos);
+
+impl Clone for GitUntracked {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl Filter for GitUntracked {
+    fn keep(&self, entry: &Entry) -> bool {
+        self.0
+            .status(entry.path())
+            .is_some_and(|status| status.untracked)
+    }
+
+    fn cost(&self) -> Cost {
+        Cost::Metadata
+    }
+}
+
+/// Keep files with staged or unstaged changes and directories containing them
+///
+/// Entries outside of a git work tree are discarded.
+#[derive(Debug, Default)]
+pub struct GitModified(Repos);
+
+impl Clone for GitModified {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl Filter for GitModified {
+    fn keep(&self, entry: &Entry) -> bool {
+        self.0
+            .status(entry.path())
+            .is_some_and(|status| status.modified)
+    }
+
+    fn cost(&self) -> Cost {
+        Cost::Metadata
+    }
+}
+
+/// Keep directories and text files whose contents match a regex
+///
+/// Binary files and files larger than [`Contains::max_size`] are discarded. The
+/// files of a directory are searched in parallel when it is read.
+#[derive(Debug)]
+pub struct Contains {
+    pattern: regex::Regex,
+    max_size: u64,
+    matches: Mutex<HashMap<PathBuf, bool>>,
+}
+
+impl Clone for Contains {
+    fn clone(&self) -> Self {
+        Self {
+            pattern: self.pattern.clone(),
+            max_size: self.max_size,
+            matches: Mutex::default(),
+        }
+    }
+}
+
+impl Contains {
+    pub fn new<S: AsRef<str>>(pattern: S) -> Result<Self, regex::Error> {
+        Ok(Self {
+            pattern: regex::Regex::new(pattern.as_ref())?,
+            max_size: content::MAX_SIZE,
+            matches: Mutex::default(),
+        })
+    }
+
+    /// Largest file in bytes that will be searched, defaults to 1 MiB
+    pub fn max_size(mut self, max_size: u64) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    fn search(pattern: &regex::Regex, max_size: u64, path: &Path) -> bool {
+        match content::read_text(path, max_size) {
+            Ok(Some(text)) => pattern.is_match(&text),
+            _ => false,
+        }
+    }
+}
+
+impl Filter for Contains {
+    fn keep(&self, entry: &Entry) -> bool {
+        if entry.is_dir() {
+            return true;
+        }
+        if let Some(matched) = self.matches.lock().unwrap().get(entry.path()) {
+            return *matched;
+        }
+        Self::search(&self.pattern, self.max_size, entry.path())
+    }
+
+    fn cost(&self) -> Cost {
+        Cost::Content
+    }
+
+    fn prepare(&self, entries: &[Entry]) {
+        let files = entries
+            .iter()
+            .filter(|e| e.is_file())
+            .map(|e| e.path())
+            .collect::<Vec<_>>();
+        let (pattern, max_size) = (&self.pattern, self.max_size);
+        let found = content::parallel_map(&files, |path| Self::search(pattern, max_size, path));
+
+        // Only the directory being read is kept so long walks don't hold a
+        // verdict for every file they passed, anything else is searched again
+        let mut matches = self.matches.lock().unwrap();
+        matches.clear();
+        matches.extend(files.into_iter().map(Path::to_path_buf).zip(found));
+    }
+}
+
+/// Prepare `first` with every entry and `second` with only the entries that
+/// `first` didn't already decide
+///
+/// `decided` is the result of `first` that still requires `second` to be
+/// evaluated, `true` for [`And`] and `false` for [`Or`].
+fn prepare_remaining(first: &dyn Filter, second: &dyn Filter, entries: &[Entry], decided: bool) {
+    first.prepare(entries);
+    if second.cost() == Cost::Name {
+        second.prepare(entries);
+        return;
+    }
+
+    let remaining = entries
+        .iter()
+        .filter(|entry| first.keep(entry) == decided)
+        .cloned()
+        .collect::<Vec<_>>();
+    second.prepare(&remaining);
+}
+
+/// Keep entries that every filter keeps
+///
+/// Filters are evaluated from cheapest to most expensive, see [`Cost`].
+#[derive(Default)]
+pub struct All(Vec<Box<dyn Filter>>);
+
+impl All {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with<F: Filter + 'static>(mut self, filter: F) -> Self {
+        self.push(filter);
+        self
+    }
+
+    pub fn push<F: Filter + 'static>(&mut self, filter: F) {
+        let cost = filter.cost();
+        let index = self.0.partition_point(|f| f.cost() <= cost);
+        self.0.insert(index, Box::new(filter));
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl FromIterator<Box<dyn Filter>> for All {
+    fn from_iter<T: IntoIterator<Item = Box<dyn Filter>>>(iter: T) -> Self {
+        let mut all = Self::new();
+        for filter in iter {
+            all.push(filter);
+        }
+        all
+    }
+}
+
+impl Debug for All {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("All")
+            .field("filters", &self.0.len())
+            .finish()
+    }
+}
+
+impl Filter for All {
+    fn keep(&self, entry: &Entry) -> bool {
+        self.0.iter().all(|filter| filter.keep(entry))
+    }
+
+    fn cost(&self) -> Cost {
+        self.0.last().map(|f| f.cost()).unwrap_or_default()
+    }
+
+    fn prepare(&self, entries: &[Entry]) {
+        let mut remaining = None::<Vec<Entry>>;
+        for (i, filter) in self.0.iter().enumerate() {
+            let current = remaining.as_deref().unwrap_or(entries);
+            filter.prepare(current);
+
+            // Filters are sorted by cost so only narrow the entries when an
+            // expensive filter is still to come
+            if self.0[i + 1..].iter().any(|f| f.cost() > Cost::Name) {
+                remaining = Some(
+                    current
+                        .iter()
+                        .filter(|entry| filter.keep(entry))
+                        .cloned()
+                        .collect(),
+                );
+            }
+        }
+    }
+}
+
 pub struct And<A, B>(A, B);
 
 impl<A: Default, B: Default> Default for And<A, B> {
@@ -149,9 +547,29 @@ impl<A, B> And<A, B> {
     }
 }
 
+impl<A: Filter, B: Filter> And<A, B> {
+    fn ordered(&self) -> (&dyn Filter, &dyn Filter) {
+        if self.1.cost() < self.0.cost() {
+            (&self.1, &self.0)
+        } else {
+            (&self.0, &self.1)
+        }
+    }
+}
+
 impl<A: Filter, B: Filter> Filter for And<A, B> {
     fn keep(&self, entry: &Entry) -> bool {
-        self.0.keep(entry) && self.1.keep(entry)
+        let (first, second) = self.ordered();
+        first.keep(entry) && second.keep(entry)
+    }
+
+    fn cost(&self) -> Cost {
+        self.0.cost().max(self.1.cost())
+    }
+
+    fn prepare(&self, entries: &[Entry]) {
+        let (first, second) = self.ordered();
+        prepare_remaining(first, second, entries, true);
     }
 }
 
@@ -198,9 +616,29 @@ impl<A, B> Or<A, B> {
     }
 }
 
+impl<A: Filter, B: Filter> Or<A, B> {
+    fn ordered(&self) -> (&dyn Filter, &dyn Filter) {
+        if self.1.cost() < self.0.cost() {
+            (&self.1, &self.0)
+        } else {
+            (&self.0, &self.1)
+        }
+    }
+}
+
 impl<A: Filter, B: Filter> Filter for Or<A, B> {
     fn keep(&self, entry: &Entry) -> bool {
-        self.0.keep(entry) || self.1.keep(entry)
+        let (first, second) = self.ordered();
+        first.keep(entry) || second.keep(entry)
+    }
+
+    fn cost(&self) -> Cost {
+        self.0.cost().max(self.1.cost())
+    }
+
+    fn prepare(&self, entries: &[Entry]) {
+        let (first, second) = self.ordered();
+        prepare_remaining(first, second, entries, false);
     }
 }
 
@@ -244,4 +682,12 @@ impl<F: Filter> Filter for Not<F> {
     fn keep(&self, entry: &Entry) -> bool {
         self.0.discard(entry)
     }
+
+    fn cost(&self) -> Cost {
+        self.0.cost()
+    }
+
+    fn prepare(&self, entries: &[Entry]) {
+        self.0.prepare(entries)
+    }
 }
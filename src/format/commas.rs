@@ -0,0 +1,120 @@
+use std::io::Write;
+
+use crate::{style::Colorizer, Entry, FileSystem};
+
+use super::{terminal_width, FlushEvery, Formatter};
+
+/// Entries joined by `, ` and wrapped to the terminal width, like `ls -m`
+pub struct Commas {
+    file_system: FileSystem,
+    width: Option<usize>,
+    flush_every: usize,
+}
+
+impl Commas {
+    pub fn new(file_system: FileSystem) -> Self {
+        Self {
+            file_system,
+            width: None,
+            flush_every: 1,
+        }
+    }
+
+    /// Wrap lines at `width` columns, defaults to the terminal width
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Flush the output after every `n` lines instead of every line
+    pub fn flush_every(mut self, n: usize) -> Self {
+        self.flush_every = n;
+        self
+    }
+}
+
+impl Formatter for Commas {
+    fn render_to(
+        &mut self,
+        writer: &mut dyn Write,
+        colorizer: Colorizer,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let width = self.width.unwrap_or_else(terminal_width);
+        let entries = self.file_system.entries()?;
+
+        let mut out = FlushEvery::new(writer, self.flush_every);
+        let mut pos = 0;
+        for (i, entry) in entries.iter().enumerate() {
+            let comma = if i + 1 < entries.len() { "," } else { "" };
+            let len = colorizer.file_width(entry) + comma.len();
+
+            if pos > 0 {
+                if pos + 1 + len > width {
+                    writeln!(out)?;
+                    pos = 0;
+                } else {
+                    write!(out, " ")?;
+                    pos += 1;
+                }
+            }
+
+            write!(out, "{}{comma}", colorizer.file(entry))?;
+            pos += len;
+        }
+        if pos > 0 {
+            writeln!(out)?;
+        }
+        out.flush()?;
+
+        Ok(())
+    }
+
+    fn render_entry(&mut self, entry: &Entry, colorizer: &Colorizer) -> String {
+        colorizer.file(entry)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{sort::Natural, style::ColorMode, testing::TempDir};
+
+    /// Writer that counts how often it is flushed
+    #[derive(Default)]
+    struct Flushes(usize);
+
+    impl Write for Flushes {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.0 += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn flush_every() {
+        let base = TempDir::new("commas-flush");
+        for name in ["alpha", "bravo", "charlie", "delta"] {
+            std::fs::write(base.join(name), "").unwrap();
+        }
+
+        let flushes = |every: usize| {
+            let mut writer = Flushes::default();
+            Commas::new(FileSystem::from(base.path()).with_sorter(Natural))
+                .width(10)
+                .flush_every(every)
+                .render_to(
+                    &mut writer,
+                    Colorizer::default().color_mode(ColorMode::Never),
+                )
+                .unwrap();
+            writer.0
+        };
+        // Every entry is on its own line, followed by the final flush
+        assert_eq!(flushes(1), 5);
+        assert_eq!(flushes(100), 1);
+    }
+}
@@ -0,0 +1,38 @@
+use std::io::{self, BufWriter, Write};
+
+/// Buffered writer that flushes after every `n` lines
+///
+/// Flushing after every line is the default for stdout, which dominates the
+/// runtime of large listings over slow connections like SSH.
+pub struct FlushEvery<W: Write> {
+    inner: BufWriter<W>,
+    every: usize,
+    lines: usize,
+}
+
+impl<W: Write> FlushEvery<W> {
+    pub fn new(inner: W, every: usize) -> Self {
+        Self {
+            inner: BufWriter::new(inner),
+            every: every.max(1),
+            lines: 0,
+        }
+    }
+}
+
+impl<W: Write> Write for FlushEvery<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.lines += buf[..written].iter().filter(|b| **b == b'\n').count();
+        if self.lines >= self.every {
+            self.lines = 0;
+            self.inner.flush()?;
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.lines = 0;
+        self.inner.flush()
+    }
+}
@@ -1,71 +1,206 @@
-use terminal_size::{terminal_size, Width};
+use std::io::Write;
 
 use crate::{
+    listable::Listable,
     style::{Colorizer, Spacer},
     Entry, FileSystem,
 };
 
-use super::Formatter;
+use super::{terminal_width, Formatter};
 
-pub struct Grid(FileSystem);
+/// Width of the permission column along with the separators in front of each
+/// long cell, excluding the size and date columns, see [`Colorizer::size_width`]
+/// and [`Colorizer::date_width`]
+const LONG_PREFIX: usize = 10 + 1 + 1 + 1;
+
+/// Order cells are placed into the [`Grid`]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fill {
+    /// Left to right and then top to bottom
+    #[default]
+    Across,
+    /// Top to bottom and then left to right, like `ls`
+    Down,
+}
+
+impl Fill {
+    /// Index of the entry in a cell of the grid
+    fn index(&self, row: usize, col: usize, rows: usize, cols: usize) -> usize {
+        match self {
+            Self::Across => row * cols + col,
+            Self::Down => col * rows + row,
+        }
+    }
+}
+
+pub struct Grid {
+    file_system: FileSystem,
+    long: bool,
+    fill: Fill,
+    width: Option<usize>,
+}
 
 impl Grid {
     pub fn new(file_system: FileSystem) -> Self {
-        Self(file_system)
+        Self {
+            file_system,
+            long: false,
+            fill: Fill::default(),
+            width: None,
+        }
     }
-}
 
-impl Formatter for Grid {
-    fn print(&mut self, colorizer: Colorizer) -> Result<(), Box<dyn std::error::Error>> {
-        let (Width(width), _) = terminal_size().unwrap();
-        let width = width as usize;
-
-        let entries: Vec<Entry> = self.0.entries()?;
-        let mut min = entries.len();
-        {
-            let mut pos = 0;
-            let mut cols = 0;
-            for entry in entries.iter() {
-                if entry.file_name().len() + 2 + pos > width || cols >= min {
-                    min = cols;
-                    cols = 0;
-                    pos = entry.file_name().len() + 2;
-                }
-
-                cols += 1;
-                pos += entry.file_name().len() + 2;
-            }
+    /// Order to place entries into the grid, defaults to [`Fill::Across`]
+    pub fn fill(mut self, fill: Fill) -> Self {
+        self.fill = fill;
+        self
+    }
+
+    /// Wrap to `width` columns instead of the [`terminal_width`]
+    pub fn width(mut self, width: Option<usize>) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Prefix each cell with the permission, size, and date columns of [`super::List`]
+    pub fn long(mut self, long: bool) -> Self {
+        self.long = long;
+        self
+    }
+
+    fn cell_width(&self, colorizer: &Colorizer, item: &dyn Listable) -> usize {
+        let prefix = if self.long {
+            LONG_PREFIX + colorizer.size_width() + colorizer.date_width()
+        } else {
+            0
+        };
+        prefix + colorizer.item_width(item)
+    }
+
+    fn cell(&self, colorizer: &Colorizer, item: &dyn Listable) -> String {
+        if self.long {
+            format!(
+                "{} {} {} {}",
+                colorizer.item_permissions(item),
+                colorizer.item_size_aligned(item, colorizer.size_width()),
+                colorizer.date_aligned(item.modified(), colorizer.date_width()),
+                colorizer.item(item)
+            )
+        } else {
+            colorizer.item(item)
+        }
+    }
+
+    /// Lay out any [`Listable`] items instead of the entries of the file system
+    pub fn render_items(
+        &self,
+        writer: &mut dyn Write,
+        items: &[&dyn Listable],
+        colorizer: &Colorizer,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if items.is_empty() {
+            return Ok(());
         }
 
-        let widths = entries.chunks(min).fold(vec![0; min], |mut acc, val| {
-            for i in 0..val.len() {
-                if val[i].file_name().len() > acc[i] {
-                    acc[i] = val[i].file_name().len();
-                }
+        let cells = items
+            .iter()
+            .map(|item| self.cell_width(colorizer, *item))
+            .collect::<Vec<_>>();
+        let (rows, widths) = layout(&cells, self.width.unwrap_or_else(terminal_width), self.fill);
+
+        let mut output = Vec::with_capacity(rows);
+        for row in 0..rows {
+            let mut line = Vec::with_capacity(widths.len());
+            for (col, width) in widths.iter().enumerate() {
+                let i = self.fill.index(row, col, rows, widths.len());
+                let Some(item) = items.get(i) else {
+                    continue;
+                };
+                line.push(format!(
+                    "{}{}",
+                    self.cell(colorizer, *item),
+                    (cells[i]..*width).spacer()
+                ));
             }
-            acc
-        });
-
-        println!(
-            "{}",
-            entries
-                .chunks(min)
-                .map(|vals| {
-                    vals.iter()
-                        .enumerate()
-                        .map(|(i, v)| {
-                            format!(
-                                "{}{}",
-                                colorizer.file(v),
-                                (0..widths[i] - v.file_name().len()).spacer()
-                            )
-                        })
-                        .collect::<Vec<_>>()
-                        .join("  ")
-                })
-                .collect::<Vec<_>>()
-                .join("\n")
-        );
+            output.push(line.join("  ").trim_end().to_string());
+        }
+
+        writeln!(writer, "{}", output.join("\n"))?;
         Ok(())
     }
 }
+
+impl Formatter for Grid {
+    fn render_to(
+        &mut self,
+        writer: &mut dyn Write,
+        colorizer: Colorizer,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let entries = self.file_system.entries()?;
+        let items = entries
+            .iter()
+            .map(|entry| entry as &dyn Listable)
+            .collect::<Vec<_>>();
+        self.render_items(writer, &items, &colorizer)
+    }
+
+    fn render_entry(&mut self, entry: &Entry, colorizer: &Colorizer) -> String {
+        self.cell(colorizer, entry)
+    }
+}
+
+/// Number of rows and the width of each column for the most columns that fit in
+/// `width`
+fn layout(cells: &[usize], width: usize, fill: Fill) -> (usize, Vec<usize>) {
+    // Every column is at least as wide as the narrowest cell which bounds how
+    // many columns could possibly fit
+    let narrowest = cells.iter().min().copied().unwrap_or_default();
+    let most = (width / (narrowest + 2) + 1).clamp(1, cells.len().max(1));
+
+    for cols in (1..=most).rev() {
+        let rows = cells.len().div_ceil(cols);
+        // Filling down can leave trailing columns empty
+        let cols = match fill {
+            Fill::Across => cols,
+            Fill::Down => cells.len().div_ceil(rows),
+        };
+
+        let mut widths = vec![0; cols];
+        for (i, cell) in cells.iter().enumerate() {
+            let col = match fill {
+                Fill::Across => i % cols,
+                Fill::Down => i / rows,
+            };
+            widths[col] = widths[col].max(*cell);
+        }
+
+        if cols == 1 || widths.iter().sum::<usize>() + 2 * (cols - 1) <= width {
+            return (rows, widths);
+        }
+    }
+
+    (
+        cells.len(),
+        vec![cells.iter().max().copied().unwrap_or_default()],
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fill_order() {
+        let cells = [3, 3, 3, 3, 3];
+        assert_eq!(layout(&cells, 13, Fill::Across), (2, vec![3, 3, 3]));
+        assert_eq!(Fill::Across.index(1, 0, 2, 3), 3);
+        assert_eq!(Fill::Down.index(1, 0, 2, 3), 1);
+
+        // Four columns fit, but two rows filled down only need three of them
+        assert_eq!(layout(&cells, 18, Fill::Down), (2, vec![3, 3, 3]));
+
+        // Columns are sized by the cells in them rather than across rows
+        assert_eq!(layout(&[10, 2, 2], 14, Fill::Down), (2, vec![10, 2]));
+        assert_eq!(layout(&[10, 2, 2], 13, Fill::Down), (3, vec![10]));
+    }
+}
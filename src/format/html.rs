@@ -0,0 +1,107 @@
+use std::io::Write;
+
+use crate::{style::Colorizer, Entry, FileSystem};
+
+use super::Formatter;
+
+/// Styles for the classes of the builtin [`Colorizer`] groups
+const STYLESHEET: &str = "body { font-family: monospace; }
+table { border-collapse: collapse; }
+td { padding: 0 1em 0 0; }
+td.size, td.date { text-align: right; color: #888; }
+a { color: inherit; text-decoration: none; }
+a:hover { text-decoration: underline; }
+.dir { color: #2f6fdb; font-weight: bold; }
+.hidden { color: #888; }
+.image { color: #b03ab0; }
+.config { color: #a68a00; text-decoration: underline; }
+.exe { color: #2a9d3a; }
+";
+
+/// Standalone HTML directory index page, similar to `python -m http.server`
+///
+/// Every row gets the name of its [`Colorizer`] group as a CSS class, e.g.
+/// `dir` or `image`, so custom themes can be styled as well.
+pub struct Html {
+    file_system: FileSystem,
+}
+
+impl Html {
+    pub fn new(file_system: FileSystem) -> Self {
+        Self { file_system }
+    }
+}
+
+impl Formatter for Html {
+    fn render_to(
+        &mut self,
+        writer: &mut dyn Write,
+        colorizer: Colorizer,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let title = escape(&self.file_system.path().display().to_string());
+        writeln!(
+            writer,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Index of {title}</title>\n<style>\n{STYLESHEET}</style>\n</head>\n<body>\n<h1>Index of {title}</h1>\n<table>"
+        )?;
+        for entry in self.file_system.entries()? {
+            writeln!(writer, "{}", self.render_entry(&entry, &colorizer))?;
+        }
+        writeln!(writer, "</table>\n</body>\n</html>")?;
+        Ok(())
+    }
+
+    fn render_entry(&mut self, entry: &Entry, colorizer: &Colorizer) -> String {
+        let class = colorizer
+            .classify(entry)
+            .map(|group| format!(" class=\"{}\"", class_name(group)))
+            .unwrap_or_default();
+        let slash = if entry.is_dir() { "/" } else { "" };
+        let size = match entry.is_dir() {
+            true => String::new(),
+            false => colorizer.size_text(entry.metadata().len()),
+        };
+        let date = colorizer.date_modified_text(entry);
+        format!(
+            "<tr{class}><td><a href=\"{href}{slash}\">{name}{slash}</a></td><td class=\"size\">{size}</td><td class=\"date\">{date}</td></tr>",
+            href = escape(&encode(entry.file_name())),
+            name = escape(entry.file_name()),
+            date = escape(date.trim()),
+        )
+    }
+}
+
+/// Escape text for use in HTML content and attribute values
+pub(crate) fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Percent encode a single path segment
+pub(crate) fn encode(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+/// Lowercase group name with anything that isn't valid in a class replaced
+fn class_name(group: &str) -> String {
+    group
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,199 @@
+use std::{io::Write, time::SystemTime};
+
+use chrono::{DateTime, Local};
+use serde::Serialize;
+
+use crate::{diagnostic::Diagnostic, style::Colorizer, Entry, FileSystem};
+
+use super::Formatter;
+
+/// Entries as a JSON array for piping into `jq` and other tools
+///
+/// The colorizer is ignored so the output is always plain.
+pub struct Json(FileSystem, bool);
+
+impl Json {
+    pub fn new(file_system: FileSystem) -> Self {
+        Self(file_system, false)
+    }
+
+    /// Nest the entries of each directory in its `children` array, walking
+    /// the same hierarchy as the tree format
+    ///
+    /// Directories that aren't walked into, because of the max depth or an
+    /// error, have a `diagnostic` instead.
+    pub fn nested(self, nested: bool) -> Self {
+        Self(self.0, nested)
+    }
+}
+
+#[derive(Serialize)]
+struct Item<'e> {
+    name: &'e str,
+    path: String,
+    #[serde(rename = "type")]
+    etype: &'static str,
+    size: u64,
+    permissions: String,
+    modified: Option<String>,
+    accessed: Option<String>,
+    created: Option<String>,
+    /// Why a directory can't be walked into, see [`FileSystem::diagnose`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    diagnostic: Option<Diagnostic>,
+    /// Entries of a directory in nested output
+    #[serde(skip_serializing_if = "Option::is_none")]
+    children: Option<Vec<Item<'e>>>,
+}
+
+/// An entry along with the entries below it for nested output
+struct Node {
+    entry: Entry,
+    children: Result<Vec<Node>, Option<Diagnostic>>,
+}
+
+impl<'e> From<&'e Node> for Item<'e> {
+    fn from(node: &'e Node) -> Self {
+        let (children, diagnostic) = match &node.children {
+            Ok(children) => (Some(children.iter().map(Item::from).collect()), None),
+            Err(diagnostic) => (None, diagnostic.clone()),
+        };
+        Self {
+            diagnostic,
+            children,
+            ..Item::from(&node.entry)
+        }
+    }
+}
+
+fn timestamp(time: std::io::Result<SystemTime>) -> Option<String> {
+    time.ok()
+        .map(|time| DateTime::<Local>::from(time).to_rfc3339())
+}
+
+impl<'e> From<&'e Entry> for Item<'e> {
+    fn from(entry: &'e Entry) -> Self {
+        let meta = entry.metadata();
+        Self {
+            name: entry.file_name(),
+            path: entry.path().display().to_string(),
+            etype: if entry.is_dir() { "dir" } else { "file" },
+            size: meta.len(),
+            permissions: entry.permissions().to_string(),
+            modified: timestamp(meta.modified()),
+            accessed: timestamp(meta.accessed()),
+            created: timestamp(meta.created()),
+            diagnostic: None,
+            children: None,
+        }
+    }
+}
+
+impl Json {
+    /// Write the entries as a single line JSON array
+    pub fn write<W: Write + ?Sized>(&self, out: &mut W) -> Result<(), Box<dyn std::error::Error>> {
+        let entries = self.0.entries()?;
+        if self.1 {
+            let nodes = self.nest(entries, 1)?;
+            serde_json::to_writer(&mut *out, &nodes.iter().map(Item::from).collect::<Vec<_>>())?;
+            writeln!(out)?;
+            return Ok(());
+        }
+
+        let items = entries
+            .iter()
+            .map(|entry| Item {
+                diagnostic: self.0.diagnose(entry),
+                ..Item::from(entry)
+            })
+            .collect::<Vec<_>>();
+
+        serde_json::to_writer(&mut *out, &items)?;
+        writeln!(out)?;
+        Ok(())
+    }
+
+    /// Read the entries below each directory down to the max depth
+    fn nest(
+        &self,
+        entries: Vec<Entry>,
+        depth: usize,
+    ) -> Result<Vec<Node>, Box<dyn std::error::Error>> {
+        let mut nodes = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let children = if !entry.is_dir() {
+                Err(None)
+            } else if self.0.max_depth().is_some_and(|max| depth >= max) {
+                Err(Some(Diagnostic::MaxDepth))
+            } else if !self.0.descends_into(&entry) {
+                Err(self.0.cycle(&entry))
+            } else {
+                match self.0.read_dir(entry.path()) {
+                    Ok(children) => Ok(self.nest(children, depth + 1)?),
+                    Err(err) if self.0.strict() => return Err(err),
+                    Err(err) => Err(Some(Diagnostic::unreadable(err.as_ref()))),
+                }
+            };
+            nodes.push(Node { entry, children });
+        }
+        Ok(nodes)
+    }
+}
+
+impl Formatter for Json {
+    fn render_to(
+        &mut self,
+        writer: &mut dyn Write,
+        _colorizer: Colorizer,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.write(writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    fn render_entry(&mut self, entry: &Entry, _colorizer: &Colorizer) -> String {
+        let item = Item {
+            diagnostic: self.0.diagnose(entry),
+            ..Item::from(entry)
+        };
+        serde_json::to_string(&item).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{sort::Natural, testing::TempDir};
+
+    #[test]
+    fn nested_tree() {
+        let base = TempDir::new("json-tree");
+        std::fs::create_dir_all(base.join("a").join("b")).unwrap();
+        std::fs::write(base.join("a").join("x"), "").unwrap();
+        std::fs::write(base.join("z"), "").unwrap();
+
+        let render = |max_depth: Option<usize>| {
+            let mut out = Vec::new();
+            Json::new(
+                FileSystem::from(base.path())
+                    .with_sorter(Natural)
+                    .with_max_depth(max_depth),
+            )
+            .nested(true)
+            .write(&mut out)
+            .unwrap();
+            serde_json::from_slice::<serde_json::Value>(&out).unwrap()
+        };
+
+        let tree = render(None);
+        assert_eq!(tree[0]["name"], "a");
+        assert_eq!(tree[0]["children"][0]["name"], "b");
+        assert_eq!(tree[0]["children"][0]["children"], serde_json::json!([]));
+        assert_eq!(tree[0]["children"][1]["name"], "x");
+        assert!(tree[1].get("children").is_none());
+
+        let shallow = render(Some(1));
+        assert!(shallow[0].get("children").is_none());
+        assert_eq!(shallow[0]["diagnostic"]["kind"], "max-depth");
+    }
+}
@@ -1,40 +1,794 @@
+mod commas;
+mod flush;
 mod grid;
+mod html;
+mod json;
+mod names;
+mod spill;
+mod strip;
+mod template;
 mod tree;
 
-pub use grid::Grid;
-pub use tree::Tree;
+pub use commas::Commas;
+pub use flush::FlushEvery;
+pub use grid::{Fill, Grid};
+pub use html::Html;
+#[cfg(feature = "serve")]
+pub(crate) use html::{encode, escape};
+pub use json::Json;
+pub use names::{Names, Print0};
+pub use spill::Spill;
+pub use strip::StripAnsi;
+pub use template::{Template, FIELDS};
+pub use tree::{Branches, Tree};
 
-use std::io::Write;
+use std::{fmt::Display, io::Write, str::FromStr};
 
-use crate::{style::Colorizer, FileSystem};
+use hashbrown::HashMap;
+use owo_colors::OwoColorize;
+
+use crate::{
+    codeowners::CodeOwners,
+    content,
+    exec::ExecColumn,
+    hash::HashCache,
+    listable::Listable,
+    sort::SortKey,
+    style::{Colorizer, Spacer},
+    text, Entry, FileSystem,
+};
 
 pub trait Formatter {
-    fn print(&mut self, colorizer: Colorizer) -> Result<(), Box<dyn std::error::Error>>;
+    /// Write the formatted entries to `writer`
+    fn render_to(
+        &mut self,
+        writer: &mut dyn Write,
+        colorizer: Colorizer,
+    ) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// A single entry as it would appear in the output, without a newline
+    ///
+    /// Alignment that depends on the other entries, like column widths, is
+    /// left out.
+    fn render_entry(&mut self, entry: &Entry, colorizer: &Colorizer) -> String;
+
+    /// Write the formatted entries to stdout
+    fn print(&mut self, colorizer: Colorizer) -> Result<(), Box<dyn std::error::Error>> {
+        self.render_to(&mut std::io::stdout().lock(), colorizer)
+    }
+}
+
+/// Columns available for formats that wrap to the terminal
+///
+/// Falls back to `$COLUMNS` and then `80` when stdout isn't a terminal.
+pub fn terminal_width() -> usize {
+    match terminal_size::terminal_size() {
+        Some((terminal_size::Width(width), _)) => width as usize,
+        None => std::env::var("COLUMNS")
+            .ok()
+            .and_then(|columns| columns.parse().ok())
+            .unwrap_or(80),
+    }
+}
+
+/// A column of the [`List`] format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Column {
+    Permissions,
+    Size,
+    /// Allocated size on disk, see [`Entry::allocated_size`]
+    Blocks,
+    /// Last modified date
+    Date,
+    /// Creation date, `-` on platforms and file systems without one
+    Created,
+    /// Last access date, `-` on platforms and file systems without one
+    Accessed,
+    /// Name of the owning user
+    Owner,
+    /// Name of the owning group
+    Group,
+    /// Inode number, `-` on platforms without one
+    Inode,
+    /// Cloud placeholder status glyph, see [`crate::cloud::CloudStatus`]
+    Cloud,
+    Name,
+}
+
+impl Column {
+    /// Columns printed when none are selected
+    pub const DEFAULT: [Column; 6] = [
+        Self::Permissions,
+        Self::Owner,
+        Self::Group,
+        Self::Size,
+        Self::Date,
+        Self::Name,
+    ];
+
+    pub const ALL: [Column; 11] = [
+        Self::Permissions,
+        Self::Size,
+        Self::Blocks,
+        Self::Date,
+        Self::Created,
+        Self::Accessed,
+        Self::Owner,
+        Self::Group,
+        Self::Inode,
+        Self::Cloud,
+        Self::Name,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Permissions => "perms",
+            Self::Size => "size",
+            Self::Blocks => "blocks",
+            Self::Date => "date",
+            Self::Created => "created",
+            Self::Accessed => "accessed",
+            Self::Owner => "owner",
+            Self::Group => "group",
+            Self::Inode => "inode",
+            Self::Cloud => "cloud",
+            Self::Name => "name",
+        }
+    }
+
+    /// Title of the column in the header row
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::Permissions => "Permissions",
+            Self::Size => "Size",
+            Self::Blocks => "Blocks",
+            Self::Date => "Modified",
+            Self::Created => "Created",
+            Self::Accessed => "Accessed",
+            Self::Owner => "Owner",
+            Self::Group => "Group",
+            Self::Inode => "Inode",
+            Self::Cloud => "Cloud",
+            Self::Name => "Name",
+        }
+    }
+
+    /// Sort key that orders entries by this column
+    pub fn sort_key(&self) -> Option<SortKey> {
+        match self {
+            Self::Size => Some(SortKey::Size),
+            Self::Date => Some(SortKey::Modified),
+            Self::Name => Some(SortKey::Name),
+            _ => None,
+        }
+    }
+
+    /// Whether values are aligned to the right edge of the column
+    fn right_aligned(&self) -> bool {
+        matches!(
+            self,
+            Self::Size | Self::Blocks | Self::Date | Self::Created | Self::Accessed | Self::Inode
+        )
+    }
+
+    /// Parse a comma separated list of columns, `perms,size,name`
+    pub fn parse_list(columns: &str) -> Result<Vec<Column>, String> {
+        columns.split(',').map(|c| c.trim().parse()).collect()
+    }
+
+    /// Unstyled text of the blocks, created, accessed, owner, group, inode,
+    /// and cloud columns
+    ///
+    /// Columns that only entries have are `-` for other items.
+    fn text(&self, item: &dyn Listable, colorizer: &Colorizer) -> String {
+        match self {
+            Self::Created => return colorizer.date_text(item.created()),
+            Self::Accessed => return colorizer.date_text(item.accessed()),
+            _ => {}
+        }
+        let Some(entry) = item.entry() else {
+            return "-".to_string();
+        };
+        match self {
+            Self::Blocks => entry
+                .allocated_size()
+                .map(|size| colorizer.size_text(size))
+                .unwrap_or("-".to_string()),
+            Self::Owner => entry.permissions().user().name.clone(),
+            Self::Group => entry.permissions().group().name.clone(),
+            Self::Inode => inode(entry)
+                .map(|i| i.to_string())
+                .unwrap_or("-".to_string()),
+            Self::Cloud => entry
+                .cloud_status()
+                .map(|status| status.glyph().to_string())
+                .unwrap_or_default(),
+            _ => String::new(),
+        }
+    }
+}
+
+impl Display for Column {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
 }
 
-pub struct List(FileSystem);
+impl FromStr for Column {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "perms" | "permissions" => Ok(Self::Permissions),
+            "size" => Ok(Self::Size),
+            "blocks" | "allocated" => Ok(Self::Blocks),
+            "date" | "modified" => Ok(Self::Date),
+            "created" | "birth" => Ok(Self::Created),
+            "accessed" | "atime" => Ok(Self::Accessed),
+            "owner" | "user" => Ok(Self::Owner),
+            "group" => Ok(Self::Group),
+            "inode" => Ok(Self::Inode),
+            "cloud" => Ok(Self::Cloud),
+            "name" => Ok(Self::Name),
+            other => Err(format!(
+                "unknown column '{other}', expected one of {}",
+                Self::ALL
+                    .iter()
+                    .map(Column::name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+}
+
+/// Width of a name along with the target of a link
+fn name_width(item: &dyn Listable, colorizer: &Colorizer) -> usize {
+    colorizer.item_width(item)
+        + item
+            .entry()
+            .map(|entry| colorizer.link_target_width(entry))
+            .unwrap_or_default()
+}
+
+fn inode(entry: &Entry) -> Option<u64> {
+    entry.file_id().map(|id| id.inode)
+}
+
+/// Index of the first earlier entry that is a hard link to the same file
+pub fn hard_links(entries: &[Entry]) -> Vec<Option<usize>> {
+    let items = entries
+        .iter()
+        .map(|entry| entry as &dyn Listable)
+        .collect::<Vec<_>>();
+    item_hard_links(&items)
+}
+
+/// Same as [`hard_links`] for items that may not be entries
+fn item_hard_links(items: &[&dyn Listable]) -> Vec<Option<usize>> {
+    let mut seen = HashMap::new();
+    items
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            let entry = item.entry()?;
+            let id = entry.file_id().filter(|_| entry.hard_links() > 1)?;
+            match seen.get(&id) {
+                Some(first) => Some(*first),
+                None => {
+                    seen.insert(id, i);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+pub struct List {
+    file_system: FileSystem,
+    columns: Vec<Column>,
+    header: bool,
+    sort_indicators: (char, char),
+    hashes: Option<HashCache>,
+    lines: bool,
+    exec: Option<ExecColumn>,
+    code_owners: Option<CodeOwners>,
+    flush_every: usize,
+    #[cfg(feature = "media")]
+    media: bool,
+}
 
 impl List {
     pub fn new(file_system: FileSystem) -> Self {
-        Self(file_system)
+        Self {
+            file_system,
+            columns: Column::DEFAULT.to_vec(),
+            header: false,
+            sort_indicators: ('▲', '▼'),
+            hashes: None,
+            lines: false,
+            exec: None,
+            code_owners: None,
+            flush_every: 1,
+            #[cfg(feature = "media")]
+            media: false,
+        }
+    }
+
+    /// Columns to print in order, defaults to [`Column::DEFAULT`]
+    ///
+    /// The hash, lines, exec, and media columns are placed before the name.
+    pub fn columns<I: IntoIterator<Item = Column>>(mut self, columns: I) -> Self {
+        self.columns = columns.into_iter().collect();
+        self
+    }
+
+    /// Print the listing root as a breadcrumb and a row of column titles before
+    /// the entries
+    ///
+    /// The column the entries are sorted by, see
+    /// [`crate::sort::SortStrategy::column`], is marked with one of the sort
+    /// indicators.
+    pub fn header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Markers for ascending and descending sorted columns, defaults to `▲` and
+    /// `▼`
+    pub fn sort_indicators(mut self, ascending: char, descending: char) -> Self {
+        self.sort_indicators = (ascending, descending);
+        self
+    }
+
+    /// Title of a column in the header along with the sort indicator if the
+    /// entries are sorted by it
+    fn title(&self, column: Column) -> String {
+        let sorted = self.file_system.sorter().column();
+        match sorted {
+            Some((key, descending)) if column.sort_key() == Some(key) => {
+                let (ascending, descending_marker) = self.sort_indicators;
+                let marker = if descending {
+                    descending_marker
+                } else {
+                    ascending
+                };
+                format!("{}{marker}", column.title())
+            }
+            _ => column.title().to_string(),
+        }
+    }
+
+    /// Show a content hash column computed with the given cache
+    pub fn hash(mut self, cache: HashCache) -> Self {
+        self.hashes = Some(cache);
+        self
+    }
+
+    /// Show the owners of each entry from a `CODEOWNERS` file after its name
+    pub fn code_owners(mut self, code_owners: Option<CodeOwners>) -> Self {
+        self.code_owners = code_owners;
+        self
+    }
+
+    /// Show a column with the number of lines in text files
+    pub fn lines(mut self, lines: bool) -> Self {
+        self.lines = lines;
+        self
+    }
+
+    /// Show a column with the output of a command run for each entry
+    pub fn exec(mut self, exec: Option<ExecColumn>) -> Self {
+        self.exec = exec;
+        self
+    }
+
+    /// Flush the output after every `n` lines instead of every line
+    pub fn flush_every(mut self, n: usize) -> Self {
+        self.flush_every = n;
+        self
+    }
+
+    /// Show a column with image dimensions or audio/video durations
+    #[cfg(feature = "media")]
+    pub fn media(mut self, media: bool) -> Self {
+        self.media = media;
+        self
     }
 }
 
-impl Formatter for List {
-    fn print(&mut self, colorizer: Colorizer) -> Result<(), Box<dyn std::error::Error>> {
-        let mut stdout = std::io::stdout();
+impl List {
+    /// Width of every column across `entries`
+    ///
+    /// Owner, group, and inode vary in length so they are padded to the widest.
+    fn widths(&self, items: &[&dyn Listable], colorizer: &Colorizer) -> Vec<usize> {
+        let last = self.columns.len().saturating_sub(1);
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                let width = match column {
+                    Column::Permissions => 10,
+                    Column::Size => colorizer.size_width(),
+                    Column::Date => items
+                        .iter()
+                        .map(|item| text::width(&colorizer.date_text(item.modified())))
+                        .max()
+                        .unwrap_or(colorizer.date_width()),
+                    // Names only need padding when other columns follow them
+                    Column::Name if i == last => 0,
+                    Column::Name => items
+                        .iter()
+                        .map(|item| name_width(*item, colorizer))
+                        .max()
+                        .unwrap_or_default(),
+                    other => items
+                        .iter()
+                        .map(|item| text::width(&other.text(*item, colorizer)))
+                        .max()
+                        .unwrap_or_default(),
+                };
+                match self.header && (*column != Column::Name || i != last) {
+                    true => width.max(text::width(&self.title(*column))),
+                    false => width,
+                }
+            })
+            .collect()
+    }
+
+    /// Hash, line count, exec, and media columns that are placed before the
+    /// name
+    fn extra(
+        &mut self,
+        item: &dyn Listable,
+        lines: Option<Option<usize>>,
+        exec: Option<(Option<&str>, usize)>,
+    ) -> String {
+        let entry = item.entry();
+        let mut extra = match self.hashes.as_mut() {
+            Some(cache) => match entry.map(|entry| cache.hash(entry)) {
+                Some(Ok(Some(hash))) => format!("{:016x} ", hash.dimmed()),
+                _ => format!("{:>16} ", "-".dimmed()),
+            },
+            None => String::new(),
+        };
+
+        if let Some(count) = lines {
+            match count {
+                Some(count) => extra.push_str(&format!("{count:>7} ")),
+                None => extra.push_str(&format!("{:>7} ", "-".dimmed())),
+            }
+        }
+
+        if let Some((output, width)) = exec {
+            match output {
+                Some(output) => extra.push_str(&format!("{} ", text::pad(output, width))),
+                None => {
+                    extra.push_str(&format!("{} ", text::pad(&"-".dimmed().to_string(), width)))
+                }
+            }
+        }
+
+        #[cfg(feature = "media")]
+        if self.media {
+            let media = entry
+                .and_then(|entry| crate::media::probe(entry.path()))
+                .map(|info| info.to_string())
+                .unwrap_or_default();
+            extra.push_str(&format!("{:>9} ", media.dimmed()));
+        }
+        extra
+    }
+
+    fn row(
+        &self,
+        item: &dyn Listable,
+        colorizer: &Colorizer,
+        widths: &[usize],
+        mut extra: String,
+    ) -> String {
+        let mut line = Vec::with_capacity(self.columns.len() + 1);
+        for (column, width) in self.columns.iter().zip(widths.iter()) {
+            match column {
+                Column::Permissions => line.push(format!(
+                    "{}{}",
+                    colorizer.item_permissions(item),
+                    (10..*width).spacer()
+                )),
+                Column::Size => line.push(colorizer.item_size_aligned(item, *width)),
+                Column::Date => line.push(colorizer.date_aligned(item.modified(), *width)),
+                Column::Created => line.push(colorizer.date_aligned(item.created(), *width)),
+                Column::Accessed => line.push(colorizer.date_aligned(item.accessed(), *width)),
+                Column::Owner | Column::Group if item.entry().is_none() => {
+                    line.push(format!("{:<width$}", "-"))
+                }
+                Column::Owner => line.push(colorizer.owner_aligned(item.entry().unwrap(), *width)),
+                Column::Group => line.push(colorizer.group_aligned(item.entry().unwrap(), *width)),
+                Column::Name => {
+                    if !extra.is_empty() {
+                        // Only the separator goes, the padding of an aligned
+                        // exec column has to stay
+                        let mut extra = std::mem::take(&mut extra);
+                        extra.pop();
+                        line.push(extra);
+                    }
+                    let target = item
+                        .entry()
+                        .and_then(|entry| colorizer.link_target(entry))
+                        .unwrap_or_default();
+                    let name = format!(
+                        "{}{target}{}",
+                        colorizer.item(item),
+                        (name_width(item, colorizer)..*width).spacer()
+                    );
+                    // Names are set apart from the columns before them by a wider gap
+                    match line.is_empty() {
+                        true => line.push(name),
+                        false => line.push(format!(" {name}")),
+                    }
+                }
+                other => {
+                    let text = other.text(item, colorizer);
+                    line.push(match other.right_aligned() {
+                        true => format!("{text:>width$}"),
+                        false => format!("{text:<width$}"),
+                    });
+                }
+            }
+        }
+        if !extra.is_empty() {
+            line.push(extra.trim_end().to_string());
+        }
+        if let (Some(code_owners), Some(entry)) = (self.code_owners.as_ref(), item.entry()) {
+            let owners = code_owners.owners(entry.path());
+            if !owners.is_empty() {
+                line.push(owners.join(" ").cyan().to_string());
+            }
+        }
+        line.join(" ")
+    }
+
+    /// Print any [`Listable`] items instead of the entries of the file system
+    pub fn render_items(
+        &mut self,
+        writer: &mut dyn Write,
+        items: &[&dyn Listable],
+        colorizer: &Colorizer,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut out = FlushEvery::new(writer, self.flush_every);
 
-        for entry in self.0.entries()? {
+        // Reading every file is slow so the counts are done up front in parallel
+        let lines = if self.lines {
+            let paths = items
+                .iter()
+                .filter_map(|item| item.entry().map(Entry::path))
+                .collect::<Vec<_>>();
+            let mut counts = content::parallel_map(&paths, |path| {
+                path.is_file()
+                    .then(|| content::count_lines(path, content::MAX_SIZE))
+                    .flatten()
+            })
+            .into_iter();
+            items
+                .iter()
+                .map(|item| item.entry().and_then(|_| counts.next().flatten()))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        // Commands can be slow too so they also run up front
+        let exec = match self.exec.as_ref() {
+            Some(exec) => {
+                let paths = items
+                    .iter()
+                    .filter_map(|item| item.entry().map(Entry::path))
+                    .collect::<Vec<_>>();
+                let mut outputs = exec.run_all(&paths).into_iter();
+                items
+                    .iter()
+                    .map(|item| item.entry().and_then(|_| outputs.next().flatten()))
+                    .collect()
+            }
+            None => Vec::new(),
+        };
+        let exec_width = exec
+            .iter()
+            .map(|output| output.as_deref().map(text::width).unwrap_or(1))
+            .chain(self.header.then_some(4))
+            .max()
+            .unwrap_or(1);
+
+        let widths = self.widths(items, colorizer);
+        let links = item_hard_links(items);
+
+        if self.header {
+            writeln!(out, "{}", colorizer.breadcrumb(&self.file_system.path))?;
+
+            // Mirrors the placement of the hash, lines, exec, and media columns below
+            let mut extra = String::new();
+            if self.hashes.is_some() {
+                extra.push_str(&format!("{:<16} ", "Hash"));
+            }
+            if self.lines {
+                extra.push_str(&format!("{:>7} ", "Lines"));
+            }
+            if self.exec.is_some() {
+                extra.push_str(&format!("{:<exec_width$} ", "Exec"));
+            }
+            #[cfg(feature = "media")]
+            if self.media {
+                extra.push_str(&format!("{:>9} ", "Media"));
+            }
+
+            let mut line = Vec::with_capacity(self.columns.len() + 1);
+            for (column, width) in self.columns.iter().zip(widths.iter()) {
+                let title = self.title(*column);
+                match column {
+                    Column::Name => {
+                        if !extra.is_empty() {
+                            let mut extra = std::mem::take(&mut extra);
+                            extra.pop();
+                            line.push(extra);
+                        }
+                        match line.is_empty() {
+                            true => line.push(format!("{title:<width$}")),
+                            false => line.push(format!(" {title:<width$}")),
+                        }
+                    }
+                    other if other.right_aligned() => line.push(format!("{title:>width$}")),
+                    _ => line.push(format!("{title:<width$}")),
+                }
+            }
+            if !extra.is_empty() {
+                line.push(extra.trim_end().to_string());
+            }
             writeln!(
-                stdout,
-                "{} {} {}  {}",
-                colorizer.permissions(&entry),
-                colorizer.file_size(&entry),
-                colorizer.date_modified(&entry),
-                colorizer.file(&entry),
+                out,
+                "{}",
+                colorizer.paint(line.join(" "), owo_colors::Style::new().dimmed())
             )?;
         }
-        stdout.flush()?;
+
+        for (i, item) in items.iter().enumerate() {
+            let extra = self.extra(
+                *item,
+                lines.get(i).copied(),
+                self.exec
+                    .is_some()
+                    .then(|| (exec[i].as_deref(), exec_width)),
+            );
+            let row = self.row(*item, colorizer, &widths, extra);
+            match links[i] {
+                Some(first) => writeln!(
+                    out,
+                    "{row} {}",
+                    format!("=> same as {}", items[first].name()).dimmed()
+                )?,
+                None => writeln!(out, "{row}")?,
+            }
+        }
+        out.flush()?;
+
+        if let Some(cache) = self.hashes.as_mut() {
+            cache.save()?;
+        }
         Ok(())
     }
 }
+
+impl Formatter for List {
+    fn render_to(
+        &mut self,
+        writer: &mut dyn Write,
+        colorizer: Colorizer,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let entries = self.file_system.entries()?;
+        let items = entries
+            .iter()
+            .map(|entry| entry as &dyn Listable)
+            .collect::<Vec<_>>();
+        self.render_items(writer, &items, &colorizer)
+    }
+
+    fn render_entry(&mut self, entry: &Entry, colorizer: &Colorizer) -> String {
+        let lines = self.lines.then(|| {
+            entry
+                .path()
+                .is_file()
+                .then(|| content::count_lines(entry.path(), content::MAX_SIZE))
+                .flatten()
+        });
+        let exec = self.exec.as_ref().map(|exec| exec.run(entry.path()));
+        let widths = self.widths(&[entry], colorizer);
+        let extra = self.extra(
+            entry,
+            lines,
+            exec.as_ref().map(|output| {
+                let output = output.as_deref();
+                (output, output.map(text::width).unwrap_or(1))
+            }),
+        );
+        self.row(entry, colorizer, &widths, extra)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn header_alignment() {
+        let base = TempDir::new("header");
+        std::fs::write(base.join("a.txt"), "12345").unwrap();
+        std::fs::create_dir_all(base.join("folder")).unwrap();
+
+        let file_system = FileSystem::from(base.path()).with_sorter(crate::sort::Natural);
+        let mut list = List::new(file_system)
+            .columns([Column::Size, Column::Name, Column::Owner])
+            .header(true);
+        let mut out = Vec::new();
+        list.render_to(
+            &mut out,
+            Colorizer::default_theme().color_mode(crate::style::ColorMode::Never),
+        )
+        .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], base.display().to_string());
+        assert_eq!(lines[1], "Size  Name▲  Owner");
+
+        // Titles start at the same character as the values of their columns
+        let start = |line: &str, at: usize| line[..at].chars().count();
+        let header = lines[1];
+        for (row, name) in lines[2..].iter().zip(["a.txt", "folder"]) {
+            assert_eq!(
+                start(row, row.find(name).unwrap()),
+                start(header, header.find("Name").unwrap())
+            );
+            assert_eq!(
+                start(row, row.trim_end().rfind(' ').unwrap() + 1),
+                start(header, header.find("Owner").unwrap())
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hard_link_groups() {
+        let base = TempDir::new("links");
+        std::fs::write(base.join("a"), "a").unwrap();
+        std::fs::write(base.join("c"), "c").unwrap();
+        std::fs::hard_link(base.join("a"), base.join("b")).unwrap();
+
+        let entries =
+            ["a", "b", "c"].map(|name| Entry::try_from(base.join(name).as_path()).unwrap());
+        assert_eq!(hard_links(&entries), [None, Some(0), None]);
+        assert_eq!(entries[0].file_id(), entries[1].file_id());
+        assert!(entries[0].same_file(&entries[1]));
+        assert!(!entries[0].same_file(&entries[2]));
+
+        std::fs::rename(base.join("c"), base.join("d")).unwrap();
+        let renamed = Entry::try_from(base.join("d").as_path()).unwrap();
+        assert!(renamed.same_file(&entries[2]));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlink_targets() {
+        let base = TempDir::new("symlinks");
+        std::fs::write(base.join("file"), "").unwrap();
+        std::os::unix::fs::symlink("file", base.join("good")).unwrap();
+        std::os::unix::fs::symlink("missing", base.join("bad")).unwrap();
+
+        let entries = FileSystem::from(base.path()).entries().unwrap();
+        let target = |name: &str| {
+            let entry = entries.iter().find(|e| e.file_name() == name).unwrap();
+            (entry.link_target(), entry.is_broken_link())
+        };
+        assert_eq!(target("file"), (None, false));
+        assert_eq!(target("good"), (Some("file".into()), false));
+        assert_eq!(target("bad"), (Some("missing".into()), true));
+    }
+}
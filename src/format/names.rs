@@ -0,0 +1,134 @@
+use std::io::{IsTerminal, Write};
+
+use crate::{style::Colorizer, Entry, FileSystem};
+
+use super::{FlushEvery, Formatter};
+
+/// Only file names, one per line, for piping into `xargs`, `grep`, and shell
+/// loops
+///
+/// Names are styled by the colorizer only when stdout is a terminal.
+pub struct Names {
+    file_system: FileSystem,
+    color: bool,
+    flush_every: usize,
+}
+
+impl Names {
+    pub fn new(file_system: FileSystem) -> Self {
+        Self {
+            file_system,
+            color: std::io::stdout().is_terminal(),
+            flush_every: 1,
+        }
+    }
+
+    /// Style names with the colorizer, defaults to whether stdout is a terminal
+    pub fn color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Flush the output after every `n` lines instead of every line
+    pub fn flush_every(mut self, n: usize) -> Self {
+        self.flush_every = n;
+        self
+    }
+}
+
+impl Formatter for Names {
+    fn render_to(
+        &mut self,
+        writer: &mut dyn Write,
+        colorizer: Colorizer,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut out = FlushEvery::new(writer, self.flush_every);
+        for entry in self.file_system.entries()? {
+            if self.color {
+                writeln!(out, "{}", colorizer.file(&entry))?;
+            } else {
+                writeln!(out, "{}", colorizer.display_name(&entry))?;
+            }
+        }
+        out.flush()?;
+        Ok(())
+    }
+
+    fn render_entry(&mut self, entry: &Entry, colorizer: &Colorizer) -> String {
+        match self.color {
+            true => colorizer.file(entry),
+            false => colorizer.display_name(entry).into_owned(),
+        }
+    }
+}
+
+/// Raw paths separated by NUL bytes for `xargs -0`
+///
+/// Paths are written as is without color or escaping, so names with spaces or
+/// newlines survive the trip.
+pub struct Print0 {
+    file_system: FileSystem,
+}
+
+impl Print0 {
+    pub fn new(file_system: FileSystem) -> Self {
+        Self { file_system }
+    }
+}
+
+impl Formatter for Print0 {
+    fn render_to(
+        &mut self,
+        writer: &mut dyn Write,
+        _colorizer: Colorizer,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut out = std::io::BufWriter::new(writer);
+        for entry in self.file_system.entries()? {
+            out.write_all(entry.path().as_os_str().as_encoded_bytes())?;
+            out.write_all(b"\0")?;
+        }
+        out.flush()?;
+        Ok(())
+    }
+
+    /// The raw path followed by a NUL, lossy for paths that aren't unicode
+    fn render_entry(&mut self, entry: &Entry, _colorizer: &Colorizer) -> String {
+        format!("{}\0", entry.path().display())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn render_to_buffer() {
+        let base = TempDir::new("names");
+        for name in ["b.txt", "a b.rs"] {
+            std::fs::write(base.join(name), "").unwrap();
+        }
+
+        let file_system = FileSystem::from(&base).with_sorter(crate::sort::Natural);
+        let mut out = Vec::new();
+        Names::new(file_system.clone())
+            .color(false)
+            .render_to(&mut out, Colorizer::default())
+            .unwrap();
+        assert_eq!(out, b"a b.rs\nb.txt\n");
+
+        let entry = Entry::try_from(base.join("a b.rs").as_path()).unwrap();
+        let mut names = Names::new(file_system.clone()).color(false);
+        assert_eq!(names.render_entry(&entry, &Colorizer::default()), "a b.rs");
+
+        let mut out = Vec::new();
+        Print0::new(file_system)
+            .render_to(&mut out, Colorizer::default())
+            .unwrap();
+        let expected = [base.join("a b.rs"), base.join("b.txt")]
+            .iter()
+            .flat_map(|path| [path.as_os_str().as_encoded_bytes(), b"\0"].concat())
+            .collect::<Vec<_>>();
+        assert_eq!(out, expected);
+    }
+}
@@ -0,0 +1,152 @@
+use std::{
+    ffi::OsStr,
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::Entry;
+
+static SPILLS: AtomicUsize = AtomicUsize::new(0);
+
+/// Rendered entries kept in memory up to a limit and written to a temp file
+/// after that
+///
+/// Recursive listings collect every line before printing so the metadata
+/// columns can be aligned, which doesn't fit in memory for millions of files.
+/// Spilled rows only keep the path of their entry, so its metadata is read
+/// again when the rows are replayed and entries removed in the meantime are
+/// skipped.
+pub struct Spill {
+    limit: Option<usize>,
+    rows: Vec<(Entry, String)>,
+    file: Option<(PathBuf, BufWriter<File>)>,
+}
+
+impl Spill {
+    /// Keep at most `limit` rows in memory, `None` for no limit
+    pub fn new(limit: Option<usize>) -> Self {
+        Self {
+            limit,
+            rows: Vec::new(),
+            file: None,
+        }
+    }
+
+    /// Whether any rows were written to disk
+    pub fn spilled(&self) -> bool {
+        self.file.is_some()
+    }
+
+    pub fn push(&mut self, entry: Entry, line: String) -> io::Result<()> {
+        if self.limit.is_none_or(|limit| self.rows.len() < limit) {
+            self.rows.push((entry, line));
+            return Ok(());
+        }
+
+        let (_, file) = match self.file.as_mut() {
+            Some(file) => file,
+            None => {
+                let path = std::env::temp_dir().join(format!(
+                    "xf-spill-{}-{}",
+                    std::process::id(),
+                    SPILLS.fetch_add(1, Ordering::Relaxed)
+                ));
+                let file = File::options().write(true).create_new(true).open(&path)?;
+                self.file.insert((path, BufWriter::new(file)))
+            }
+        };
+        write_record(file, entry.path().as_os_str().as_encoded_bytes())?;
+        write_record(file, line.as_bytes())
+    }
+
+    /// Call `f` with every row in the order they were pushed
+    pub fn for_each<F>(&mut self, mut f: F) -> io::Result<()>
+    where
+        F: FnMut(&Entry, &str) -> io::Result<()>,
+    {
+        for (entry, line) in &self.rows {
+            f(entry, line)?;
+        }
+
+        let Some((path, file)) = self.file.as_mut() else {
+            return Ok(());
+        };
+        file.flush()?;
+
+        let mut reader = BufReader::new(File::open(&*path)?);
+        while let Some(path) = read_record(&mut reader)? {
+            let line = read_record(&mut reader)?.unwrap_or_default();
+            // SAFETY: The bytes were written by `as_encoded_bytes` in this process
+            let path = Path::new(unsafe { OsStr::from_encoded_bytes_unchecked(&path) });
+            let Ok(entry) = fs::symlink_metadata(path)
+                .map_err(Into::into)
+                .and_then(|meta| Entry::with_metadata(path.to_path_buf(), meta))
+            else {
+                continue;
+            };
+            f(&entry, &String::from_utf8_lossy(&line))?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Spill {
+    fn drop(&mut self) {
+        if let Some((path, _)) = self.file.take() {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Length prefixed bytes, names may contain newlines so lines can't be used
+fn write_record<W: Write>(writer: &mut W, bytes: &[u8]) -> io::Result<()> {
+    writer.write_all(&(bytes.len() as u64).to_le_bytes())?;
+    writer.write_all(bytes)
+}
+
+fn read_record<R: Read>(reader: &mut R) -> io::Result<Option<Vec<u8>>> {
+    let mut len = [0u8; 8];
+    match reader.read_exact(&mut len) {
+        Ok(()) => {}
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err),
+    }
+    let mut bytes = vec![0; u64::from_le_bytes(len) as usize];
+    reader.read_exact(&mut bytes)?;
+    Ok(Some(bytes))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn spill_keeps_order() {
+        let base = TempDir::new("spill-test");
+
+        let mut spill = Spill::new(Some(2));
+        for name in ["a", "b\nnewline", "c", "d"] {
+            std::fs::write(base.join(name), "").unwrap();
+            let entry = Entry::try_from(base.join(name).as_path()).unwrap();
+            spill.push(entry, format!("line {name}")).unwrap();
+        }
+        assert!(spill.spilled());
+
+        let mut rows = Vec::new();
+        spill
+            .for_each(|entry, line| {
+                rows.push((entry.file_name().to_string(), line.to_string()));
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(
+            rows,
+            ["a", "b\nnewline", "c", "d"].map(|name| (name.to_string(), format!("line {name}")))
+        );
+
+        drop(spill);
+    }
+}
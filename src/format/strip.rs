@@ -0,0 +1,86 @@
+use std::io::{self, Write};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum State {
+    #[default]
+    Text,
+    /// After an `ESC`
+    Escape,
+    /// Inside a control sequence, `ESC [ ... m`
+    Csi,
+    /// Inside an operating system command like a hyperlink, `ESC ] ... ESC \`
+    Osc,
+    /// After an `ESC` inside an operating system command
+    OscEscape,
+}
+
+/// Writer that drops ANSI escape sequences so styled output is written as
+/// plain text, see [`crate::style::ColorMode`]
+///
+/// Sequences split across writes are still removed.
+pub struct StripAnsi<W: Write> {
+    inner: W,
+    state: State,
+}
+
+impl<W: Write> StripAnsi<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            state: State::default(),
+        }
+    }
+}
+
+impl<W: Write> Write for StripAnsi<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut text = Vec::with_capacity(buf.len());
+        for &byte in buf {
+            self.state = match (self.state, byte) {
+                (State::Text, 0x1b) => State::Escape,
+                (State::Text, _) => {
+                    text.push(byte);
+                    State::Text
+                }
+                (State::Escape, b'[') => State::Csi,
+                (State::Escape, b']') => State::Osc,
+                (State::Escape, _) => State::Text,
+                (State::Csi, 0x40..=0x7e) => State::Text,
+                (State::Csi, _) => State::Csi,
+                (State::Osc, 0x07) => State::Text,
+                (State::Osc, 0x1b) => State::OscEscape,
+                (State::Osc, _) => State::Osc,
+                (State::OscEscape, b'\\') => State::Text,
+                (State::OscEscape, _) => State::Osc,
+            };
+        }
+        self.inner.write_all(&text)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use owo_colors::OwoColorize;
+
+    #[test]
+    fn strip_escapes() {
+        let styled = format!(
+            "{} {} {}",
+            "dir".blue().bold(),
+            crate::style::hyperlink("file:///tmp/a", "a"),
+            "plain"
+        );
+        let mut out = StripAnsi::new(Vec::new());
+        // Split in the middle of the escape sequences
+        for chunk in styled.as_bytes().chunks(3) {
+            out.write_all(chunk).unwrap();
+        }
+        assert_eq!(String::from_utf8(out.inner).unwrap(), "dir a plain");
+    }
+}
@@ -0,0 +1,530 @@
+use std::{fmt::Display, io::Write};
+
+use chrono::{DateTime, Local};
+use owo_colors::OwoColorize;
+
+use crate::{style::Colorizer, text, Entry, FileSystem};
+
+use super::{inode, FlushEvery, Formatter};
+
+/// Entry attributes available as template placeholders
+pub const FIELDS: &[&str] = &[
+    "name", "path", "ext", "type", "perms", "mode", "size", "bytes", "date", "modified",
+    "accessed", "created", "owner", "group", "inode", "lines", "cloud", "data",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Align {
+    Left,
+    Right,
+    Center,
+}
+
+/// Conditions usable in `{if ...}` besides the [`FIELDS`]
+pub const CONDITIONS: &[&str] = &["dir", "file", "link", "hidden", "executable"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum FieldStyle {
+    /// Style of the group the entry belongs to
+    Entry,
+    /// Style of a named group, `DIR`
+    Group(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Field {
+        name: String,
+        align: Align,
+        width: usize,
+        style: Option<FieldStyle>,
+    },
+    If {
+        condition: String,
+        negate: bool,
+        then: Vec<Segment>,
+        otherwise: Vec<Segment>,
+    },
+}
+
+/// One line per entry laid out by a user template, `{perms} {size:>8} {name}`
+///
+/// Placeholders are any of [`FIELDS`] with an optional `<`, `>`, or `^`
+/// alignment and width after a `:`. Braces are escaped by doubling them, `{{`.
+///
+/// `{if dir}/{else} {end}` only renders its body when the condition holds, see
+/// [`CONDITIONS`], and `{if !dir}` when it doesn't. A field as the condition
+/// holds when its value isn't empty or `-`.
+///
+/// Values are unstyled so the output can be used by scripts as is, unless the
+/// placeholder has a style directive. `{name|style}` uses the style of the
+/// entry's group and `{name|style=DIR}` the style of a named group.
+///
+/// `{data}` is the caller's own value for an entry, see [`Template::render_with`]
+/// and [`FileSystem::walk_with`], and is empty otherwise.
+pub struct Template {
+    file_system: FileSystem,
+    segments: Vec<Segment>,
+    flush_every: usize,
+}
+
+impl Template {
+    pub fn new(file_system: FileSystem, template: &str) -> Result<Self, String> {
+        Ok(Self {
+            file_system,
+            segments: parse(template)?,
+            flush_every: 1,
+        })
+    }
+
+    /// Check that a template is valid without a file system to list
+    pub fn check(template: &str) -> Result<(), String> {
+        parse(template).map(|_| ())
+    }
+
+    /// Flush the output after every `n` lines instead of every line
+    pub fn flush_every(mut self, n: usize) -> Self {
+        self.flush_every = n;
+        self
+    }
+
+    /// Render the template for a single entry
+    pub fn render(&self, entry: &Entry) -> String {
+        self.render_with(entry, &"")
+    }
+
+    /// Render the template for a single entry with `data` as the `{data}`
+    /// placeholder
+    ///
+    /// Style directives are ignored, see [`Template::render_styled`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use xf::{format::Template, FileSystem};
+    ///
+    /// let file_system = FileSystem::from("src");
+    /// let template = Template::new(file_system.clone(), "{name:<20} {data}").unwrap();
+    /// let walked = file_system
+    ///     .walk_with(|entry| entry.path().components().count())
+    ///     .unwrap();
+    /// for (entry, depth) in walked {
+    ///     println!("{}", template.render_with(&entry, &depth));
+    /// }
+    /// ```
+    pub fn render_with<T: Display>(&self, entry: &Entry, data: &T) -> String {
+        let mut line = String::new();
+        let colorizer = Colorizer::default();
+        render_segments(
+            &self.segments,
+            entry,
+            &data.to_string(),
+            (&colorizer, false),
+            &mut line,
+        );
+        line
+    }
+
+    /// Render the template for a single entry with the style directives
+    /// applied from the colorizer's groups
+    ///
+    /// Sizes and dates follow the colorizer's units, time style, and clock.
+    pub fn render_styled(&self, entry: &Entry, colorizer: &Colorizer) -> String {
+        let mut line = String::new();
+        render_segments(&self.segments, entry, "", (colorizer, true), &mut line);
+        line
+    }
+}
+
+/// Render segments with the colorizer's formats, and its styles when `styled`
+fn render_segments(
+    segments: &[Segment],
+    entry: &Entry,
+    data: &str,
+    (colorizer, styled): (&Colorizer, bool),
+    line: &mut String,
+) {
+    let value = |name: &str| match name {
+        "data" => data.to_string(),
+        name => field(entry, name, colorizer),
+    };
+
+    for segment in segments {
+        match segment {
+            Segment::Literal(text) => line.push_str(text),
+            Segment::Field {
+                name,
+                align,
+                width,
+                style,
+            } => {
+                let value = value(name);
+                let padding = width.saturating_sub(text::width(&value));
+                let (before, after) = match align {
+                    Align::Left => (0, padding),
+                    Align::Right => (padding, 0),
+                    Align::Center => (padding / 2, padding - padding / 2),
+                };
+                let style = match style.as_ref().filter(|_| styled) {
+                    Some(FieldStyle::Entry) => colorizer.group_of(entry).map(|group| group.style()),
+                    Some(FieldStyle::Group(group)) => colorizer.group_style(group),
+                    None => None,
+                };
+
+                line.push_str(&" ".repeat(before));
+                match style {
+                    Some(style) => line.push_str(&value.style(style).to_string()),
+                    None => line.push_str(&value),
+                }
+                line.push_str(&" ".repeat(after));
+            }
+            Segment::If {
+                condition,
+                negate,
+                then,
+                otherwise,
+            } => {
+                let holds = match condition.as_str() {
+                    "dir" => entry.is_dir(),
+                    "file" => entry.is_file(),
+                    "link" => entry.metadata().is_symlink(),
+                    "hidden" => entry.is_hidden(),
+                    "executable" => entry.is_executable(),
+                    name => !matches!(value(name).as_str(), "" | "-"),
+                };
+                let branch = if holds != *negate { then } else { otherwise };
+                render_segments(branch, entry, data, (colorizer, styled), line);
+            }
+        }
+    }
+}
+
+impl Formatter for Template {
+    fn render_to(
+        &mut self,
+        writer: &mut dyn Write,
+        colorizer: Colorizer,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut out = FlushEvery::new(writer, self.flush_every);
+        for entry in self.file_system.entries()? {
+            writeln!(out, "{}", self.render_styled(&entry, &colorizer))?;
+        }
+        out.flush()?;
+        Ok(())
+    }
+
+    fn render_entry(&mut self, entry: &Entry, colorizer: &Colorizer) -> String {
+        self.render_styled(entry, colorizer)
+    }
+}
+
+/// An `{if}` block that hasn't been closed yet along with the segments before it
+struct Block {
+    condition: String,
+    negate: bool,
+    /// Segments of the block before an `{else}`
+    then: Option<Vec<Segment>>,
+    outer: Vec<Segment>,
+}
+
+fn parse(template: &str) -> Result<Vec<Segment>, String> {
+    let mut segments = Vec::new();
+    let mut blocks = Vec::<Block>::new();
+    let mut literal = String::new();
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                literal.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                literal.push('}');
+            }
+            '}' => return Err("unmatched '}' in template, use '}}' for a literal brace".into()),
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => placeholder.push(c),
+                        None => return Err(format!("unclosed placeholder '{{{placeholder}'")),
+                    }
+                }
+
+                if !literal.is_empty() {
+                    segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                }
+                match placeholder.trim() {
+                    "else" => match blocks.last_mut() {
+                        Some(block) if block.then.is_none() => {
+                            block.then = Some(std::mem::take(&mut segments))
+                        }
+                        Some(_) => return Err("'{else}' used twice in one '{if}'".into()),
+                        None => return Err("'{else}' without an '{if}'".into()),
+                    },
+                    "end" => {
+                        let block = blocks.pop().ok_or("'{end}' without an '{if}'")?;
+                        let (then, otherwise) = match block.then {
+                            Some(then) => (then, std::mem::take(&mut segments)),
+                            None => (std::mem::take(&mut segments), Vec::new()),
+                        };
+                        segments = block.outer;
+                        segments.push(Segment::If {
+                            condition: block.condition,
+                            negate: block.negate,
+                            then,
+                            otherwise,
+                        });
+                    }
+                    other => match other.strip_prefix("if ") {
+                        Some(condition) => {
+                            let condition = condition.trim();
+                            let (negate, condition) = match condition.strip_prefix('!') {
+                                Some(condition) => (true, condition.trim()),
+                                None => (false, condition),
+                            };
+                            if !CONDITIONS.contains(&condition) && !FIELDS.contains(&condition) {
+                                return Err(format!(
+                                    "unknown condition '{condition}', expected one of {}, or a placeholder",
+                                    CONDITIONS.join(", ")
+                                ));
+                            }
+                            blocks.push(Block {
+                                condition: condition.to_string(),
+                                negate,
+                                then: None,
+                                outer: std::mem::take(&mut segments),
+                            });
+                        }
+                        None => segments.push(placeholder_segment(&placeholder)?),
+                    },
+                }
+            }
+            c => literal.push(c),
+        }
+    }
+
+    if let Some(block) = blocks.last() {
+        return Err(format!(
+            "'{{if {}}}' is missing an '{{end}}'",
+            block.condition
+        ));
+    }
+    if !literal.is_empty() {
+        segments.push(Segment::Literal(literal));
+    }
+    Ok(segments)
+}
+
+fn placeholder_segment(placeholder: &str) -> Result<Segment, String> {
+    let (placeholder, directive) = placeholder.split_once('|').unwrap_or((placeholder, ""));
+    let (name, spec) = placeholder.split_once(':').unwrap_or((placeholder, ""));
+    let name = name.trim();
+    if !FIELDS.contains(&name) {
+        return Err(format!(
+            "unknown placeholder '{name}', expected one of {}",
+            FIELDS.join(", ")
+        ));
+    }
+
+    let style = match directive.trim() {
+        "" => None,
+        "style" => Some(FieldStyle::Entry),
+        directive => match directive.strip_prefix("style=") {
+            Some(group) if !group.trim().is_empty() => {
+                Some(FieldStyle::Group(group.trim().to_string()))
+            }
+            _ => {
+                return Err(format!(
+                "invalid directive '{directive}' for '{name}', expected 'style' or 'style=GROUP'"
+            ))
+            }
+        },
+    };
+
+    let spec = spec.trim();
+    let (align, width) = match spec.chars().next() {
+        Some('<') => (Align::Left, &spec[1..]),
+        Some('>') => (Align::Right, &spec[1..]),
+        Some('^') => (Align::Center, &spec[1..]),
+        _ => (Align::Left, spec),
+    };
+    let width = match width {
+        "" => 0,
+        width => width
+            .parse()
+            .map_err(|_| format!("invalid width '{width}' for '{name}'"))?,
+    };
+
+    Ok(Segment::Field {
+        name: name.to_string(),
+        align,
+        width,
+        style,
+    })
+}
+
+fn timestamp(time: std::io::Result<std::time::SystemTime>) -> String {
+    time.map(|time| DateTime::<Local>::from(time).to_rfc3339())
+        .unwrap_or("-".to_string())
+}
+
+/// Unstyled value of a field for an entry
+fn field(entry: &Entry, name: &str, colorizer: &Colorizer) -> String {
+    let meta = entry.metadata();
+    match name {
+        "name" => entry.file_name().to_string(),
+        "path" => entry.path().display().to_string(),
+        "ext" => entry.extension().unwrap_or_default(),
+        "type" if meta.is_symlink() => "link".to_string(),
+        "type" if entry.is_dir() => "dir".to_string(),
+        "type" => "file".to_string(),
+        "perms" => {
+            let kind = match (meta.is_symlink(), entry.is_dir()) {
+                (true, _) => 'l',
+                (_, true) => 'd',
+                _ => '-',
+            };
+            format!("{kind}{}", entry.permissions())
+        }
+        "mode" => format!("{:03o}", entry.permissions().mode()),
+        "size" => colorizer.size_text(meta.len()),
+        "bytes" => meta.len().to_string(),
+        "date" => colorizer.date_modified_text(entry),
+        "modified" => timestamp(meta.modified()),
+        "accessed" => timestamp(meta.accessed()),
+        "created" => timestamp(meta.created()),
+        "owner" => entry.permissions().user().name.clone(),
+        "group" => entry.permissions().group().name.clone(),
+        "inode" => inode(entry)
+            .map(|inode| inode.to_string())
+            .unwrap_or("-".to_string()),
+        "lines" => entry
+            .line_count()
+            .map(|lines| lines.to_string())
+            .unwrap_or("-".to_string()),
+        "cloud" => entry
+            .cloud_status()
+            .map(|status| status.name().to_string())
+            .unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn parse_template() {
+        assert_eq!(
+            parse("{perms} {size:>8} {{{name}}}").unwrap(),
+            vec![
+                Segment::Field {
+                    name: "perms".into(),
+                    align: Align::Left,
+                    width: 0,
+                    style: None,
+                },
+                Segment::Literal(" ".into()),
+                Segment::Field {
+                    name: "size".into(),
+                    align: Align::Right,
+                    width: 8,
+                    style: None,
+                },
+                Segment::Literal(" {".into()),
+                Segment::Field {
+                    name: "name".into(),
+                    align: Align::Left,
+                    width: 0,
+                    style: None,
+                },
+                Segment::Literal("}".into()),
+            ]
+        );
+
+        assert!(parse("{colour}").is_err());
+        assert!(parse("{name").is_err());
+        assert!(parse("{size:>wide}").is_err());
+    }
+
+    #[test]
+    fn conditionals_and_styles() {
+        use owo_colors::Style;
+
+        assert_eq!(
+            parse("{name:<6|style=DIR}{if !dir}{else}/{end}").unwrap(),
+            vec![
+                Segment::Field {
+                    name: "name".into(),
+                    align: Align::Left,
+                    width: 6,
+                    style: Some(FieldStyle::Group("DIR".into())),
+                },
+                Segment::If {
+                    condition: "dir".into(),
+                    negate: true,
+                    then: vec![],
+                    otherwise: vec![Segment::Literal("/".into())],
+                },
+            ]
+        );
+        assert!(parse("{if dir}").is_err());
+        assert!(parse("{end}").is_err());
+        assert!(parse("{if colour}{end}").is_err());
+        assert!(parse("{name|bold}").is_err());
+
+        let base = TempDir::new("template");
+        std::fs::create_dir_all(base.join("dir")).unwrap();
+        std::fs::write(base.join("file.rs"), "").unwrap();
+
+        let template = Template::new(
+            FileSystem::from(base.path()),
+            "{name:^9}{if dir}/{else}{if ext}.{ext}{end}{end}|",
+        )
+        .unwrap();
+        let dir = Entry::try_from(base.join("dir").as_path()).unwrap();
+        let file = Entry::try_from(base.join("file.rs").as_path()).unwrap();
+        assert_eq!(template.render(&dir), "   dir   /|");
+        assert_eq!(template.render(&file), " file.rs .rs|");
+
+        let styled = Template::new(FileSystem::from(base.path()), "{name:<5|style=DIR}|").unwrap();
+        let colorizer = Colorizer::default_theme();
+        assert_eq!(
+            styled.render_styled(&file, &colorizer),
+            format!("{}|", "file.rs".style(Style::new().blue()))
+        );
+        assert_eq!(
+            styled.render_styled(&dir, &colorizer),
+            format!("{}  |", "dir".style(Style::new().blue()))
+        );
+    }
+
+    #[test]
+    fn colorizer_formats() {
+        use crate::{
+            clock::FixedClock,
+            style::{SizeUnits, TimeStyle},
+        };
+
+        let base = TempDir::new("template-formats");
+        std::fs::write(base.join("file"), vec![0; 2000]).unwrap();
+        let file = Entry::try_from(base.join("file").as_path()).unwrap();
+        let modified = file.metadata().modified().unwrap();
+
+        let colorizer = Colorizer::default()
+            .size_units(SizeUnits::Si)
+            .time_style(TimeStyle::Relative)
+            .clock(FixedClock(
+                modified + std::time::Duration::from_secs(3 * 86_400),
+            ));
+        let template = Template::new(FileSystem::from(base.path()), "{size}|{date}").unwrap();
+        assert_eq!(template.render_styled(&file, &colorizer), "2k|3 days ago");
+    }
+}
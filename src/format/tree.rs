@@ -1,129 +1,785 @@
-use owo_colors::{colors::xterm, OwoColorize};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
-use crate::{ignore::GitIgnore, style::Colorizer, Entry, FileSystem};
+use hashbrown::{HashMap, HashSet};
+use owo_colors::OwoColorize;
 
-use super::Formatter;
+use crate::{
+    diagnostic::Diagnostic,
+    filter::Filter,
+    ignore::GitIgnore,
+    listable::Listable,
+    style::{Colorizer, Spacer},
+    text, Entry, FileSystem,
+};
 
-pub struct Tree(FileSystem, bool);
+use super::{FlushEvery, Formatter, Spill};
+
+/// Glyphs used to draw the branches of a [`Tree`]
+///
+/// Parses from `unicode`, `rounded`, `ascii`, or three comma separated custom
+/// glyphs, `fork,last,line`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Branches {
+    /// Branch of an entry that has more siblings after it, `├`
+    pub fork: String,
+    /// Branch of the last entry in a directory, `└`
+    pub last: String,
+    /// Line continuing past the children of an entry with more siblings, `│`
+    pub line: String,
+}
+
+impl Default for Branches {
+    fn default() -> Self {
+        Self::unicode()
+    }
+}
+
+impl Branches {
+    pub fn new<S: ToString>(fork: S, last: S, line: S) -> Self {
+        Self {
+            fork: fork.to_string(),
+            last: last.to_string(),
+            line: line.to_string(),
+        }
+    }
+
+    pub fn unicode() -> Self {
+        Self::new("├", "└", "│")
+    }
+
+    pub fn rounded() -> Self {
+        Self::new("├", "╰", "│")
+    }
+
+    /// Plain ASCII for terminals and fonts without box drawing characters
+    pub fn ascii() -> Self {
+        Self::new("|--", "\\--", "|")
+    }
+
+    /// Columns taken up by a branch, the shorter glyphs are padded to it
+    fn width(&self) -> usize {
+        text::width(&self.fork).max(text::width(&self.last))
+    }
+
+    /// Branch and the indentation of the children for an entry
+    fn of(&self, last: bool) -> (String, String) {
+        let width = self.width();
+        match last {
+            true => (text::pad(&self.last, width), " ".repeat(width + 1)),
+            false => (
+                text::pad(&self.fork, width),
+                text::pad(&self.line, width + 1),
+            ),
+        }
+    }
+}
+
+impl FromStr for Branches {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "unicode" => Ok(Self::unicode()),
+            "rounded" => Ok(Self::rounded()),
+            "ascii" => Ok(Self::ascii()),
+            custom => match custom.split(',').collect::<Vec<_>>()[..] {
+                [fork, last, line] => Ok(Self::new(fork, last, line)),
+                _ => Err(format!(
+                    "unknown branch style '{custom}', expected unicode, rounded, ascii, or 'fork,last,line'"
+                )),
+            },
+        }
+    }
+}
+
+/// Number of visible children and total size of the files below a directory
+#[derive(Debug, Clone, Copy, Default)]
+struct Summary {
+    items: usize,
+    size: u64,
+}
+
+impl Summary {
+    fn text(&self, colorizer: &Colorizer) -> String {
+        let items = match self.items {
+            0 => return "(empty)".to_string(),
+            1 => "1 item".to_string(),
+            n => format!("{n} items"),
+        };
+        format!("({items}, {})", colorizer.size_text(self.size).trim())
+    }
+}
+
+pub struct Tree {
+    file_system: FileSystem,
+    long: bool,
+    max_depth: Option<usize>,
+    flush_every: usize,
+    flat: bool,
+    memory_limit: Option<usize>,
+    branches: Branches,
+    compact_dirs: bool,
+    matching: Option<Box<dyn Filter>>,
+    /// Matches and their ancestors, found before the tree is collected
+    expanded: Option<HashSet<PathBuf>>,
+    summaries: Option<HashMap<PathBuf, Summary>>,
+    /// Rendered diagnostic line printed below each directory that has one
+    diagnostics: Option<HashMap<PathBuf, String>>,
+}
 
 impl Tree {
     pub fn new(file_system: FileSystem, long: bool) -> Self {
-        Self(file_system, long)
+        Self {
+            max_depth: file_system.max_depth(),
+            file_system,
+            long,
+            flush_every: 1,
+            flat: false,
+            memory_limit: None,
+            branches: Branches::default(),
+            compact_dirs: false,
+            matching: None,
+            expanded: None,
+            summaries: None,
+            diagnostics: None,
+        }
     }
 
-    pub fn print_all(
+    /// Print paths relative to the root indented by depth instead of drawing
+    /// the tree, which diffs and copies more cleanly
+    pub fn flat(mut self, flat: bool) -> Self {
+        self.flat = flat;
+        self
+    }
+
+    /// Collapse directories that only contain a single directory into one
+    /// line, `src/format/helpers`
+    pub fn compact_dirs(mut self, compact_dirs: bool) -> Self {
+        self.compact_dirs = compact_dirs;
+        self
+    }
+
+    /// Only show entries kept by `filter` along with the directories leading
+    /// to them, everything else is hidden
+    ///
+    /// Unlike the filter of the [`FileSystem`] this doesn't stop directories
+    /// that don't match from being walked.
+    pub fn matching<F: Filter + 'static>(mut self, filter: F) -> Self {
+        self.matching = Some(Box::new(filter));
+        self
+    }
+
+    /// Follow every directory with its number of visible children and the
+    /// total size of the files below it, `src (14 items, 2.3M)`
+    ///
+    /// Only what is walked is counted, so directories past the max depth
+    /// don't get a summary.
+    pub fn summaries(mut self, summaries: bool) -> Self {
+        self.summaries = summaries.then(HashMap::new);
+        self
+    }
+
+    /// Show why a directory wasn't walked into as a child node, `└ ⚠ cycle
+    /// back to ../..`, instead of leaving it empty
+    ///
+    /// Directories that can't be read are marked after their name otherwise.
+    pub fn diagnostics(mut self, diagnostics: bool) -> Self {
+        self.diagnostics = diagnostics.then(HashMap::new);
+        self
+    }
+
+    /// Record the diagnostic line shown below a directory
+    fn diagnose(
+        &mut self,
+        entry: &Entry,
+        indent: &str,
+        depth: usize,
+        diagnostic: Diagnostic,
+        colorizer: &Colorizer,
+    ) {
+        let Some(diagnostics) = self.diagnostics.as_mut() else {
+            return;
+        };
+        let line = if self.flat {
+            format!("{indent}{}", format!("⚠ {diagnostic}").yellow())
+        } else if colorizer.is_accessible() {
+            format!(
+                "{indent}L{} {}",
+                depth + 1,
+                format!("warning: {diagnostic}").yellow()
+            )
+        } else {
+            format!(
+                "{indent}{} {}",
+                self.branches.of(true).0,
+                format!("⚠ {diagnostic}").yellow()
+            )
+        };
+        diagnostics.insert(entry.path().to_path_buf(), line);
+    }
+
+    /// Line of an entry with the indentation and branch of its depth
+    fn line(&self, indent: &str, branch: &str, entry: &Entry, colorizer: &Colorizer) -> String {
+        self.compact_line(indent, branch, "", entry, colorizer)
+    }
+
+    /// Same as [`Tree::line`] with the names of collapsed parent directories in
+    /// front of the entry, flat lines already start with their parents
+    fn compact_line(
         &self,
+        indent: &str,
+        branch: &str,
+        parents: &str,
+        entry: &Entry,
+        colorizer: &Colorizer,
+    ) -> String {
+        if !self.flat {
+            let parents = match colorizer.group_of(entry) {
+                Some(group) if !parents.is_empty() => parents.style(group.style()).to_string(),
+                _ => parents.to_string(),
+            };
+            return format!("{indent}{branch} {parents}{}", colorizer.file(entry));
+        }
+
+        let parent = entry
+            .path()
+            .parent()
+            .and_then(|parent| parent.strip_prefix(&self.file_system.path).ok())
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .map(|parent| format!("{}{}", parent.display(), std::path::MAIN_SEPARATOR))
+            .unwrap_or_default();
+        format!("{indent}{}{}", parent.dimmed(), colorizer.file(entry))
+    }
+
+    /// Limit how many levels of the tree are displayed, `1` only shows the
+    /// entries of the root directory
+    ///
+    /// Defaults to [`FileSystem::max_depth`].
+    pub fn max_depth(mut self, depth: Option<usize>) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Flush the output after every `n` lines instead of every line
+    pub fn flush_every(mut self, n: usize) -> Self {
+        self.flush_every = n;
+        self
+    }
+
+    /// Glyphs used to draw the branches, defaults to [`Branches::unicode`]
+    pub fn branches(mut self, branches: Branches) -> Self {
+        self.branches = branches;
+        self
+    }
+
+    /// Keep at most `rows` lines in memory and spill the rest to a temp file,
+    /// see [`Spill`]
+    pub fn memory_limit(mut self, rows: Option<usize>) -> Self {
+        self.memory_limit = rows;
+        self
+    }
+
+    // The metadata columns are rendered after the whole tree is collected so
+    // they can be aligned to the widest value across every depth.
+    fn collect(
+        &mut self,
+        rows: &mut Spill,
         entries: &[Entry],
         ignore: Option<GitIgnore>,
         indent: String,
+        depth: usize,
+        colorizer: &Colorizer,
+    ) -> Result<Summary, Box<dyn std::error::Error>> {
+        let entries = self.visible(entries, &ignore);
+        let mut summary = Summary {
+            items: entries.len(),
+            size: 0,
+        };
+
+        for (i, entry) in entries.iter().enumerate() {
+            // Screen readers get a spoken depth marker instead of box drawing
+            let (branch, nested) = if self.flat {
+                (String::new(), "  ".to_string())
+            } else if colorizer.is_accessible() {
+                (format!("L{depth}"), "  ".to_string())
+            } else {
+                self.branches.of(i + 1 == entries.len())
+            };
+
+            if !entry.is_dir() {
+                summary.size += entry.metadata().len();
+            }
+            if !self.file_system.descends_into(entry)
+                || self.max_depth.is_some_and(|max| depth >= max)
+            {
+                rows.push(
+                    (*entry).clone(),
+                    self.line(&indent, &branch, entry, colorizer),
+                )?;
+                let diagnostic = match self.file_system.cycle(entry) {
+                    Some(cycle) => Some(cycle),
+                    None if entry.is_dir() && self.file_system.descends_into(entry) => {
+                        Some(Diagnostic::MaxDepth)
+                    }
+                    None => None,
+                };
+                if let Some(diagnostic) = diagnostic {
+                    let nested = format!("{indent}{nested}");
+                    self.diagnose(entry, &nested, depth, diagnostic, colorizer);
+                }
+                continue;
+            }
+
+            match entry.entries(&self.file_system) {
+                Ok(mut rec) => {
+                    let mut gitignore = nested_ignore(entry, &ignore, self.file_system.strict())?;
+
+                    // Look ahead through directories with a single child
+                    // directory and draw them as one line
+                    let mut last = (*entry).clone();
+                    let mut parents = String::new();
+                    let mut depth = depth;
+                    while self.compact_dirs && self.max_depth.is_none_or(|max| depth + 1 < max) {
+                        let child = match self.visible(&rec, &gitignore)[..] {
+                            [child] if self.file_system.descends_into(child) => child.clone(),
+                            _ => break,
+                        };
+                        let Ok(nested) = child.entries(&self.file_system) else {
+                            break;
+                        };
+                        gitignore = nested_ignore(&child, &gitignore, self.file_system.strict())?;
+                        parents.push_str(last.file_name());
+                        parents.push(std::path::MAIN_SEPARATOR);
+                        last = child;
+                        rec = nested;
+                        depth += 1;
+                    }
+
+                    rows.push(
+                        last.clone(),
+                        self.compact_line(&indent, &branch, &parents, &last, colorizer),
+                    )?;
+                    let nested = self.collect(
+                        rows,
+                        &rec,
+                        gitignore,
+                        format!("{indent}{nested}"),
+                        depth + 1,
+                        colorizer,
+                    )?;
+                    summary.size += nested.size;
+                    if let Some(summaries) = self.summaries.as_mut() {
+                        summaries.insert(last.path().to_path_buf(), nested);
+                    }
+                }
+                Err(err) if self.file_system.strict() => {
+                    return Err(format!("{}: {err}", entry.path.display()).into())
+                }
+                Err(err) if self.diagnostics.is_some() => {
+                    rows.push(
+                        (*entry).clone(),
+                        self.line(&indent, &branch, entry, colorizer),
+                    )?;
+                    let nested = format!("{indent}{nested}");
+                    self.diagnose(
+                        entry,
+                        &nested,
+                        depth,
+                        Diagnostic::unreadable(err.as_ref()),
+                        colorizer,
+                    );
+                }
+                Err(err) => rows.push(
+                    (*entry).clone(),
+                    format!(
+                        "{} {}",
+                        self.line(&indent, &branch, entry, colorizer),
+                        format!("[{}]", Diagnostic::unreadable(err.as_ref())).red()
+                    ),
+                )?,
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Draw any [`Listable`] items along with their [`Listable::children`]
+    /// instead of walking the file system
+    pub fn render_items(
+        &self,
+        writer: &mut dyn Write,
+        items: &[&dyn Listable],
         colorizer: &Colorizer,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        for entry in entries[..entries.len().saturating_sub(1)]
+        let mut rows = Vec::new();
+        self.collect_items(&mut rows, items, String::new(), 1, colorizer);
+
+        let (mut size_width, mut date_width) = (colorizer.size_width(), 0);
+        if self.long {
+            for (item, _) in &rows {
+                size_width = size_width.max(text::width(&colorizer.item_size_text(*item)));
+                date_width = date_width.max(text::width(&colorizer.date_text(item.modified())));
+            }
+        }
+
+        let mut out = FlushEvery::new(writer, self.flush_every);
+        for (item, line) in rows {
+            if self.long {
+                writeln!(
+                    out,
+                    "{} {} {} {line}",
+                    colorizer.item_permissions(item),
+                    colorizer.item_size_aligned(item, size_width),
+                    colorizer.date_aligned(item.modified(), date_width),
+                )?;
+            } else {
+                writeln!(out, "{line}")?;
+            }
+        }
+        out.flush()?;
+        Ok(())
+    }
+
+    fn collect_items<'a>(
+        &self,
+        rows: &mut Vec<(&'a dyn Listable, String)>,
+        items: &[&'a dyn Listable],
+        indent: String,
+        depth: usize,
+        colorizer: &Colorizer,
+    ) {
+        for (i, item) in items.iter().enumerate() {
+            let (branch, nested) = if self.flat {
+                (String::new(), "  ".to_string())
+            } else if colorizer.is_accessible() {
+                (format!("L{depth}"), "  ".to_string())
+            } else {
+                self.branches.of(i + 1 == items.len())
+            };
+            let line = match self.flat {
+                true => format!("{indent}{}", colorizer.item(*item)),
+                false => format!("{indent}{branch} {}", colorizer.item(*item)),
+            };
+            rows.push((*item, line));
+
+            if self.max_depth.is_none_or(|max| depth < max) {
+                let children = item.children();
+                self.collect_items(
+                    rows,
+                    &children,
+                    format!("{indent}{nested}"),
+                    depth + 1,
+                    colorizer,
+                );
+            }
+        }
+    }
+
+    /// Entries that aren't excluded by the ignore rules
+    ///
+    /// This prunes directories before anything is read so excluded ones,
+    /// e.g. `target/`, are never walked.
+    fn visible<'a>(&self, entries: &'a [Entry], ignore: &Option<GitIgnore>) -> Vec<&'a Entry> {
+        entries
             .iter()
             .filter(|e| {
                 ignore
                     .as_ref()
-                    .map(|v| v.include(e.path().strip_prefix(&self.0.path).unwrap()))
+                    .map(|v| v.include(e.path().strip_prefix(&self.file_system.path).unwrap()))
                     .unwrap_or(true)
             })
-        {
-            let permissions = if self.1 {
-                format!(
-                    "{} {} {} ",
-                    colorizer.permissions(entry),
-                    colorizer.file_size(entry),
-                    colorizer.date_modified(entry),
-                )
-            } else {
-                String::new()
-            };
+            .filter(|e| {
+                self.expanded
+                    .as_ref()
+                    .is_none_or(|expanded| expanded.contains(e.path()))
+            })
+            .collect()
+    }
+
+    /// Walk the whole tree and mark every match of [`Tree::matching`] along
+    /// with the chain of directories above it
+    fn expand(&self, filter: &dyn Filter) -> Result<HashSet<PathBuf>, Box<dyn std::error::Error>> {
+        let walked = self
+            .file_system
+            .clone()
+            .with_max_depth(self.max_depth)
+            .walk()?;
+        filter.prepare(&walked);
 
-            if entry.path.is_dir() {
-                println!("{permissions}{indent}├ {}", colorizer.file(entry));
-                let rec = entry.entries(&self.0)?;
-                let gitignore = match entry.path.join(".gitignore").exists() {
-                    true => Some(GitIgnore::try_from(entry.path.join(".gitignore"))?),
-                    false => None,
+        let mut expanded = HashSet::new();
+        for entry in walked.iter().filter(|entry| filter.keep(entry)) {
+            for ancestor in entry.path().ancestors() {
+                if ancestor == self.file_system.path || !expanded.insert(ancestor.to_path_buf()) {
+                    break;
                 }
-                .or_else(|| ignore.clone());
-                self.print_all(&rec, gitignore, format!("{indent}│ "), colorizer)?;
-            } else {
-                println!("{permissions}{indent}├ {}", colorizer.file(entry));
             }
         }
+        Ok(expanded)
+    }
+}
 
-        if let Some(last) = entries.last() {
-            let permissions = if self.1 {
-                format!(
-                    "{} {} {} ",
-                    colorizer.permissions(last),
-                    colorizer.file_size(last),
-                    colorizer.date_modified(last),
-                )
-            } else {
-                String::new()
-            };
+/// Ignore rules of a directory's own `.gitignore`, or the ones of its parent
+fn nested_ignore(
+    entry: &Entry,
+    ignore: &Option<GitIgnore>,
+    strict: bool,
+) -> Result<Option<GitIgnore>, Box<dyn std::error::Error>> {
+    Ok(load_ignore(&entry.path, strict)?.or_else(|| ignore.clone()))
+}
 
-            if last.path.is_dir() {
-                println!("{permissions}{indent}└ {}", colorizer.file(last));
-                let rec = last.entries(&self.0)?;
-                let gitignore = match last.path.join(".gitignore").exists() {
-                    true => Some(GitIgnore::try_from(last.path.join(".gitignore"))?),
-                    false => None,
-                };
-                self.print_all(&rec, gitignore, format!("{indent}  "), colorizer)?;
+/// The `.gitignore` of a directory, `None` when it doesn't have one
+///
+/// A file that can't be read is only an error in strict mode, see
+/// [`FileSystem::with_strict`].
+fn load_ignore(dir: &Path, strict: bool) -> Result<Option<GitIgnore>, Box<dyn std::error::Error>> {
+    let path = dir.join(".gitignore");
+    if !path.exists() {
+        return Ok(None);
+    }
+    match GitIgnore::load(&path, strict) {
+        Ok(ignore) => Ok(Some(ignore)),
+        Err(err) if strict => Err(err.into()),
+        Err(_) => Ok(None),
+    }
+}
+
+impl Formatter for Tree {
+    fn render_to(
+        &mut self,
+        writer: &mut dyn Write,
+        colorizer: Colorizer,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let entries = self.file_system.entries()?;
+        if let Some(filter) = self.matching.as_deref() {
+            self.expanded = Some(self.expand(filter)?);
+        }
+
+        // Flat paths are relative to the root so it isn't printed
+        let mut rows = Spill::new(self.memory_limit);
+        if !self.flat {
+            rows.push(
+                Entry::try_from(self.file_system.path.as_path())?,
+                colorizer.breadcrumb(&self.file_system.path),
+            )?;
+        }
+
+        let gitignore = load_ignore(&self.file_system.path, self.file_system.strict())?;
+        let summary = self.collect(&mut rows, &entries, gitignore, String::new(), 1, &colorizer)?;
+        if let Some(summaries) = self.summaries.as_mut() {
+            summaries.insert(self.file_system.path.clone(), summary);
+        }
+
+        // Global width pass so the metadata columns line up regardless of depth
+        let (mut size_width, mut date_width) = (0, 0);
+        if self.long {
+            rows.for_each(|entry, _| {
+                size_width = size_width.max(text::width(&colorizer.file_size_text(entry)));
+                date_width = date_width.max(text::width(&colorizer.date_modified_text(entry)));
+                Ok(())
+            })?;
+        }
+
+        let mut out = FlushEvery::new(writer, self.flush_every);
+        rows.for_each(|entry, line| {
+            let line = match self.summaries.as_ref().and_then(|s| s.get(entry.path())) {
+                Some(summary) => format!("{line} {}", summary.text(&colorizer).dimmed()),
+                None => line.to_string(),
+            };
+            if self.long {
+                writeln!(
+                    out,
+                    "{} {} {} {}",
+                    colorizer.permissions(entry),
+                    colorizer.file_size_aligned(entry, size_width),
+                    colorizer.date_modified_aligned(entry, date_width),
+                    line
+                )?;
             } else {
-                println!("{permissions}{indent}└ {}", colorizer.file(last));
+                writeln!(out, "{line}")?;
             }
-        }
+            match self.diagnostics.as_ref().and_then(|d| d.get(entry.path())) {
+                // Diagnostics line up with the names below the metadata columns
+                Some(diagnostic) if self.long => writeln!(
+                    out,
+                    "{}{diagnostic}",
+                    (0..10 + size_width + date_width + 3).spacer()
+                ),
+                Some(diagnostic) => writeln!(out, "{diagnostic}"),
+                None => Ok(()),
+            }
+        })?;
+        out.flush()?;
 
         Ok(())
     }
+
+    fn render_entry(&mut self, entry: &Entry, colorizer: &Colorizer) -> String {
+        let line = match self.flat {
+            true => self.line("", "", entry, colorizer),
+            false => colorizer.file(entry),
+        };
+        match self.long {
+            true => format!(
+                "{} {} {} {line}",
+                colorizer.permissions(entry),
+                colorizer.file_size(entry),
+                colorizer.date_modified(entry)
+            ),
+            false => line,
+        }
+    }
 }
 
-impl Formatter for Tree {
-    fn print(&mut self, colorizer: Colorizer) -> Result<(), Box<dyn std::error::Error>> {
-        let entries = self.0.entries()?;
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{sort::Natural, testing::TempDir};
 
-        let parent = Entry::try_from(self.0.path.as_path())?;
-        let permissions = if self.1 {
-            format!(
-                "{} {} {} ",
-                colorizer.permissions(&parent),
-                colorizer.file_size(&parent),
-                colorizer.date_modified(&parent),
-            )
-        } else {
-            String::new()
+    #[test]
+    fn compact_single_child_dirs() {
+        let base = TempDir::new("compact");
+        std::fs::create_dir_all(base.join("src/main/java")).unwrap();
+        std::fs::create_dir_all(base.join("other/a")).unwrap();
+        std::fs::create_dir_all(base.join("other/b")).unwrap();
+        std::fs::write(base.join("src/main/java/App.java"), "").unwrap();
+
+        let render = |compact: bool| {
+            let mut out = Vec::new();
+            Tree::new(FileSystem::from(base.path()).with_sorter(Natural), false)
+                .branches(Branches::ascii())
+                .compact_dirs(compact)
+                .render_to(&mut out, Colorizer::default())
+                .unwrap();
+            String::from_utf8(out)
+                .unwrap()
+                .lines()
+                .skip(1)
+                .map(str::to_string)
+                .collect::<Vec<_>>()
         };
+        let sep = std::path::MAIN_SEPARATOR;
+        assert_eq!(
+            render(true),
+            [
+                "|-- other".to_string(),
+                "|   |-- a".to_string(),
+                "|   \\-- b".to_string(),
+                format!("\\-- src{sep}main{sep}java"),
+                "    \\-- App.java".to_string(),
+            ]
+        );
+        assert_eq!(render(false).len(), 7);
+    }
 
-        let parent_name = self
-            .0
-            .path
-            .parent()
-            .unwrap()
-            .file_name()
-            .unwrap()
-            .to_str()
+    #[test]
+    fn matching_keeps_ancestors() {
+        let base = TempDir::new("matching");
+        std::fs::create_dir_all(base.join("a/b")).unwrap();
+        std::fs::create_dir_all(base.join("c")).unwrap();
+        std::fs::write(base.join("a/b/hit.rs"), "").unwrap();
+        std::fs::write(base.join("a/miss.txt"), "").unwrap();
+        std::fs::write(base.join("c/miss.txt"), "").unwrap();
+
+        let mut out = Vec::new();
+        Tree::new(FileSystem::from(base.path()).with_sorter(Natural), false)
+            .branches(Branches::ascii())
+            .matching(crate::filter::Match::new(r"\.rs$").unwrap())
+            .render_to(&mut out, Colorizer::default())
             .unwrap();
-        println!(
-            "{permissions}{}{}",
-            format!("{}/", parent_name).fg::<xterm::Rose>(),
-            self.0
-                .path
-                .file_name()
-                .unwrap()
-                .to_str()
-                .unwrap()
-                .fg::<xterm::Rose>()
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(
+            out.lines().skip(1).collect::<Vec<_>>(),
+            ["\\-- a", "    \\-- b", "        \\-- hit.rs"]
         );
+    }
+
+    #[test]
+    fn directory_summaries() {
+        let base = TempDir::new("summaries");
+        std::fs::create_dir_all(base.join("src/nested")).unwrap();
+        std::fs::create_dir_all(base.join("empty")).unwrap();
+        std::fs::write(base.join("src/nested/big"), "x".repeat(3000)).unwrap();
+        std::fs::write(base.join("src/small"), "x".repeat(500)).unwrap();
+
+        let mut out = Vec::new();
+        Tree::new(FileSystem::from(base.path()).with_sorter(Natural), false)
+            .branches(Branches::ascii())
+            .summaries(true)
+            .render_to(&mut out, Colorizer::default())
+            .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        let summary = |text: &str| text.dimmed().to_string();
+        assert!(lines[0].ends_with(&summary("(2 items, 3K)")));
+        assert_eq!(
+            lines[1..],
+            [
+                format!("|-- empty {}", summary("(empty)")),
+                format!("\\-- src {}", summary("(2 items, 3K)")),
+                format!("    |-- nested {}", summary("(1 item, 3K)")),
+                "    |   \\-- big".to_string(),
+                "    \\-- small".to_string(),
+            ]
+        );
+    }
 
-        let gitignore = match parent.path.join(".gitignore").exists() {
-            true => Some(GitIgnore::try_from(parent.path.join(".gitignore"))?),
-            false => None,
+    #[cfg(unix)]
+    #[test]
+    fn diagnostic_nodes() {
+        let base = TempDir::new("diagnostics");
+        std::fs::create_dir_all(base.join("a/b/c")).unwrap();
+        std::os::unix::fs::symlink("../..", base.join("a/b/up")).unwrap();
+
+        let render = |file_system: FileSystem| {
+            let mut out = Vec::new();
+            Tree::new(file_system.with_sorter(Natural), false)
+                .branches(Branches::ascii())
+                .diagnostics(true)
+                .render_to(&mut out, Colorizer::default())
+                .unwrap();
+            String::from_utf8(out)
+                .unwrap()
+                .lines()
+                .skip(1)
+                .map(str::to_string)
+                .collect::<Vec<_>>()
         };
-        self.print_all(&entries, gitignore, String::new(), &colorizer)?;
+        let warning = |text: &str| format!("⚠ {text}").yellow().to_string();
 
-        Ok(())
+        let cycles = render(FileSystem::from(base.path()).with_dereference(true));
+        assert_eq!(
+            cycles,
+            [
+                "\\-- a".to_string(),
+                "    \\-- b".to_string(),
+                "        |-- c".to_string(),
+                "        \\-- up".to_string(),
+                format!("            \\-- {}", warning("cycle back to ../..")),
+            ]
+        );
+
+        let depth = render(FileSystem::from(base.path()).with_max_depth(Some(2)));
+        assert_eq!(
+            depth[2],
+            format!("        \\-- {}", warning("max depth reached"))
+        );
+
+        let json = FileSystem::from(base.join("a/b")).with_dereference(true);
+        let up = json
+            .entries()
+            .unwrap()
+            .into_iter()
+            .find(|entry| entry.file_name() == "up")
+            .unwrap();
+        assert_eq!(
+            json.diagnose(&up),
+            Some(Diagnostic::Cycle {
+                target: PathBuf::from("../..")
+            })
+        );
     }
 }
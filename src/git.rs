@@ -0,0 +1,253 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::{Arc, Mutex},
+};
+
+use hashbrown::HashMap;
+
+/// Git state of a file, or of anything inside of a directory
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Status {
+    pub tracked: bool,
+    /// Changed in the index or the work tree compared to `HEAD`
+    pub modified: bool,
+    pub untracked: bool,
+}
+
+impl Status {
+    fn merge(&mut self, other: Status) {
+        self.tracked |= other.tracked;
+        self.modified |= other.modified;
+        self.untracked |= other.untracked;
+    }
+}
+
+/// Tracked and changed files of a git work tree
+///
+/// Loaded with the `git` executable, so it needs to be on the `PATH`.
+#[derive(Debug)]
+pub struct Repo {
+    root: PathBuf,
+    statuses: HashMap<PathBuf, Status>,
+}
+
+impl Repo {
+    /// Root of the work tree containing the path, if any
+    pub fn discover(path: &Path) -> Option<PathBuf> {
+        let path = std::path::absolute(path).ok()?;
+        path.ancestors()
+            .find(|dir| dir.join(".git").exists())
+            .map(Path::to_path_buf)
+    }
+
+    /// Load the status of every tracked and untracked file in the work tree
+    pub fn open<P: AsRef<Path>>(root: P) -> io::Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        let mut repo = Self {
+            statuses: HashMap::new(),
+            root,
+        };
+
+        let files = repo.git(&["ls-files", "-z"])?;
+        let status = repo.git(&["status", "--porcelain=v1", "-z", "--untracked-files=all"])?;
+        repo.parse(&files, &status);
+
+        Ok(repo)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Status of a file, or the combined status of everything in a directory
+    pub fn status(&self, path: &Path) -> Status {
+        std::path::absolute(path)
+            .ok()
+            .and_then(|path| self.statuses.get(&path).copied())
+            .unwrap_or_default()
+    }
+
+    fn git(&self, args: &[&str]) -> io::Result<String> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&self.root)
+            .args(args)
+            .output()?;
+        if !output.status.success() {
+            return Err(io::Error::other(
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Record the output of `git ls-files -z` and `git status --porcelain=v1 -z`
+    ///
+    /// Paths are separated by `\0` so they are never quoted.
+    fn parse(&mut self, files: &str, status: &str) {
+        for path in files.split('\0') {
+            self.insert(path, |status| status.tracked = true);
+        }
+
+        let mut records = status.split('\0');
+        while let Some(record) = records.next() {
+            if record.len() < 4 {
+                continue;
+            }
+            let (code, path) = record.split_at(3);
+            match code.trim_end() {
+                "??" => self.insert(path, |status| status.untracked = true),
+                "!!" => {}
+                code => {
+                    self.insert(path, |status| status.modified = true);
+                    // Renames and copies are followed by the original path
+                    if code.contains(['R', 'C']) {
+                        records.next();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Record the status of a file and every directory above it in the work
+    /// tree
+    fn insert(&mut self, path: &str, update: impl Fn(&mut Status)) {
+        if path.is_empty() {
+            return;
+        }
+        let path = self.root.join(path.trim_end_matches('/'));
+        let mut status = Status::default();
+        update(&mut status);
+
+        for ancestor in path.ancestors() {
+            if ancestor == self.root {
+                break;
+            }
+            self.statuses
+                .entry(ancestor.to_path_buf())
+                .or_default()
+                .merge(status);
+        }
+    }
+}
+
+/// Lazily loaded [`Repo`]s by the directories they were looked up from
+#[derive(Debug, Default)]
+pub struct Repos {
+    dirs: Mutex<HashMap<PathBuf, Option<Arc<Repo>>>>,
+    roots: Mutex<HashMap<PathBuf, Arc<Repo>>>,
+}
+
+impl Repos {
+    /// Status of the path in the work tree containing it, `None` outside of a
+    /// work tree
+    pub fn status(&self, path: &Path) -> Option<Status> {
+        let parent = path.parent().unwrap_or(path).to_path_buf();
+        let repo = self
+            .dirs
+            .lock()
+            .unwrap()
+            .entry(parent)
+            .or_insert_with_key(|dir| self.load(dir))
+            .clone()?;
+        Some(repo.status(path))
+    }
+
+    fn load(&self, dir: &Path) -> Option<Arc<Repo>> {
+        let root = Repo::discover(dir)?;
+        let mut roots = self.roots.lock().unwrap();
+        if let Some(repo) = roots.get(&root) {
+            return Some(repo.clone());
+        }
+        let repo = Arc::new(Repo::open(&root).ok()?);
+        roots.insert(root, repo.clone());
+        Some(repo)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn porcelain() {
+        let root = PathBuf::from("/repo");
+        let mut repo = Repo {
+            root: root.clone(),
+            statuses: HashMap::new(),
+        };
+        repo.parse(
+            "src/lib.rs\0src/old name.rs\0\"quoted\".md\0",
+            concat!(
+                " M src/lib.rs\0",
+                "R  src/new name.rs\0src/old name.rs\0",
+                "?? notes/tab\there.txt\0",
+                "?? \"quoted\" dir/\0",
+                "!! target/\0",
+            ),
+        );
+
+        let status = |path: &str| repo.statuses.get(&root.join(path)).copied();
+        let modified = Status {
+            tracked: true,
+            modified: true,
+            untracked: false,
+        };
+        assert_eq!(status("src/lib.rs"), Some(modified));
+        // The original path of a rename isn't parsed as its own record
+        assert_eq!(
+            status("src/new name.rs"),
+            Some(Status {
+                modified: true,
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            status("src/old name.rs"),
+            Some(Status {
+                tracked: true,
+                ..Default::default()
+            })
+        );
+        assert!(status("notes/tab\there.txt").is_some_and(|s| s.untracked));
+        assert!(status("\"quoted\" dir").is_some_and(|s| s.untracked));
+        assert!(status("\"quoted\".md").is_some_and(|s| s.tracked && !s.modified));
+        assert_eq!(status("target"), None);
+        // Directories combine the status of everything in them
+        assert_eq!(status("src"), Some(modified));
+        assert!(status("notes").is_some_and(|s| s.untracked && !s.tracked));
+    }
+
+    #[test]
+    fn lookup() {
+        let base = TempDir::new("git-lookup");
+        std::fs::create_dir_all(base.join("repo").join("src")).unwrap();
+        std::fs::write(base.join("repo").join("src").join("lib.rs"), "").unwrap();
+        std::fs::write(base.join("outside"), "").unwrap();
+        let init = Command::new("git")
+            .arg("init")
+            .arg("-q")
+            .arg(base.join("repo"))
+            .status();
+        // Only runs where the `git` executable is available
+        if !init.is_ok_and(|status| status.success()) {
+            return;
+        }
+
+        let root = Repo::discover(&base.join("repo").join("src")).unwrap();
+        assert_eq!(root, std::path::absolute(base.join("repo")).unwrap());
+        let repo = Repo::open(&root).unwrap();
+        assert!(repo.status(&root.join("src")).untracked);
+        assert_eq!(repo.status(&root.join("missing")), Status::default());
+
+        let repos = Repos::default();
+        let lib = base.join("repo").join("src").join("lib.rs");
+        assert!(repos
+            .status(&lib)
+            .is_some_and(|s| s.untracked && !s.tracked));
+        assert_eq!(repos.status(&base.join("outside")), None);
+    }
+}
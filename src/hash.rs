@@ -0,0 +1,182 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Read, Write},
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use hashbrown::HashMap;
+
+use crate::Entry;
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Hash the contents of a file with 64 bit FNV-1a
+///
+/// FNV is used over the std hashers since its output is stable between
+/// releases, which is required for the persistent [`HashCache`].
+pub fn hash_file<P: AsRef<Path>>(path: P) -> io::Result<u64> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut hash = FNV_OFFSET;
+    let mut buffer = [0u8; 8192];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        for byte in &buffer[..read] {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+    Ok(hash)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Cached {
+    size: u64,
+    modified: u128,
+    hash: u64,
+}
+
+/// Cache of file content hashes keyed by path
+///
+/// A cached hash is only reused while the file's size and modified time are
+/// unchanged, otherwise the file is hashed again. The cache is stored as one
+/// `hash size modified path` line per file in `<cache dir>/xf/hashes`.
+#[derive(Default, Debug, Clone)]
+pub struct HashCache {
+    path: Option<PathBuf>,
+    entries: HashMap<PathBuf, Cached>,
+    dirty: bool,
+}
+
+impl HashCache {
+    /// Location of the persistent cache file
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join("xf").join("hashes"))
+    }
+
+    /// Cache that is never written to disk
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Load the persistent cache, starting empty if it doesn't exist yet
+    pub fn load() -> io::Result<Self> {
+        match Self::default_path() {
+            Some(path) => Self::open(path),
+            None => Ok(Self::in_memory()),
+        }
+    }
+
+    /// Load a cache from a specific file, starting empty if it doesn't exist yet
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut cache = Self {
+            path: Some(path.clone()),
+            ..Default::default()
+        };
+
+        if !path.exists() {
+            return Ok(cache);
+        }
+
+        for line in BufReader::new(File::open(&path)?).lines() {
+            let line = line?;
+            let mut parts = line.splitn(4, '\t');
+            // Malformed lines are dropped, they will be rehashed when needed
+            let (Some(hash), Some(size), Some(modified), Some(file)) =
+                (parts.next(), parts.next(), parts.next(), parts.next())
+            else {
+                continue;
+            };
+            let (Ok(hash), Ok(size), Ok(modified)) = (
+                u64::from_str_radix(hash, 16),
+                size.parse::<u64>(),
+                modified.parse::<u128>(),
+            ) else {
+                continue;
+            };
+            cache.entries.insert(
+                PathBuf::from(file),
+                Cached {
+                    size,
+                    modified,
+                    hash,
+                },
+            );
+        }
+
+        Ok(cache)
+    }
+
+    /// Content hash of the entry, `None` for directories
+    ///
+    /// The cached value is used when the size and modified time still match.
+    pub fn hash(&mut self, entry: &Entry) -> io::Result<Option<u64>> {
+        if entry.is_dir() {
+            return Ok(None);
+        }
+
+        let size = entry.metadata().len();
+        let modified = entry
+            .metadata()
+            .modified()
+            .ok()
+            .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+
+        if let Some(cached) = self.entries.get(entry.path()) {
+            if cached.size == size && cached.modified == modified {
+                return Ok(Some(cached.hash));
+            }
+        }
+
+        let hash = hash_file(entry.path())?;
+        self.entries.insert(
+            entry.path().to_path_buf(),
+            Cached {
+                size,
+                modified,
+                hash,
+            },
+        );
+        self.dirty = true;
+        Ok(Some(hash))
+    }
+
+    /// Write the cache back to disk if anything changed
+    ///
+    /// Entries for files that no longer exist are dropped.
+    pub fn save(&mut self) -> io::Result<()> {
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+        if !self.dirty {
+            return Ok(());
+        }
+
+        self.entries.retain(|file, _| file.exists());
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = io::BufWriter::new(File::create(path)?);
+        for (file_path, cached) in self.entries.iter() {
+            writeln!(
+                file,
+                "{:016x}\t{}\t{}\t{}",
+                cached.hash,
+                cached.size,
+                cached.modified,
+                file_path.display()
+            )?;
+        }
+        file.flush()?;
+        self.dirty = false;
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+use hashbrown::HashMap;
+
+use crate::Entry;
+
+/// Nerd font glyphs for entries by file name, extension, and type
+///
+/// Requires a [nerd font](https://www.nerdfonts.com) to render. Every glyph is
+/// assumed to take up a single column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Icons {
+    filenames: HashMap<String, char>,
+    extensions: HashMap<String, char>,
+    directory: char,
+    file: char,
+}
+
+impl Default for Icons {
+    fn default() -> Self {
+        Self::nerd_font()
+    }
+}
+
+impl Icons {
+    /// Icons without any file name or extension specific glyphs
+    pub fn new(directory: char, file: char) -> Self {
+        Self {
+            filenames: HashMap::new(),
+            extensions: HashMap::new(),
+            directory,
+            file,
+        }
+    }
+
+    /// Glyphs for common file types
+    pub fn nerd_font() -> Self {
+        let mut icons = Self::new('\u{f07b}', '\u{f15b}');
+
+        for (icon, filenames) in [
+            ('\u{e7a8}', &["Cargo.toml", "Cargo.lock"][..]),
+            (
+                '\u{f1d3}',
+                &[".git", ".gitignore", ".gitattributes", ".gitmodules"],
+            ),
+            ('\u{f02d}', &["LICENSE", "LICENSE-MIT", "LICENSE-APACHE"]),
+            ('\u{f308}', &["Dockerfile", "docker-compose.yml"]),
+            ('\u{f489}', &["Makefile", "justfile"]),
+        ] {
+            for filename in filenames {
+                icons.filenames.insert(filename.to_string(), icon);
+            }
+        }
+
+        for (icon, extensions) in [
+            ('\u{e7a8}', &["rs"][..]),
+            ('\u{e6b2}', &["toml"]),
+            ('\u{f48a}', &["md", "markdown"]),
+            ('\u{e60b}', &["json", "jsonc"]),
+            ('\u{f481}', &["yml", "yaml"]),
+            ('\u{e74e}', &["js", "mjs", "cjs"]),
+            ('\u{e628}', &["ts", "tsx"]),
+            ('\u{e606}', &["py"]),
+            ('\u{e61e}', &["c", "h"]),
+            ('\u{e61d}', &["cpp", "cc", "hpp"]),
+            ('\u{e627}', &["go"]),
+            ('\u{f13b}', &["html", "htm"]),
+            ('\u{e749}', &["css", "scss"]),
+            ('\u{f489}', &["sh", "bash", "zsh", "fish", "ps1"]),
+            ('\u{f023}', &["lock"]),
+            ('\u{f15c}', &["txt", "log"]),
+            ('\u{f1c1}', &["pdf"]),
+            (
+                '\u{f1c5}',
+                &[
+                    "png", "jpg", "jpeg", "gif", "webp", "avif", "ico", "bmp", "svg",
+                ],
+            ),
+            ('\u{f001}', &["mp3", "wav", "flac", "ogg", "m4a"]),
+            ('\u{f03d}', &["mp4", "mkv", "mov", "webm"]),
+            (
+                '\u{f410}',
+                &["zip", "gz", "tgz", "xz", "bz2", "7z", "rar", "tar", "zst"],
+            ),
+            ('\u{f17a}', &["exe", "dll", "msi"]),
+        ] {
+            for extension in extensions {
+                icons.extensions.insert(extension.to_string(), icon);
+            }
+        }
+
+        icons
+    }
+
+    pub fn filename<S: ToString>(mut self, filename: S, icon: char) -> Self {
+        self.filenames.insert(filename.to_string(), icon);
+        self
+    }
+
+    /// Extensions are matched case insensitively
+    pub fn extension<S: AsRef<str>>(mut self, extension: S, icon: char) -> Self {
+        self.extensions
+            .insert(extension.as_ref().to_ascii_lowercase(), icon);
+        self
+    }
+
+    /// Glyph specific to the entry's file name or extension
+    pub fn specific(&self, entry: &Entry) -> Option<char> {
+        self.filenames.get(entry.file_name()).copied().or_else(|| {
+            entry
+                .extension()
+                .and_then(|ext| self.extensions.get(&ext.to_ascii_lowercase()).copied())
+        })
+    }
+
+    /// Glyph used when nothing more specific matches
+    pub fn fallback(&self, entry: &Entry) -> char {
+        if entry.is_dir() {
+            self.directory
+        } else {
+            self.file
+        }
+    }
+
+    pub fn icon(&self, entry: &Entry) -> char {
+        self.specific(entry).unwrap_or_else(|| self.fallback(entry))
+    }
+}
@@ -36,19 +36,15 @@ impl GitIgnore {
     }
 }
 
-impl TryFrom<PathBuf> for GitIgnore {
-    type Error = String;
-
-    fn try_from(value: PathBuf) -> Result<Self, Self::Error> {
-        let content = std::fs::read_to_string(value).map_err(|e| e.to_string())?;
-        Self::from_str(content.as_str())
+impl GitIgnore {
+    /// Read a `.gitignore`, skipping patterns that can't be parsed unless
+    /// `strict` is set
+    pub fn load(path: &Path, strict: bool) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Self::parse(content.as_str(), strict).map_err(|e| format!("{}: {e}", path.display()))
     }
-}
-
-impl FromStr for GitIgnore {
-    type Err = String;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+    fn parse(s: &str, strict: bool) -> Result<Self, String> {
         let mut ignore = GitIgnore::default();
 
         for line in s.lines() {
@@ -74,10 +70,11 @@ impl FromStr for GitIgnore {
                     line = line.strip_suffix('/').unwrap().to_string();
                 }
 
-                ignore.exclude.push(
-                    Regex::new(format!("^{}$", line.as_str()).as_str())
-                        .map_err(|e| e.to_string())?,
-                )
+                match Regex::new(format!("^{}$", line.as_str()).as_str()) {
+                    Ok(exclude) => ignore.exclude.push(exclude),
+                    Err(err) if strict => return Err(err.to_string()),
+                    Err(_) => {}
+                }
             }
         }
 
@@ -85,6 +82,22 @@ impl FromStr for GitIgnore {
     }
 }
 
+impl TryFrom<PathBuf> for GitIgnore {
+    type Error = String;
+
+    fn try_from(value: PathBuf) -> Result<Self, Self::Error> {
+        Self::load(&value, true)
+    }
+}
+
+impl FromStr for GitIgnore {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, true)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -116,6 +129,14 @@ mod test {
         assert_eq!(ignore.include.len(), 0);
     }
 
+    #[test]
+    fn skip_invalid_patterns() {
+        assert!(GitIgnore::from_str("build/\nsrc/(unclosed").is_err());
+        let ignore = GitIgnore::parse("build/\nsrc/(unclosed", false).unwrap();
+        assert_eq!(ignore.exclude.len(), 1);
+        assert!(!ignore.include("build"));
+    }
+
     #[test]
     fn should_include() {
         let ignore = GitIgnore::from_str(
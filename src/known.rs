@@ -0,0 +1,177 @@
+use std::path::{Path, PathBuf};
+
+use hashbrown::HashMap;
+use owo_colors::Style;
+
+use crate::Entry;
+
+/// A well known user directory, resolved from the XDG user dirs on linux and
+/// the Known Folders on windows and macos
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KnownFolder {
+    Desktop,
+    Documents,
+    Downloads,
+    Music,
+    Pictures,
+    Public,
+    Templates,
+    Videos,
+}
+
+impl KnownFolder {
+    pub const ALL: [KnownFolder; 8] = [
+        Self::Desktop,
+        Self::Documents,
+        Self::Downloads,
+        Self::Music,
+        Self::Pictures,
+        Self::Public,
+        Self::Templates,
+        Self::Videos,
+    ];
+
+    /// English name of the folder, used as the default label
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Desktop => "Desktop",
+            Self::Documents => "Documents",
+            Self::Downloads => "Downloads",
+            Self::Music => "Music",
+            Self::Pictures => "Pictures",
+            Self::Public => "Public",
+            Self::Templates => "Templates",
+            Self::Videos => "Videos",
+        }
+    }
+
+    /// Nerd font glyph of the folder
+    pub fn icon(&self) -> char {
+        match self {
+            Self::Desktop => '\u{f108}',
+            Self::Documents => '\u{f0219}',
+            Self::Downloads => '\u{f024d}',
+            Self::Music => '\u{f1359}',
+            Self::Pictures => '\u{f024f}',
+            Self::Public => '\u{f0256}',
+            Self::Templates => '\u{f0b9d}',
+            Self::Videos => '\u{f025e}',
+        }
+    }
+
+    /// Location of the folder on this system
+    ///
+    /// Falls back to `~/<name>` when the platform doesn't define the folder but
+    /// the directory exists.
+    pub fn path(&self) -> Option<PathBuf> {
+        let path = match self {
+            Self::Desktop => dirs::desktop_dir(),
+            Self::Documents => dirs::document_dir(),
+            Self::Downloads => dirs::download_dir(),
+            Self::Music => dirs::audio_dir(),
+            Self::Pictures => dirs::picture_dir(),
+            Self::Public => dirs::public_dir(),
+            Self::Templates => dirs::template_dir(),
+            Self::Videos => dirs::video_dir(),
+        };
+        path.or_else(|| {
+            dirs::home_dir()
+                .map(|home| home.join(self.name()))
+                .filter(|path| path.is_dir())
+        })
+    }
+}
+
+/// Resolved [`KnownFolder`]s used to give them dedicated icons, a style, and
+/// labels
+#[derive(Debug, Clone, PartialEq)]
+pub struct KnownFolders {
+    folders: Vec<(PathBuf, KnownFolder)>,
+    labels: HashMap<KnownFolder, String>,
+    show_labels: bool,
+    style: Style,
+}
+
+impl Default for KnownFolders {
+    fn default() -> Self {
+        Self::resolve()
+    }
+}
+
+impl KnownFolders {
+    /// Resolve the locations of every known folder on this system
+    pub fn resolve() -> Self {
+        Self {
+            folders: KnownFolder::ALL
+                .iter()
+                .filter_map(|folder| folder.path().map(|path| (path, *folder)))
+                .collect(),
+            labels: HashMap::new(),
+            show_labels: false,
+            style: Style::default().cyan(),
+        }
+    }
+
+    /// Style of known folder names, defaults to cyan
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Show the folder's label after names that differ from it, which makes
+    /// localized folders, `Téléchargements (Downloads)`, recognizable
+    pub fn show_labels(mut self, show: bool) -> Self {
+        self.show_labels = show;
+        self
+    }
+
+    /// Label of a folder, defaults to [`KnownFolder::name`]
+    pub fn label<S: ToString>(mut self, folder: KnownFolder, label: S) -> Self {
+        self.labels.insert(folder, label.to_string());
+        self
+    }
+
+    pub fn get_style(&self) -> Style {
+        self.style
+    }
+
+    /// Known folder at the path
+    pub fn folder(&self, path: &Path) -> Option<KnownFolder> {
+        if self.folders.is_empty() {
+            return None;
+        }
+        let path = std::path::absolute(path).ok()?;
+        self.folders
+            .iter()
+            .find(|(known, _)| *known == path)
+            .map(|(_, folder)| *folder)
+    }
+
+    /// Known folder of a directory entry
+    pub fn of(&self, entry: &Entry) -> Option<KnownFolder> {
+        // Only directories named like one of the folders need a path comparison
+        let name = entry.file_name();
+        if !entry.is_dir()
+            || !self
+                .folders
+                .iter()
+                .any(|(path, _)| path.file_name().is_some_and(|n| n == name))
+        {
+            return None;
+        }
+        self.folder(entry.path())
+    }
+
+    /// Label shown after the name of the folder's entry, if any
+    pub fn entry_label(&self, entry: &Entry, folder: KnownFolder) -> Option<&str> {
+        if !self.show_labels {
+            return None;
+        }
+        let label = self
+            .labels
+            .get(&folder)
+            .map(String::as_str)
+            .unwrap_or(folder.name());
+        (label != entry.file_name()).then_some(label)
+    }
+}
@@ -1,22 +1,68 @@
+mod content;
 mod ignore;
 
+pub mod action;
+pub mod bench;
+pub mod cache;
+pub mod clock;
+pub mod cloud;
+pub mod codeowners;
+pub mod config;
+pub mod debug;
+pub mod diagnostic;
+pub mod disk;
+pub mod exec;
 pub mod filter;
 pub mod format;
+pub mod git;
+pub mod hash;
+pub mod icons;
+pub mod known;
+pub mod listable;
+pub mod listing;
+#[cfg(feature = "media")]
+pub mod media;
 pub mod permission;
+pub mod project;
+#[cfg(feature = "serve")]
+pub mod serve;
+#[cfg(feature = "sftp")]
+pub mod sftp;
+pub mod snapshot;
 pub mod sort;
+#[cfg(feature = "object-store")]
+pub mod store;
 pub mod style;
+pub mod testing;
+pub mod text;
+mod walk;
 
 use std::{
     cmp::Ordering,
     fs::{self, DirEntry, Metadata},
     io,
     path::{Path, PathBuf},
-    rc::Rc,
+    sync::{Arc, Mutex, OnceLock},
+    time::Instant,
 };
 
+use bench::Timings;
+use cache::{DirCache, Page, PageIndex};
+use cloud::CloudStatus;
+use diagnostic::Diagnostic;
+
 use filter::{Filter, Not};
 use permission::Perms;
-use sort::{Natural, SortStrategy};
+use sort::{Natural, SortKey, SortStrategy};
+use style::Colorizer;
+
+/// Name of the [`Colorizer`] group the entry belongs to
+///
+/// Exposes xf's classification logic so other renderers can reuse it without
+/// depending on ANSI output.
+pub fn classify<'c>(entry: &Entry, colorizer: &'c Colorizer) -> Option<&'c str> {
+    colorizer.classify(entry)
+}
 
 /// Wrapper around [`std::fs::DirEntry`]
 ///
@@ -28,6 +74,7 @@ pub struct Entry {
     permissions: Perms,
     meta: Metadata,
     path: PathBuf,
+    child_count: OnceLock<Option<usize>>,
 }
 
 #[derive(Debug, PartialEq, Clone, Copy, strum_macros::EnumIs)]
@@ -36,6 +83,19 @@ pub enum EntryType {
     Dir,
 }
 
+/// Identity of the file an entry points to, shared by every hard link to it
+///
+/// The identity stays the same when the file is renamed or moved within the
+/// same volume, so it can be used to track entries across renames. Ids can be
+/// reused by new files after the original is deleted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct FileId {
+    /// Device id on unix, volume serial number on windows
+    pub device: u64,
+    /// Inode on unix, file index on windows
+    pub inode: u64,
+}
+
 impl Entry {
     pub fn etype(&self) -> EntryType {
         self.entry_type
@@ -85,6 +145,179 @@ impl Entry {
     pub fn is_executable(&self) -> bool {
         self.permissions().user().executable()
     }
+
+    /// Path a symbolic link points to as it was written, `None` for other entries
+    pub fn link_target(&self) -> Option<PathBuf> {
+        match self.meta.is_symlink() {
+            true => fs::read_link(&self.path).ok(),
+            false => None,
+        }
+    }
+
+    /// Replace a symbolic link with the file it points to, keeping its path
+    ///
+    /// The entry is then classified, sized, and dated by its target. Broken
+    /// links and other entries are returned as is.
+    pub fn dereference(self) -> Self {
+        if !self.meta.is_symlink() {
+            return self;
+        }
+        match fs::metadata(&self.path) {
+            Ok(meta) => Self {
+                entry_type: match meta.is_dir() {
+                    true => EntryType::Dir,
+                    false => EntryType::File,
+                },
+                meta,
+                child_count: OnceLock::new(),
+                ..self
+            },
+            Err(_) => self,
+        }
+    }
+
+    /// Whether the entry is a symbolic link whose target doesn't exist
+    pub fn is_broken_link(&self) -> bool {
+        self.meta.is_symlink() && fs::metadata(&self.path).is_err()
+    }
+
+    /// Device and inode of the file, or volume serial number and file index on
+    /// windows, `None` on other platforms or when it can't be read
+    #[cfg(unix)]
+    pub fn file_id(&self) -> Option<FileId> {
+        use std::os::unix::fs::MetadataExt;
+        Some(FileId {
+            device: self.meta.dev(),
+            inode: self.meta.ino(),
+        })
+    }
+
+    #[cfg(windows)]
+    pub fn file_id(&self) -> Option<FileId> {
+        let info = file_information(&self.path)?;
+        Some(FileId {
+            device: info.dwVolumeSerialNumber as u64,
+            inode: ((info.nFileIndexHigh as u64) << 32) | info.nFileIndexLow as u64,
+        })
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    pub fn file_id(&self) -> Option<FileId> {
+        None
+    }
+
+    /// Whether both entries are the same file, even when one was renamed or is
+    /// a hard link to the other
+    ///
+    /// Falls back to comparing paths when [`Entry::file_id`] isn't available.
+    pub fn same_file(&self, other: &Entry) -> bool {
+        match (self.file_id(), other.file_id()) {
+            (Some(id), Some(other)) => id == other,
+            _ => self.path == other.path,
+        }
+    }
+
+    /// Space the file takes up on disk, which is smaller than its length for
+    /// sparse and compressed files and rounded up to whole blocks otherwise
+    #[cfg(unix)]
+    pub fn allocated_size(&self) -> Option<u64> {
+        use std::os::unix::fs::MetadataExt;
+        // `st_blocks` is always in 512 byte units regardless of the block size
+        Some(self.meta.blocks() * 512)
+    }
+
+    #[cfg(windows)]
+    pub fn allocated_size(&self) -> Option<u64> {
+        use std::os::windows::ffi::OsStrExt;
+        use windows::{core::PCWSTR, Win32::Storage::FileSystem::GetCompressedFileSizeW};
+
+        if self.is_dir() {
+            return None;
+        }
+        let wide = self
+            .path
+            .as_os_str()
+            .encode_wide()
+            .chain([0])
+            .collect::<Vec<_>>();
+        let mut high = 0u32;
+        let low =
+            unsafe { GetCompressedFileSizeW(PCWSTR::from_raw(wide.as_ptr()), Some(&mut high)) };
+        // `INVALID_FILE_SIZE` is also a valid low half so the error has to be checked
+        if low == u32::MAX && io::Error::last_os_error().raw_os_error() != Some(0) {
+            return None;
+        }
+        Some(((high as u64) << 32) | low as u64)
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    pub fn allocated_size(&self) -> Option<u64> {
+        None
+    }
+
+    /// Sync state of a cloud file placeholder, e.g. OneDrive, see [`CloudStatus`]
+    pub fn cloud_status(&self) -> Option<CloudStatus> {
+        CloudStatus::of(&self.meta)
+    }
+
+    /// Number of hard links to the file, `1` on platforms without them
+    #[cfg(unix)]
+    pub fn hard_links(&self) -> u64 {
+        use std::os::unix::fs::MetadataExt;
+        self.meta.nlink()
+    }
+
+    #[cfg(windows)]
+    pub fn hard_links(&self) -> u64 {
+        file_information(&self.path)
+            .map(|info| info.nNumberOfLinks as u64)
+            .unwrap_or(1)
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    pub fn hard_links(&self) -> u64 {
+        1
+    }
+
+    /// Number of entries in a directory that don't start with a `.`, `None` for
+    /// files and directories that can't be read
+    ///
+    /// The directory is only read the first time this is called.
+    pub fn child_count(&self) -> Option<usize> {
+        if !self.is_dir() {
+            return None;
+        }
+        *self.child_count.get_or_init(|| {
+            fs::read_dir(&self.path).ok().map(|dir| {
+                dir.filter_map(Result::ok)
+                    .filter(|child| !child.file_name().to_string_lossy().starts_with('.'))
+                    .count()
+            })
+        })
+    }
+
+    /// Whether the file's contents look like binary data, directories are
+    /// never binary
+    ///
+    /// This reads the start of the file so prefer cheaper checks first.
+    pub fn is_binary(&self) -> bool {
+        self.is_file() && content::is_binary(self.path())
+    }
+
+    /// Number of lines in a text file, `None` for directories, binary files,
+    /// and files over 1 MiB
+    pub fn line_count(&self) -> Option<usize> {
+        if self.is_file() {
+            content::count_lines(self.path(), content::MAX_SIZE)
+        } else {
+            None
+        }
+    }
+
+    /// Path segments of the entry from its root, see [`style::breadcrumbs`]
+    pub fn components(&self) -> impl Iterator<Item = style::Crumb> {
+        style::breadcrumbs(self.path()).into_iter()
+    }
 }
 
 impl Entry {
@@ -95,20 +328,7 @@ impl Entry {
             );
         }
 
-        let mut entries = fs::read_dir(&self.path)?
-            .filter_map(|v| match v {
-                Ok(v) => {
-                    // PERF: Handle error
-                    let entry = Entry::try_from(v).ok()?;
-                    parent.filters.keep(&entry).then_some(entry)
-                }
-                _ => None,
-            })
-            .collect::<Vec<_>>();
-
-        entries.sort_by(|f, s| parent.sorter.compare(f, s));
-
-        Ok(entries)
+        parent.read_dir(&self.path)
     }
 }
 
@@ -123,10 +343,17 @@ impl PartialEq for Entry {
 }
 impl Eq for Entry {}
 
-impl TryFrom<DirEntry> for Entry {
-    type Error = Box<dyn std::error::Error>;
-    fn try_from(value: DirEntry) -> Result<Self, Self::Error> {
-        let entry_type = if value.path().is_dir() {
+impl Entry {
+    /// Build an entry from a path and the metadata its directory listing
+    /// reported
+    ///
+    /// Symbolic links are classified by the link itself, so a link to a
+    /// directory is not a directory until it is [`Entry::dereference`]d.
+    pub(crate) fn with_metadata(
+        path: PathBuf,
+        meta: Metadata,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let entry_type = if meta.is_dir() {
             EntryType::Dir
         } else {
             EntryType::File
@@ -134,14 +361,21 @@ impl TryFrom<DirEntry> for Entry {
 
         Ok(Self {
             entry_type,
-            permissions: Perms::try_from(value.path().as_path())?,
-            //permissions: Perms::default(),
-            meta: value.metadata().unwrap(),
-            path: value.path().to_path_buf(),
+            permissions: Perms::try_from(path.as_path())?,
+            meta,
+            path,
+            child_count: OnceLock::new(),
         })
     }
 }
 
+impl TryFrom<DirEntry> for Entry {
+    type Error = Box<dyn std::error::Error>;
+    fn try_from(value: DirEntry) -> Result<Self, Self::Error> {
+        Entry::with_metadata(value.path(), value.metadata()?)
+    }
+}
+
 impl TryFrom<&Path> for Entry {
     type Error = Box<dyn std::error::Error>;
     fn try_from(value: &Path) -> Result<Self, Self::Error> {
@@ -155,12 +389,38 @@ impl TryFrom<&Path> for Entry {
             entry_type,
             permissions: Perms::try_from(value)?,
             //permissions: Perms::default(),
-            meta: value.metadata().unwrap(),
+            meta: value.metadata()?,
             path: value.to_path_buf(),
+            child_count: OnceLock::new(),
         })
     }
 }
 
+/// Volume, file index, and link count of a file without following symlinks
+#[cfg(windows)]
+fn file_information(
+    path: &Path,
+) -> Option<windows::Win32::Storage::FileSystem::BY_HANDLE_FILE_INFORMATION> {
+    use std::os::windows::{fs::OpenOptionsExt, io::AsRawHandle};
+    use windows::Win32::{
+        Foundation::HANDLE,
+        Storage::FileSystem::{
+            GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION, FILE_FLAG_BACKUP_SEMANTICS,
+            FILE_FLAG_OPEN_REPARSE_POINT,
+        },
+    };
+
+    // Directories can only be opened with backup semantics
+    let file = fs::OpenOptions::new()
+        .access_mode(0)
+        .custom_flags((FILE_FLAG_BACKUP_SEMANTICS | FILE_FLAG_OPEN_REPARSE_POINT).0)
+        .open(path)
+        .ok()?;
+    let mut info = BY_HANDLE_FILE_INFORMATION::default();
+    unsafe { GetFileInformationByHandle(HANDLE(file.as_raw_handle()), &mut info) }.ok()?;
+    Some(info)
+}
+
 /// Helper to normalize `~` and other path features along with canonicalize the path
 trait NormalizeCanonicalize {
     fn normalize_and_canonicalize(&self) -> Result<PathBuf, std::io::Error>;
@@ -182,8 +442,15 @@ impl<A: AsRef<str>> NormalizeCanonicalize for A {
 /// Main logic for transforming, sorting, and filtering file entries
 pub struct FileSystem {
     path: PathBuf,
-    filters: Rc<dyn Filter>,
-    sorter: Rc<dyn SortStrategy>,
+    filters: Arc<dyn Filter>,
+    sorter: Arc<dyn SortStrategy>,
+    cache: Option<Arc<Mutex<DirCache>>>,
+    max_depth: Option<usize>,
+    dereference: bool,
+    hidden_dirs: bool,
+    strict: bool,
+    timings: Option<Arc<Timings>>,
+    index: Arc<Mutex<PageIndex>>,
 }
 
 impl std::fmt::Debug for FileSystem {
@@ -198,6 +465,13 @@ impl Clone for FileSystem {
             path: self.path.clone(),
             filters: self.filters.clone(),
             sorter: self.sorter.clone(),
+            cache: self.cache.clone(),
+            max_depth: self.max_depth,
+            dereference: self.dereference,
+            hidden_dirs: self.hidden_dirs,
+            strict: self.strict,
+            timings: self.timings.clone(),
+            index: self.index.clone(),
         }
     }
 }
@@ -209,8 +483,15 @@ impl Default for FileSystem {
             path: path
                 .normalize_and_canonicalize()
                 .expect("Could not find the path specified"),
-            filters: Rc::new(Not::<Hidden>::default()),
-            sorter: Rc::new(()),
+            filters: Arc::new(Not::<Hidden>::default()),
+            sorter: Arc::new(()),
+            cache: None,
+            max_depth: None,
+            dereference: false,
+            hidden_dirs: false,
+            strict: false,
+            timings: None,
+            index: Arc::default(),
         }
     }
 }
@@ -226,36 +507,223 @@ impl FileSystem {
             path: path
                 .normalize_and_canonicalize()
                 .expect("Could not find the path specified"),
-            filters: Rc::new(filters),
-            sorter: Rc::new(sorter),
+            filters: Arc::new(filters),
+            sorter: Arc::new(sorter),
+            cache: None,
+            max_depth: None,
+            dereference: false,
+            hidden_dirs: false,
+            strict: false,
+            timings: None,
+            index: Arc::default(),
         }
     }
 }
 
+impl FileSystem {
+    /// Canonical path of the directory being listed
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// How many levels recursive walks descend, `None` for no limit
+    pub fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+}
+
 impl FileSystem {
     pub fn with_sorter<S: SortStrategy + 'static>(self, sorter: S) -> FileSystem {
         FileSystem {
-            path: self.path,
-            filters: self.filters,
-            sorter: Rc::new(sorter),
+            sorter: Arc::new(sorter),
+            // Cached listings are sorted with the old sorter
+            cache: self.fresh_cache(),
+            index: Arc::default(),
+            ..self
         }
     }
 
     pub fn with_filter<F: Filter + 'static>(self, filters: F) -> FileSystem {
         FileSystem {
-            path: self.path,
-            filters: Rc::new(filters),
-            sorter: self.sorter,
+            filters: Arc::new(filters),
+            // Cached listings are filtered with the old filters
+            cache: self.fresh_cache(),
+            index: Arc::default(),
+            ..self
+        }
+    }
+
+    /// Cache up to `capacity` directory listings between reads
+    ///
+    /// Useful when the same directories are listed repeatedly, see [`DirCache`].
+    pub fn with_cache(self, capacity: usize) -> FileSystem {
+        FileSystem {
+            cache: Some(Arc::new(Mutex::new(DirCache::new(capacity)))),
+            ..self
+        }
+    }
+
+    /// Limit recursive walks to `depth` levels, `1` only includes the entries
+    /// of the root directory
+    pub fn with_max_depth(self, depth: Option<usize>) -> FileSystem {
+        FileSystem {
+            max_depth: depth,
+            ..self
+        }
+    }
+
+    pub fn set_max_depth(&mut self, depth: Option<usize>) {
+        self.max_depth = depth;
+    }
+
+    /// Follow symbolic links so they are listed, sorted, filtered, and walked
+    /// as the files and directories they point to, see [`Entry::dereference`]
+    pub fn with_dereference(self, dereference: bool) -> FileSystem {
+        FileSystem {
+            dereference,
+            // Cached entries were read with the old setting
+            cache: self.fresh_cache(),
+            index: Arc::default(),
+            ..self
+        }
+    }
+
+    pub fn set_dereference(&mut self, dereference: bool) {
+        self.dereference = dereference;
+        self.index = Arc::default();
+        self.cache = self.fresh_cache();
+    }
+
+    /// Whether symbolic links are followed, see [`FileSystem::with_dereference`]
+    pub fn dereference(&self) -> bool {
+        self.dereference
+    }
+
+    /// Walk into hidden directories like `.github`
+    ///
+    /// Off by default, so hidden directories that the filters let through are
+    /// listed but their contents aren't.
+    pub fn with_hidden_dirs(self, hidden_dirs: bool) -> FileSystem {
+        FileSystem {
+            hidden_dirs,
+            ..self
+        }
+    }
+
+    pub fn set_hidden_dirs(&mut self, hidden_dirs: bool) {
+        self.hidden_dirs = hidden_dirs;
+    }
+
+    /// Fail on entries and nested directories that can't be read instead of
+    /// skipping them
+    pub fn with_strict(self, strict: bool) -> FileSystem {
+        FileSystem { strict, ..self }
+    }
+
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Add the time spent reading directories to `timings`, shared with every
+    /// clone of the file system
+    pub fn with_timings(self, timings: Option<Arc<Timings>>) -> FileSystem {
+        FileSystem { timings, ..self }
+    }
+
+    pub fn set_timings(&mut self, timings: Option<Arc<Timings>>) {
+        self.timings = timings;
+    }
+
+    /// Whether unreadable entries are errors, see [`FileSystem::with_strict`]
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Whether a recursive walk should descend into an entry
+    ///
+    /// Hidden directories are only walked into when enabled with
+    /// [`FileSystem::with_hidden_dirs`]. Links back to one of their own
+    /// ancestors are never followed so walks that dereference links still end.
+    pub(crate) fn descends_into(&self, entry: &Entry) -> bool {
+        if !entry.is_dir() || (!self.hidden_dirs && entry.is_hidden()) {
+            return false;
+        }
+        if !self.dereference || !fs::symlink_metadata(entry.path()).is_ok_and(|m| m.is_symlink()) {
+            return true;
+        }
+        matches!(link_cycle(entry.path()), Ok(None))
+    }
+
+    /// Why a directory can't be walked into, a followed link back to one of
+    /// its ancestors or a directory that can't be read
+    pub fn diagnose(&self, entry: &Entry) -> Option<Diagnostic> {
+        if !entry.is_dir() {
+            return None;
+        }
+        self.cycle(entry).or_else(|| {
+            fs::read_dir(entry.path())
+                .err()
+                .map(|err| Diagnostic::unreadable(&err))
+        })
+    }
+
+    /// Cycle of a followed link back to one of its ancestors
+    pub(crate) fn cycle(&self, entry: &Entry) -> Option<Diagnostic> {
+        if !self.dereference || !fs::symlink_metadata(entry.path()).is_ok_and(|m| m.is_symlink()) {
+            return None;
+        }
+        match link_cycle(entry.path()) {
+            Ok(Some(target)) => Some(Diagnostic::Cycle { target }),
+            _ => None,
         }
     }
 
+    pub fn sorter(&self) -> &dyn SortStrategy {
+        self.sorter.as_ref()
+    }
+
     pub fn set_sorter<S: SortStrategy + 'static>(&mut self, sorter: S) {
-        self.sorter = Rc::new(sorter);
+        self.sorter = Arc::new(sorter);
+        // Clones share the index and cache but keep their own sorter
+        self.index = Arc::default();
+        self.cache = self.fresh_cache();
     }
 
     pub fn set_filter<F: Filter + 'static>(&mut self, filters: F) {
-        self.filters = Rc::new(filters);
+        self.filters = Arc::new(filters);
+        self.index = Arc::default();
+        self.cache = self.fresh_cache();
+    }
+
+    /// Empty cache with the same capacity, so clones with other settings don't
+    /// fill the shared one
+    fn fresh_cache(&self) -> Option<Arc<Mutex<DirCache>>> {
+        self.cache
+            .as_ref()
+            .map(|cache| Arc::new(Mutex::new(DirCache::new(cache.lock().unwrap().capacity()))))
     }
+
+    pub fn clear_cache(&self) {
+        if let Some(cache) = self.cache.as_ref() {
+            cache.lock().unwrap().clear();
+        }
+        self.index.lock().unwrap().clear();
+    }
+}
+
+/// Path from the directory of a link up to its target when the target is one
+/// of the link's ancestors, `../..`
+fn link_cycle(link: &Path) -> io::Result<Option<PathBuf>> {
+    let target = fs::canonicalize(link)?;
+    let parent = fs::canonicalize(link.parent().unwrap_or(link))?;
+    if !parent.starts_with(&target) {
+        return Ok(None);
+    }
+    let up = parent.components().count() - target.components().count();
+    Ok(Some(match up {
+        0 => PathBuf::from("."),
+        up => (0..up).map(|_| "..").collect(),
+    }))
 }
 
 impl<P: AsRef<Path>> From<P> for FileSystem {
@@ -265,29 +733,122 @@ impl<P: AsRef<Path>> From<P> for FileSystem {
             path: value
                 .normalize_and_canonicalize()
                 .expect("Could not find the path specified"),
-            filters: Rc::new(Not::<Hidden>::default()),
-            sorter: Rc::new(()),
+            filters: Arc::new(Not::<Hidden>::default()),
+            sorter: Arc::new(()),
+            cache: None,
+            max_depth: None,
+            dereference: false,
+            hidden_dirs: false,
+            strict: false,
+            timings: None,
+            index: Arc::default(),
         }
     }
 }
 
 impl FileSystem {
     pub fn entries(&self) -> Result<Vec<Entry>, Box<dyn std::error::Error>> {
-        let mut entries = fs::read_dir(&self.path)?
-            .filter_map(|v| match v {
-                Ok(v) => {
-                    // PERF: Handle error
-                    let entry = Entry::try_from(v).ok()?;
-                    self.filters.keep(&entry).then_some(entry)
+        self.read_dir(&self.path)
+    }
+
+    /// Up to `limit` entries starting at `offset`, for UIs that scroll through
+    /// large directories
+    ///
+    /// The sorted listing is kept until the directory is modified, so each
+    /// page only clones its own entries instead of reading and sorting the
+    /// whole directory again.
+    pub fn entries_page(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Page, Box<dyn std::error::Error>> {
+        let modified = fs::metadata(&self.path)?.modified()?;
+        self.index
+            .lock()
+            .unwrap()
+            .page(modified, offset, limit, || self.read_dir(&self.path))
+    }
+
+    /// Filtered and sorted entries of a directory, using the cache if enabled
+    fn read_dir(&self, path: &Path) -> Result<Vec<Entry>, Box<dyn std::error::Error>> {
+        let modified = match self.cache.as_ref() {
+            Some(cache) => {
+                let modified = fs::metadata(path)?.modified()?;
+                if let Some(entries) = cache.lock().unwrap().get(path, modified) {
+                    return Ok(entries);
                 }
-                _ => None,
-            })
-            .collect::<Vec<_>>();
+                Some(modified)
+            }
+            None => None,
+        };
+
+        let started = Instant::now();
+        let children = match fs::read_dir(path) {
+            Ok(dir) => dir.collect::<Vec<_>>(),
+            Err(err) => {
+                if let Some(timings) = self.timings.as_deref() {
+                    timings.add_errors(1);
+                }
+                return Err(err.into());
+            }
+        };
+        let read = started.elapsed();
+
+        let started = Instant::now();
+        let mut entries = Vec::new();
+        let mut errors = 0;
+        for child in children {
+            let entry = child
+                .map_err(|err| format!("{}: {err}", path.display()).into())
+                .and_then(|child| {
+                    let child_path = child.path();
+                    Entry::try_from(child)
+                        .map_err(|err| format!("{}: {err}", child_path.display()).into())
+                });
+            match entry {
+                Ok(entry) if self.dereference => entries.push(entry.dereference()),
+                Ok(entry) => entries.push(entry),
+                Err(err) if self.strict => return Err(err),
+                Err(_) => errors += 1,
+            }
+        }
+        if let Some(timings) = self.timings.as_deref() {
+            timings.add_readdir(read);
+            timings.add_stat(started.elapsed());
+            timings.add_entries(entries.len());
+            timings.add_directories(1);
+            timings.add_errors(errors);
+        }
+
+        let entries = self.filter_and_sort(entries);
 
-        entries.sort_by(|f, s| self.sorter.compare(f, s));
+        if let (Some(cache), Some(modified)) = (self.cache.as_ref(), modified) {
+            cache
+                .lock()
+                .unwrap()
+                .insert(path, modified, entries.clone());
+        }
 
         Ok(entries)
     }
+
+    /// Apply the filters and sorter to the raw entries of a single directory
+    fn filter_and_sort(&self, mut entries: Vec<Entry>) -> Vec<Entry> {
+        let started = Instant::now();
+        self.filters.prepare(&entries);
+        entries.retain(|entry| self.filters.keep(entry));
+        let filtered = started.elapsed();
+
+        let started = Instant::now();
+        if !self.sorter.preserves_order() {
+            entries.sort_by(|f, s| self.sorter.compare(f, s));
+        }
+        if let Some(timings) = self.timings.as_deref() {
+            timings.add_filter(filtered);
+            timings.add_sort(started.elapsed());
+        }
+        entries
+    }
 }
 
 /// A sorter that will sort directories first
@@ -312,6 +873,10 @@ impl<T: SortStrategy> SortStrategy for Directory<T> {
             _ => self.0.compare(first, second),
         }
     }
+
+    fn column(&self) -> Option<(SortKey, bool)> {
+        self.0.column()
+    }
 }
 impl Filter for Directory {
     fn keep(&self, entry: &Entry) -> bool {
@@ -347,6 +912,10 @@ impl<T: SortStrategy> SortStrategy for Hidden<T> {
             _ => self.0.compare(first, second),
         }
     }
+
+    fn column(&self) -> Option<(SortKey, bool)> {
+        self.0.column()
+    }
 }
 
 impl Filter for Hidden {
@@ -0,0 +1,251 @@
+use std::{borrow::Cow, io, path::Path, time::SystemTime};
+
+use crate::Entry;
+
+/// What a [`Listable`] is, used for styling and type indicators
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    #[default]
+    File,
+    Dir,
+    Link,
+    /// Anything else, e.g. a pipe, socket, or device
+    Other,
+}
+
+impl Kind {
+    /// First character of the permissions column
+    pub fn char(&self) -> char {
+        match self {
+            Self::File => '.',
+            Self::Dir => 'd',
+            Self::Link => 'l',
+            Self::Other => '-',
+        }
+    }
+}
+
+/// Anything that can be printed by the grid, list, and tree formats
+///
+/// [`Entry`] is the main implementation, other items like archive members,
+/// remote files, or synthetic rows such as `..` only need a name and kind.
+/// They get the columns they can fill in and `-` for the rest.
+pub trait Listable {
+    fn name(&self) -> Cow<'_, str>;
+
+    fn kind(&self) -> Kind;
+
+    /// Size in bytes, `None` for directories and items without one
+    fn size(&self) -> Option<u64> {
+        None
+    }
+
+    fn modified(&self) -> io::Result<SystemTime> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    fn accessed(&self) -> io::Result<SystemTime> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    fn created(&self) -> io::Result<SystemTime> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    /// Unix permission bits, `0o755`, for items that have them
+    fn mode(&self) -> Option<u32> {
+        None
+    }
+
+    /// Name of a [`crate::style::Colorizer`] group to style the item with
+    /// instead of the one matched by its kind
+    fn group(&self) -> Option<&str> {
+        None
+    }
+
+    /// Items nested below this one in the tree format
+    fn children(&self) -> Vec<&dyn Listable> {
+        Vec::new()
+    }
+
+    /// The file system entry behind the item, which gets the full styling and
+    /// every column
+    fn entry(&self) -> Option<&Entry> {
+        None
+    }
+}
+
+impl Listable for Entry {
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self.file_name())
+    }
+
+    fn kind(&self) -> Kind {
+        if self.metadata().is_symlink() {
+            Kind::Link
+        } else if self.is_dir() {
+            Kind::Dir
+        } else if self.metadata().is_file() {
+            Kind::File
+        } else {
+            Kind::Other
+        }
+    }
+
+    fn size(&self) -> Option<u64> {
+        (!self.is_dir()).then(|| self.metadata().len())
+    }
+
+    fn modified(&self) -> io::Result<SystemTime> {
+        self.metadata().modified()
+    }
+
+    fn accessed(&self) -> io::Result<SystemTime> {
+        self.metadata().accessed()
+    }
+
+    fn created(&self) -> io::Result<SystemTime> {
+        self.metadata().created()
+    }
+
+    fn entry(&self) -> Option<&Entry> {
+        Some(self)
+    }
+}
+
+/// An item that doesn't come from the file system, e.g. `..`, a drive root,
+/// or a file inside of an archive
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Row {
+    pub(crate) name: String,
+    pub(crate) kind: Kind,
+    pub(crate) size: Option<u64>,
+    pub(crate) modified: Option<SystemTime>,
+    group: Option<String>,
+    pub(crate) children: Vec<Row>,
+}
+
+impl Row {
+    pub fn new<S: ToString>(name: S, kind: Kind) -> Self {
+        Self {
+            name: name.to_string(),
+            kind,
+            ..Default::default()
+        }
+    }
+
+    /// Link to the parent directory, `..`
+    pub fn parent() -> Self {
+        Self::new("..", Kind::Dir)
+    }
+
+    /// Root of a drive or mounted volume, `C:\` or `/mnt/usb`
+    pub fn drive(root: &Path) -> Self {
+        Self::new(root.display(), Kind::Dir)
+    }
+
+    pub fn with_size(self, size: Option<u64>) -> Self {
+        Self { size, ..self }
+    }
+
+    pub fn with_modified(self, modified: Option<SystemTime>) -> Self {
+        Self { modified, ..self }
+    }
+
+    /// Style the row with a [`crate::style::Colorizer`] group, see
+    /// [`Listable::group`]
+    pub fn with_group<S: ToString>(self, group: S) -> Self {
+        Self {
+            group: Some(group.to_string()),
+            ..self
+        }
+    }
+
+    pub fn with_children<I: IntoIterator<Item = Row>>(self, children: I) -> Self {
+        Self {
+            children: children.into_iter().collect(),
+            ..self
+        }
+    }
+}
+
+impl Listable for Row {
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.name)
+    }
+
+    fn kind(&self) -> Kind {
+        self.kind
+    }
+
+    fn size(&self) -> Option<u64> {
+        self.size
+    }
+
+    fn modified(&self) -> io::Result<SystemTime> {
+        self.modified
+            .ok_or_else(|| io::ErrorKind::Unsupported.into())
+    }
+
+    fn group(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+
+    fn children(&self) -> Vec<&dyn Listable> {
+        self.children
+            .iter()
+            .map(|child| child as &dyn Listable)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        format::{Column, List, Tree},
+        style::Colorizer,
+        testing::TempDir,
+        FileSystem,
+    };
+
+    #[test]
+    fn render_rows() {
+        let base = TempDir::new("listable");
+        std::fs::write(base.join("file"), "12345").unwrap();
+
+        let entry = Entry::try_from(base.join("file").as_path()).unwrap();
+        let parent = Row::parent();
+        let archive = Row::new("archive.zip", Kind::Dir).with_children([
+            Row::new("a.txt", Kind::File).with_size(Some(10)),
+            Row::new("nested", Kind::Dir).with_children([Row::new("b.txt", Kind::File)]),
+        ]);
+        let items: [&dyn Listable; 3] = [&parent, &archive, &entry];
+
+        let mut out = Vec::new();
+        List::new(FileSystem::from(base.path()))
+            .columns([Column::Size, Column::Owner, Column::Name])
+            .render_items(&mut out, &items, &Colorizer::default())
+            .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].contains("-") && lines[0].ends_with(".."));
+        assert!(lines[2].contains('5') && lines[2].ends_with("file"));
+
+        let mut out = Vec::new();
+        Tree::new(FileSystem::from(base.path()), false)
+            .branches(crate::format::Branches::ascii())
+            .render_items(&mut out, &items[1..2], &Colorizer::default())
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap().lines().collect::<Vec<_>>(),
+            [
+                "\\-- archive.zip",
+                "    |-- a.txt",
+                "    \\-- nested",
+                "        \\-- b.txt"
+            ]
+        );
+    }
+}
@@ -0,0 +1,756 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
+
+use owo_colors::OwoColorize;
+
+use crate::{
+    bench::Timings,
+    codeowners::CodeOwners,
+    disk,
+    exec::ExecColumn,
+    filter::Filter,
+    format::{
+        Branches, Column, Commas, Fill, Formatter, Grid, Html, Json, List, Names, Print0,
+        StripAnsi, Template, Tree,
+    },
+    hash::HashCache,
+    listable::Listable,
+    sort::SortStrategy,
+    style::Colorizer,
+    Entry, FileSystem,
+};
+
+/// Layout used to print a [`Listing`]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    #[default]
+    Grid,
+    List,
+    Tree,
+    Json,
+    Html,
+    /// Only file names, one per line
+    Names,
+    /// File names separated by commas and wrapped to the terminal width
+    Commas,
+    /// Lines laid out by a template, see [`ListingBuilder::template`]
+    Template,
+    /// Raw paths separated by NUL bytes
+    Print0,
+}
+
+impl Format {
+    /// Output that is read by other programs and has to be written as is
+    fn raw(&self) -> bool {
+        matches!(self, Self::Json | Self::Html | Self::Print0)
+    }
+}
+
+/// The full `xf` pipeline, from reading a directory to printing it
+///
+/// This wires together the same pieces the command line uses so embedding
+/// applications get identical behavior.
+///
+/// # Example
+///
+/// ```no_run
+/// use xf::{listing::{Format, Listing}, style::Colorizer, Directory};
+///
+/// Listing::builder()
+///     .path("src")
+///     .recursive(2)
+///     .sort(Directory::default())
+///     .format(Format::Tree)
+///     .theme(Colorizer::default_theme())
+///     .run()
+///     .unwrap();
+/// ```
+pub struct Listing {
+    file_system: FileSystem,
+    format: Format,
+    long: bool,
+    fill: Fill,
+    width: Option<usize>,
+    columns: Option<Vec<Column>>,
+    header: bool,
+    template: Option<String>,
+    flat_indent: bool,
+    memory_limit: Option<usize>,
+    branches: Branches,
+    compact_dirs: bool,
+    summaries: bool,
+    sections: bool,
+    nested: bool,
+    diagnostics: bool,
+    matching: Option<Box<dyn Filter>>,
+    hashes: Option<HashCache>,
+    lines: bool,
+    exec: Option<ExecColumn>,
+    code_owners: Option<CodeOwners>,
+    flush_every: usize,
+    disk_usage: bool,
+    timings: bool,
+    directory: bool,
+    #[cfg(feature = "media")]
+    media: bool,
+    theme: Colorizer,
+}
+
+impl Listing {
+    pub fn builder() -> ListingBuilder {
+        ListingBuilder::default()
+    }
+
+    pub fn file_system(&self) -> &FileSystem {
+        &self.file_system
+    }
+
+    pub fn run(self) -> Result<(), Box<dyn std::error::Error>> {
+        self.render_to(&mut std::io::stdout().lock())
+    }
+
+    /// Write the listing to `writer` instead of stdout
+    ///
+    /// Escape sequences are removed when the theme isn't
+    /// [`Colorizer::colored`], except from the JSON, HTML, and NUL separated
+    /// formats which are written as is.
+    pub fn render_to(mut self, writer: &mut dyn Write) -> Result<(), Box<dyn std::error::Error>> {
+        let strip = !self.theme.colored();
+        let mut plain;
+        let out: &mut dyn Write = match strip && !self.format.raw() {
+            true => {
+                plain = StripAnsi::new(&mut *writer);
+                &mut plain
+            }
+            false => &mut *writer,
+        };
+
+        let started = Instant::now();
+        let timings = self.timings.then(Arc::<Timings>::default);
+        self.file_system.set_timings(timings.clone());
+
+        let footer = if self.disk_usage {
+            Some(disk::usage_footer(&disk::usage(self.file_system.path())?))
+        } else {
+            None
+        };
+
+        let directory = self
+            .directory
+            .then(|| self.file_system.path().to_path_buf());
+        if self.sections
+            && directory.is_none()
+            && matches!(self.format, Format::Grid | Format::List)
+        {
+            self.render_sections(out)?;
+        } else {
+            let (mut formatter, theme) = self.formatter()?;
+            match directory {
+                Some(path) => {
+                    let entry = Entry::try_from(path.as_path())?;
+                    writeln!(out, "{}", formatter.render_entry(&entry, &theme))?;
+                }
+                None => formatter.render_to(out, theme)?,
+            }
+        }
+
+        // Footers are read by people even after raw output
+        let mut plain;
+        let writer: &mut dyn Write = match strip {
+            true => {
+                plain = StripAnsi::new(writer);
+                &mut plain
+            }
+            false => writer,
+        };
+        if let Some(footer) = footer {
+            writeln!(writer, "{footer}")?;
+        }
+        if let Some(timings) = timings {
+            writeln!(writer, "{}", timings.footer(started.elapsed()).dimmed())?;
+        }
+        Ok(())
+    }
+
+    /// Print items that don't come from the file system, e.g. a remote
+    /// directory, with the same layout and theme
+    ///
+    /// Only the grid, list, and tree formats can print [`Listable`] items. The
+    /// file system of the listing is only used for its options, its path isn't
+    /// read.
+    pub fn render_items(
+        mut self,
+        writer: &mut dyn Write,
+        items: &[&dyn Listable],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut plain;
+        let writer: &mut dyn Write = match self.theme.colored() {
+            true => writer,
+            false => {
+                plain = StripAnsi::new(writer);
+                &mut plain
+            }
+        };
+
+        match self.format {
+            Format::Grid => self.grid().render_items(writer, items, &self.theme),
+            Format::List => self.list().render_items(writer, items, &self.theme),
+            Format::Tree => self.tree().render_items(writer, items, &self.theme),
+            format => Err(format!("{format:?} format can only list the file system").into()),
+        }
+    }
+}
+
+/// Grid or list that prints each directory of [`ListingBuilder::sections`]
+enum Section {
+    Grid(Grid),
+    List(List),
+}
+
+impl Listing {
+    /// Print the entries of every directory under its own header, depth first
+    fn render_sections(
+        &mut self,
+        writer: &mut dyn Write,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let file_system = self.file_system.clone();
+        let mut section = match self.format {
+            Format::List => Section::List(self.list()),
+            _ => Section::Grid(self.grid()),
+        };
+        let root = file_system.path().to_path_buf();
+        let entries = file_system.read_dir(&root)?;
+        self.render_section(writer, &file_system, &mut section, &root, entries, 1)
+    }
+
+    fn render_section(
+        &self,
+        writer: &mut dyn Write,
+        file_system: &FileSystem,
+        section: &mut Section,
+        path: &Path,
+        entries: Vec<Entry>,
+        depth: usize,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let relative = path.strip_prefix(file_system.path()).unwrap_or(path);
+        if depth > 1 {
+            writeln!(writer)?;
+        }
+        let header = match relative.as_os_str().is_empty() {
+            true => PathBuf::from("."),
+            false => Path::new(".").join(relative),
+        };
+        writeln!(writer, "{}:", header.display())?;
+
+        let items = entries
+            .iter()
+            .map(|entry| entry as &dyn Listable)
+            .collect::<Vec<_>>();
+        match section {
+            Section::Grid(grid) => grid.render_items(writer, &items, &self.theme)?,
+            Section::List(list) => list.render_items(writer, &items, &self.theme)?,
+        }
+
+        if file_system.max_depth().is_some_and(|max| depth >= max) {
+            return Ok(());
+        }
+        for entry in entries
+            .iter()
+            .filter(|entry| file_system.descends_into(entry))
+        {
+            match file_system.read_dir(entry.path()) {
+                Ok(nested) => self.render_section(
+                    writer,
+                    file_system,
+                    section,
+                    entry.path(),
+                    nested,
+                    depth + 1,
+                )?,
+                Err(err) if file_system.strict() => return Err(err),
+                Err(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Formatter for the format along with the theme to render it with
+    fn formatter(mut self) -> Result<(Box<dyn Formatter>, Colorizer), Box<dyn std::error::Error>> {
+        let formatter: Box<dyn Formatter> = match self.format {
+            Format::Grid => Box::new(self.grid()),
+            Format::List => Box::new(self.list()),
+            Format::Tree => Box::new(self.tree()),
+            Format::Json => Box::new(Json::new(self.file_system).nested(self.nested)),
+            Format::Html => Box::new(Html::new(self.file_system)),
+            Format::Template => Box::new(
+                Template::new(
+                    self.file_system,
+                    self.template.as_deref().unwrap_or("{name}"),
+                )?
+                .flush_every(self.flush_every),
+            ),
+            Format::Print0 => Box::new(Print0::new(self.file_system)),
+            Format::Commas => Box::new(Commas::new(self.file_system).flush_every(self.flush_every)),
+            Format::Names => Box::new(
+                Names::new(self.file_system)
+                    .color(self.theme.colored())
+                    .flush_every(self.flush_every),
+            ),
+        };
+        Ok((formatter, self.theme))
+    }
+
+    fn grid(&mut self) -> Grid {
+        Grid::new(std::mem::take(&mut self.file_system))
+            .long(self.long)
+            .fill(self.fill)
+            .width(self.width)
+    }
+
+    fn list(&mut self) -> List {
+        let mut list = List::new(std::mem::take(&mut self.file_system))
+            .lines(self.lines)
+            .exec(self.exec.clone())
+            .code_owners(self.code_owners.take())
+            .header(self.header)
+            .flush_every(self.flush_every);
+        if let Some(columns) = self.columns.take() {
+            list = list.columns(columns);
+        }
+        if let Some(cache) = self.hashes.take() {
+            list = list.hash(cache);
+        }
+        #[cfg(feature = "media")]
+        {
+            list = list.media(self.media);
+        }
+        list
+    }
+
+    fn tree(&mut self) -> Tree {
+        let mut tree = Tree::new(std::mem::take(&mut self.file_system), self.long)
+            .flat(self.flat_indent)
+            .memory_limit(self.memory_limit)
+            .branches(self.branches.clone())
+            .compact_dirs(self.compact_dirs)
+            .summaries(self.summaries)
+            .diagnostics(self.diagnostics)
+            .flush_every(self.flush_every);
+        if let Some(matching) = self.matching.take() {
+            tree = tree.matching(matching);
+        }
+        tree
+    }
+}
+
+#[derive(Default)]
+pub struct ListingBuilder {
+    path: Option<PathBuf>,
+    filter: Option<Box<dyn Filter>>,
+    sorter: Option<Box<dyn SortStrategy>>,
+    format: Format,
+    long: bool,
+    fill: Fill,
+    width: Option<usize>,
+    columns: Option<Vec<Column>>,
+    header: bool,
+    template: Option<String>,
+    flat_indent: bool,
+    memory_limit: Option<usize>,
+    branches: Branches,
+    compact_dirs: bool,
+    summaries: bool,
+    sections: bool,
+    nested: bool,
+    diagnostics: bool,
+    matching: Option<Box<dyn Filter>>,
+    depth: Option<usize>,
+    dereference: bool,
+    hidden_dirs: bool,
+    strict: bool,
+    hashes: Option<HashCache>,
+    lines: bool,
+    exec: Option<ExecColumn>,
+    code_owners: Option<CodeOwners>,
+    flush_every: usize,
+    disk_usage: bool,
+    timings: bool,
+    directory: bool,
+    #[cfg(feature = "media")]
+    media: bool,
+    theme: Option<Colorizer>,
+}
+
+impl ListingBuilder {
+    /// Directory to list, defaults to the current directory
+    pub fn path<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.path = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Limit recursive formats to `depth` levels
+    ///
+    /// The grid and list formats become recursive as well and print each
+    /// directory down to the depth under its own header, see
+    /// [`ListingBuilder::sections`].
+    pub fn recursive(mut self, depth: usize) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+
+    /// Follow symbolic links, see [`FileSystem::with_dereference`]
+    pub fn dereference(mut self, dereference: bool) -> Self {
+        self.dereference = dereference;
+        self
+    }
+
+    /// Walk into hidden directories, see [`FileSystem::with_hidden_dirs`]
+    pub fn hidden_dirs(mut self, hidden_dirs: bool) -> Self {
+        self.hidden_dirs = hidden_dirs;
+        self
+    }
+
+    /// Fail on anything that can't be read, see [`FileSystem::with_strict`]
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn filter<F: Filter + 'static>(mut self, filter: F) -> Self {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    pub fn sort<S: SortStrategy + 'static>(mut self, sorter: S) -> Self {
+        self.sorter = Some(Box::new(sorter));
+        self
+    }
+
+    pub fn format(mut self, format: Format) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Include the permission, size, and date columns in the grid and tree
+    /// formats
+    pub fn long(mut self, long: bool) -> Self {
+        self.long = long;
+        self
+    }
+
+    /// Order entries are placed into the grid format
+    pub fn fill(mut self, fill: Fill) -> Self {
+        self.fill = fill;
+        self
+    }
+
+    /// Wrap the grid format to `width` columns instead of the terminal width
+    pub fn width(mut self, width: Option<usize>) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Columns printed by the list format, see [`List::columns`]
+    pub fn columns<I: IntoIterator<Item = Column>>(mut self, columns: I) -> Self {
+        self.columns = Some(columns.into_iter().collect());
+        self
+    }
+
+    /// Print a row of column titles in the list format
+    pub fn header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Print relative paths indented by depth in the tree format instead of
+    /// drawing the tree
+    pub fn flat_indent(mut self, flat_indent: bool) -> Self {
+        self.flat_indent = flat_indent;
+        self
+    }
+
+    /// Keep at most `rows` lines of the tree format in memory and spill the
+    /// rest to a temp file
+    pub fn memory_limit(mut self, rows: Option<usize>) -> Self {
+        self.memory_limit = rows;
+        self
+    }
+
+    /// Glyphs used to draw the branches of the tree format
+    pub fn branches(mut self, branches: Branches) -> Self {
+        self.branches = branches;
+        self
+    }
+
+    /// Collapse chains of single child directories into one line in the tree
+    /// format
+    pub fn compact_dirs(mut self, compact_dirs: bool) -> Self {
+        self.compact_dirs = compact_dirs;
+        self
+    }
+
+    /// Follow directories in the tree format with their number of entries
+    /// and total size
+    pub fn summaries(mut self, summaries: bool) -> Self {
+        self.summaries = summaries;
+        self
+    }
+
+    /// List every directory below the path in turn under a `./src:` header
+    /// in the grid and list formats, like `ls -R`
+    ///
+    /// Each directory is filtered and sorted on its own and the walk stops at
+    /// the max depth, see [`ListingBuilder::recursive`].
+    pub fn sections(mut self, sections: bool) -> Self {
+        self.sections = sections;
+        self
+    }
+
+    /// Nest entries under their directories in the JSON format, see
+    /// [`Json::nested`]
+    pub fn nested(mut self, nested: bool) -> Self {
+        self.nested = nested;
+        self
+    }
+
+    /// Show why directories weren't walked into in the tree format, e.g. a
+    /// link cycle or the max depth
+    pub fn diagnostics(mut self, diagnostics: bool) -> Self {
+        self.diagnostics = diagnostics;
+        self
+    }
+
+    /// Only show matches and the directories leading to them in the tree
+    /// format, see [`Tree::matching`]
+    pub fn matching<F: Filter + 'static>(mut self, filter: F) -> Self {
+        self.matching = Some(Box::new(filter));
+        self
+    }
+
+    /// Print each entry with a template, `{perms} {size:>8} {name}`, see
+    /// [`Template`]
+    ///
+    /// This also switches to [`Format::Template`].
+    pub fn template<S: ToString>(mut self, template: S) -> Self {
+        self.template = Some(template.to_string());
+        self.format = Format::Template;
+        self
+    }
+
+    /// Show content hashes in the list format using the given cache
+    pub fn hash(mut self, cache: HashCache) -> Self {
+        self.hashes = Some(cache);
+        self
+    }
+
+    /// Show the number of lines in text files in the list format
+    pub fn lines(mut self, lines: bool) -> Self {
+        self.lines = lines;
+        self
+    }
+
+    /// Show the output of a command run for each entry in the list format
+    pub fn exec(mut self, exec: Option<ExecColumn>) -> Self {
+        self.exec = exec;
+        self
+    }
+
+    /// Show the owners from a `CODEOWNERS` file after each name in the list
+    /// format
+    pub fn code_owners(mut self, code_owners: Option<CodeOwners>) -> Self {
+        self.code_owners = code_owners;
+        self
+    }
+
+    /// Flush the list, tree, names, template, and commas formats after every `n`
+    /// lines, defaults to `1`
+    pub fn flush_every(mut self, n: usize) -> Self {
+        self.flush_every = n;
+        self
+    }
+
+    /// Print the used and free space of the listed file system after the listing
+    pub fn disk_usage(mut self, disk_usage: bool) -> Self {
+        self.disk_usage = disk_usage;
+        self
+    }
+
+    /// Print how long reading and rendering took after the listing, see
+    /// [`Timings`]
+    pub fn timings(mut self, timings: bool) -> Self {
+        self.timings = timings;
+        self
+    }
+
+    /// Print the listed directory itself in the chosen format instead of its
+    /// contents
+    pub fn directory(mut self, directory: bool) -> Self {
+        self.directory = directory;
+        self
+    }
+
+    /// Show image dimensions and audio/video durations in the list format
+    #[cfg(feature = "media")]
+    pub fn media(mut self, media: bool) -> Self {
+        self.media = media;
+        self
+    }
+
+    /// Colorizer used to style the output, defaults to
+    /// [`Colorizer::default_theme`]
+    pub fn theme(mut self, theme: Colorizer) -> Self {
+        self.theme = Some(theme);
+        self
+    }
+
+    pub fn build(self) -> Listing {
+        let mut file_system = match self.path {
+            Some(path) => FileSystem::from(path),
+            None => FileSystem::default(),
+        };
+
+        if let Some(filter) = self.filter {
+            file_system.set_filter(filter);
+        }
+
+        if let Some(sorter) = self.sorter {
+            file_system.set_sorter(sorter);
+        }
+
+        file_system.set_max_depth(self.depth);
+        file_system.set_dereference(self.dereference);
+        file_system.set_hidden_dirs(self.hidden_dirs);
+        file_system.set_strict(self.strict);
+
+        Listing {
+            file_system,
+            format: self.format,
+            long: self.long,
+            fill: self.fill,
+            width: self.width,
+            columns: self.columns,
+            header: self.header,
+            template: self.template,
+            flat_indent: self.flat_indent,
+            memory_limit: self.memory_limit,
+            branches: self.branches,
+            compact_dirs: self.compact_dirs,
+            summaries: self.summaries,
+            sections: self.sections || self.depth.is_some(),
+            nested: self.nested,
+            diagnostics: self.diagnostics,
+            matching: self.matching,
+            hashes: self.hashes,
+            lines: self.lines,
+            exec: self.exec,
+            code_owners: self.code_owners,
+            flush_every: self.flush_every,
+            disk_usage: self.disk_usage,
+            timings: self.timings,
+            directory: self.directory,
+            #[cfg(feature = "media")]
+            media: self.media,
+            theme: self.theme.unwrap_or_else(Colorizer::default_theme),
+        }
+    }
+
+    /// Build the listing and print it
+    pub fn run(self) -> Result<(), Box<dyn std::error::Error>> {
+        self.build().run()
+    }
+
+    /// Build the listing and write it to `writer`
+    pub fn render_to(self, writer: &mut dyn Write) -> Result<(), Box<dyn std::error::Error>> {
+        self.build().render_to(writer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn list_directory_itself() {
+        let base = TempDir::new("directory");
+        std::fs::create_dir_all(base.join("nested")).unwrap();
+        std::fs::write(base.join("file"), "").unwrap();
+
+        let render = |directory: bool| {
+            let mut out = Vec::new();
+            Listing::builder()
+                .path(&base)
+                .template("{name}{if dir}/{end}")
+                .directory(directory)
+                .render_to(&mut out)
+                .unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        assert_eq!(render(false).lines().count(), 2);
+        assert_eq!(
+            render(true),
+            format!("{}/\n", base.file_name().unwrap().to_string_lossy())
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn raw_formats_keep_escapes() {
+        let base = TempDir::new("print0-escape");
+        std::fs::write(base.join("a\x1b[31mb"), "").unwrap();
+
+        let render = |format: Format| {
+            let mut out = Vec::new();
+            Listing::builder()
+                .path(&base)
+                .format(format)
+                .theme(Colorizer::default().color_mode(crate::style::ColorMode::Never))
+                .render_to(&mut out)
+                .unwrap();
+            out
+        };
+        let path = FileSystem::from(base.path()).path().join("a\x1b[31mb");
+        let mut expected = path.as_os_str().as_encoded_bytes().to_vec();
+        expected.push(b'\0');
+        assert_eq!(render(Format::Print0), expected);
+        assert_eq!(render(Format::Names), b"ab\n");
+    }
+
+    #[test]
+    fn recursive_sections() {
+        let base = TempDir::new("sections");
+        std::fs::create_dir_all(base.join("b").join("deep")).unwrap();
+        std::fs::create_dir_all(base.join(".hidden")).unwrap();
+        std::fs::write(base.join("a"), "").unwrap();
+        std::fs::write(base.join("b").join("c"), "").unwrap();
+
+        let render = |sections: bool, depth: Option<usize>| {
+            let mut builder = Listing::builder()
+                .path(&base)
+                .filter(crate::filter::Not::<crate::Hidden>::default())
+                .sort(crate::sort::Natural)
+                .format(Format::Grid)
+                .width(Some(80))
+                .sections(sections)
+                .theme(Colorizer::default().color_mode(crate::style::ColorMode::Never));
+            if let Some(depth) = depth {
+                builder = builder.recursive(depth);
+            }
+            let mut out = Vec::new();
+            builder.render_to(&mut out).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        assert_eq!(
+            render(true, None),
+            ".:\na  b\n\n./b:\nc  deep\n\n./b/deep:\n"
+        );
+        assert_eq!(render(true, Some(2)), ".:\na  b\n\n./b:\nc  deep\n");
+        // A depth alone makes the grid recursive
+        assert_eq!(render(false, Some(2)), render(true, Some(2)));
+        assert_eq!(render(false, None), "a  b\n");
+    }
+}
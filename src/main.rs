@@ -1,15 +1,32 @@
-use clap::{ArgAction, ArgGroup};
-use owo_colors::{colors::xterm::Gray, Style};
+use std::{io::Write, path::Path, str::FromStr, sync::Arc, time::Duration};
+
+use clap::ArgAction;
 use xf::{
-    filter::{Binary, Match},
-    format::Formatter,
-    sort::{DateTime, Natural, Reverse, Size},
-    style::{Colorizer, GroupMatch},
-    Directory, FileSystem,
+    action::Action,
+    bench::Report,
+    codeowners::{CodeOwners, OwnedBy},
+    config::Config,
+    debug::print_entry,
+    disk::{drives, print_drives},
+    exec::ExecColumn,
+    filter::{
+        All, Archive, Binary, Contains, Filter, GitModified, GitTracked, GitUntracked, Match, Not,
+        ReadOnly, System,
+    },
+    format::{Branches, Column, Fill, StripAnsi, Template},
+    hash::HashCache,
+    icons::Icons,
+    known::KnownFolders,
+    listing::{Format, Listing},
+    project::{find_root, DEFAULT_MARKERS},
+    snapshot::{print_diff, Snapshot},
+    sort::{Chain, Extension, IgnoreCase, Natural, Reverse, SortStrategy, Weighted},
+    style::{ColorMode, Colorizer, PathDisplay, SizeUnits, SymlinkSize, ThemeGroup, TimeStyle},
+    Directory, FileSystem, Hidden,
 };
 
 fn main() {
-    let matches = clap::Command::new("xf")
+    let command = clap::Command::new("xf")
         .bin_name("xf")
         .display_name("xf")
         .disable_help_flag(true)
@@ -20,17 +37,137 @@ fn main() {
         )
         .arg(clap::Arg::new("help").long("help").action(ArgAction::Help))
         .arg(
-            clap::Arg::new("recursive")
+            clap::Arg::new("tree")
                 .long("tree")
+                .short('T')
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("recursive")
+                .long("recursive")
                 .short('R')
+                .help("List every directory below the path in turn under a ./dir: header, like ls -R")
+                .conflicts_with_all([
+                    "tree",
+                    "flat-indent",
+                    "directory",
+                    "json",
+                    "html",
+                    "one",
+                    "print0",
+                    "commas",
+                    "format",
+                ])
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("flat-indent")
+                .long("flat-indent")
+                .help("Recursively list relative paths indented by depth without tree lines")
+                .conflicts_with_all(["json", "grid"])
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("directory")
+                .long("directory")
+                .short('d')
+                .help("List the directory itself instead of its contents")
+                .conflicts_with_all(["tree", "flat-indent"])
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("strict")
+                .long("strict")
+                .help("Fail with a nonzero exit code on anything that can't be read instead of skipping it")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("debug-entry")
+                .long("debug-entry")
+                .value_name("PATH")
+                .help("Print everything xf knows about an entry, including the verdict of each filter")
+                .action(ArgAction::Set),
+        )
+        .arg(
+            clap::Arg::new("dereference")
+                .long("dereference")
+                .help("Follow symbolic links and list them as the files and directories they point to, like ls -L (-L here is --level). Without it a link to a directory is listed as a file and isn't descended into")
                 .action(ArgAction::SetTrue),
         )
+        .arg(
+            clap::Arg::new("level")
+                .long("level")
+                .short('L')
+                .help("Limit how many levels are listed, the grid and list formats list each level like -R")
+                .value_parser(clap::value_parser!(usize))
+                .action(ArgAction::Set),
+        )
         .arg(
             clap::Arg::new("long")
                 .long("long")
                 .short('l')
                 .action(ArgAction::SetTrue),
         )
+        .arg(
+            clap::Arg::new("icons")
+                .long("icons")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("root")
+                .long("root")
+                .help("List the root of the enclosing project, found by .git, Cargo.toml, or package.json")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("hyperlink")
+                .long("hyperlink")
+                .help("Make file names clickable links in supporting terminals")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("accessible")
+                .long("accessible")
+                .help("Spell out entry types, size units, and tree depth for screen readers")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("folder-labels")
+                .long("folder-labels")
+                .help("Label localized user folders with their English name, Downloads")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("grid")
+                .long("grid")
+                .short('G')
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("slash")
+                .long("slash")
+                .short('p')
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("color")
+                .long("color")
+                .value_name("WHEN")
+                .help("When to use colors: auto, always, or never, auto is off when NO_COLOR is set or TERM=dumb")
+                .num_args(0..=1)
+                .require_equals(true)
+                .default_value("auto")
+                .default_missing_value("always")
+                .value_parser(ColorMode::from_str)
+                .action(ArgAction::Set),
+        )
+        .arg(
+            clap::Arg::new("classify")
+                .long("classify")
+                .short('F')
+                .help("Append an indicator to names: / directory, * executable, @ link, | pipe, = socket")
+                .action(ArgAction::SetTrue),
+        )
         .arg(
             clap::Arg::new("filter")
                 .long("filter")
@@ -41,101 +178,902 @@ fn main() {
             clap::Arg::new("all")
                 .long("all")
                 .short('a')
+                .help("Show hidden entries, their contents are only listed with --hidden-dirs")
                 .action(ArgAction::SetTrue),
         )
         .arg(
-            clap::Arg::new("last-modified")
-                .long("last-modified")
-                .short('t')
+            clap::Arg::new("hidden-dirs")
+                .long("hidden-dirs")
+                .help("Descend into hidden directories when listing recursively")
                 .action(ArgAction::SetTrue),
         )
         .arg(
             clap::Arg::new("reverse")
                 .long("reverse")
                 .short('r')
+                .help("Reverse the sort order")
                 .action(ArgAction::SetTrue),
         )
         .arg(
-            clap::Arg::new("by-size")
-                .long("by-size")
-                .short('S')
+            clap::Arg::new("hash")
+                .long("hash")
                 .action(ArgAction::SetTrue),
         )
-        .group(
-            ArgGroup::new("sorting")
-                .args(["last-modified", "reverse", "by-size"])
-                .multiple(false)
-                .required(false),
+        .arg(
+            clap::Arg::new("no-cache")
+                .long("no-cache")
+                .requires("hash")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("grep")
+                .long("grep")
+                .value_name("PATTERN")
+                .help("Only list directories and text files whose contents match a regex")
+                .value_parser(|pattern: &str| {
+                    Contains::new(pattern)
+                        .map(|_| pattern.to_string())
+                        .map_err(|err| err.to_string())
+                })
+                .action(ArgAction::Set),
+        )
+        .arg(
+            clap::Arg::new("header")
+                .long("header")
+                .help("Print the listing root and a row of column titles in the long listing")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("tracked")
+                .long("tracked")
+                .help("Only list files tracked by git")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("modified")
+                .long("modified")
+                .help("Only list files with uncommitted git changes")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("untracked")
+                .long("untracked")
+                .help("Only list files that aren't tracked by git")
+                .conflicts_with("tracked")
+                .action(ArgAction::SetTrue),
         )
-        .get_matches();
+        .arg(
+            clap::Arg::new("system")
+                .long("system")
+                .help("Only list files with the windows system attribute, usually with -a")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("readonly")
+                .long("readonly")
+                .help("Only list readonly files")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("archive")
+                .long("archive")
+                .help("Only list files with the windows archive attribute")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("columns")
+                .long("columns")
+                .help("Comma separated columns of the long listing: perms, size, blocks, date, created, accessed, owner, group, inode, cloud, name")
+                .value_name("COLUMNS")
+                .value_parser(Column::parse_list)
+                .action(ArgAction::Set),
+        )
+        .arg(
+            clap::Arg::new("codeowners")
+                .long("codeowners")
+                .help("Show the owners from the repository's CODEOWNERS file after each name")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("owned-by")
+                .long("owned-by")
+                .value_name("OWNER")
+                .help("Only list entries owned by OWNER in the repository's CODEOWNERS file")
+                .action(ArgAction::Set),
+        )
+        .arg(
+            clap::Arg::new("no-profiles")
+                .long("no-profiles")
+                .help("Don't style files based on the detected project type, e.g. cargo or npm")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("dir-counts")
+                .long("dir-counts")
+                .value_name("HUGE")
+                .help("Dim empty directories and highlight ones with at least HUGE visible entries [default: 1000]")
+                .num_args(0..=1)
+                .require_equals(true)
+                .default_missing_value("1000")
+                .value_parser(clap::value_parser!(usize))
+                .action(ArgAction::Set),
+        )
+        .arg(
+            clap::Arg::new("time-style")
+                .long("time-style")
+                .value_name("STYLE")
+                .help("Date format: default, iso, long-iso, full-iso, relative, or +FORMAT")
+                .value_parser(TimeStyle::from_str)
+                .action(ArgAction::Set),
+        )
+        .arg(
+            clap::Arg::new("si")
+                .long("si")
+                .help("Show sizes in powers of 1000 (kB, MB) instead of 1024")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("no-owner")
+                .long("no-owner")
+                .help("Hide the owner and group columns of the long listing")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("blocks")
+                .long("blocks")
+                .short('s')
+                .help("Show the allocated size on disk in front of each entry")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("created")
+                .long("created")
+                .help("Show when each entry was created, `-` where the platform doesn't report it")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("accessed")
+                .long("accessed")
+                .help("Show when each entry was last accessed, `-` where the platform doesn't report it")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("lines")
+                .long("lines")
+                .requires("long")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("exec-column")
+                .long("exec-column")
+                .value_name("COMMAND")
+                .help("Show the first line a command prints for each entry, {} is replaced with its path")
+                .requires("long")
+                .action(ArgAction::Set),
+        )
+        .arg(
+            clap::Arg::new("exec-timeout")
+                .long("exec-timeout")
+                .value_name("SECONDS")
+                .help("Give up on --exec-column commands after this many seconds, defaults to 5")
+                .requires("exec-column")
+                .value_parser(|secs: &str| {
+                    secs.parse::<f64>()
+                        .ok()
+                        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+                        .ok_or(format!("invalid number of seconds '{secs}'"))
+                })
+                .action(ArgAction::Set),
+        )
+        .arg(
+            clap::Arg::new("across")
+                .long("across")
+                .help("Fill the grid left to right, the default")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("down")
+                .long("down")
+                .help("Fill the grid top to bottom like ls")
+                .conflicts_with("across")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("one")
+                .short('1')
+                .help("Print only file names, one per line")
+                .conflicts_with_all(["long", "tree", "json", "grid"])
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("print0")
+                .long("print0")
+                .short('0')
+                .help("Print raw paths separated by NUL bytes for xargs -0")
+                .conflicts_with_all(["long", "tree", "json", "grid", "one", "flat-indent"])
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("commas")
+                .short('m')
+                .help("Print file names separated by commas")
+                .conflicts_with_all(["long", "tree", "json", "grid", "one"])
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("format")
+                .long("format")
+                .help("Print each entry with a template, '{perms} {size:>8} {name}'")
+                .value_name("TEMPLATE")
+                .value_parser(|template: &str| Template::check(template).map(|_| template.to_string()))
+                .conflicts_with_all(["long", "tree", "json", "grid", "one", "commas"])
+                .action(ArgAction::Set),
+        )
+        .arg(
+            clap::Arg::new("json")
+                .long("json")
+                .help("Print entries as JSON, nested under their directories with --tree")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("html")
+                .long("html")
+                .help("Print a standalone HTML directory index page")
+                .conflicts_with_all(["long", "tree", "json", "grid", "flat-indent"])
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("flush-every")
+                .long("flush-every")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("1")
+                .action(ArgAction::Set),
+        )
+        .arg(
+            clap::Arg::new("branches")
+                .long("branches")
+                .value_name("STYLE")
+                .help("Tree branch glyphs: unicode, rounded, ascii, or 'fork,last,line'")
+                .value_parser(Branches::from_str)
+                .action(ArgAction::Set),
+        )
+        .arg(
+            clap::Arg::new("compact-dirs")
+                .long("compact-dirs")
+                .help("Collapse directories with a single child directory into one tree line")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("summaries")
+                .long("summaries")
+                .help("Show the number of entries and total size of each directory in the tree")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("diagnostics")
+                .long("diagnostics")
+                .help("Show link cycles, the max depth, and unreadable directories as tree nodes")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("symlink-size")
+                .long("symlink-size")
+                .value_name("MODE")
+                .help("Size shown for symlinks: marker, target, or link")
+                .value_parser(SymlinkSize::from_str)
+                .action(ArgAction::Set),
+        )
+        .arg(
+            clap::Arg::new("paths")
+                .long("paths")
+                .value_name("MODE")
+                .help("Show file names as: name, relative to the listed path, or absolute")
+                .value_parser(PathDisplay::from_str)
+                .action(ArgAction::Set),
+        )
+        .arg(
+            clap::Arg::new("memory-limit")
+                .long("memory-limit")
+                .value_name("ROWS")
+                .help("Keep at most ROWS lines of a tree in memory and spill the rest to disk")
+                .value_parser(clap::value_parser!(usize))
+                .action(ArgAction::Set),
+        )
+        .arg(
+            clap::Arg::new("drives")
+                .long("drives")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("du")
+                .long("du")
+                .help("Show the total size of everything below each directory")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("disk-usage")
+                .long("disk-usage")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("timings")
+                .long("timings")
+                .help("Print how long reading directories, reading metadata, and rendering took")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("sort")
+                .long("sort")
+                .help("Sort by name, version, size, time, extension, suffix, dotfiles, dir, or lines, or none to keep the order entries are read in. Size sorts largest first and time oldest first. Separate keys with commas to break ties and prefix one with - to reverse it, e.g. dir,-time,name")
+                .value_name("KEYS")
+                .allow_hyphen_values(true)
+                .value_parser(|keys: &str| keys.parse::<Chain>().map(|_| keys.to_string()))
+                .action(ArgAction::Set),
+        )
+        .arg(
+            clap::Arg::new("ignore-case")
+                .long("ignore-case")
+                .help("Sort names without regard to case")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("by-extension")
+                .short('X')
+                .help("Sort files by extension after directories")
+                .conflicts_with("sort")
+                .action(ArgAction::SetTrue),
+        )
+        .subcommand(
+            clap::Command::new("snapshot")
+                .about("Save and compare snapshots of a directory tree")
+                .subcommand_required(true)
+                .subcommand(
+                    clap::Command::new("save")
+                        .arg(clap::Arg::new("name").required(true))
+                        .arg(clap::Arg::new("path").default_value(".")),
+                )
+                .subcommand(
+                    clap::Command::new("diff")
+                        .arg(clap::Arg::new("name").required(true))
+                        .arg(clap::Arg::new("path")),
+                ),
+        )
+        .subcommand(
+            clap::Command::new("open")
+                .about("Open an entry with its default application")
+                .arg(clap::Arg::new("path").required(true))
+                .arg(
+                    clap::Arg::new("reveal")
+                        .long("reveal")
+                        .help("Show the entry in the file manager instead")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            clap::Command::new("bench")
+                .about("Time each phase of a recursive walk")
+                .arg(clap::Arg::new("path").default_value("."))
+                .arg(
+                    clap::Arg::new("depth")
+                        .long("depth")
+                        .help("Limit how many levels are walked")
+                        .value_parser(clap::value_parser!(usize)),
+                ),
+        );
+
+    #[cfg(feature = "serve")]
+    let command = command.subcommand(
+        clap::Command::new("serve")
+            .about("Browse a directory over HTTP")
+            .arg(clap::Arg::new("path").default_value("."))
+            .arg(
+                clap::Arg::new("addr")
+                    .long("addr")
+                    .default_value("127.0.0.1:8080"),
+            ),
+    );
+
+    #[cfg(feature = "media")]
+    let command = command.arg(
+        clap::Arg::new("media")
+            .long("media")
+            .requires("long")
+            .action(ArgAction::SetTrue),
+    );
+
+    let matches = command.get_matches();
+
+    if let Some(("snapshot", matches)) = matches.subcommand() {
+        snapshot(matches).unwrap();
+        return;
+    }
+
+    if let Some(("open", matches)) = matches.subcommand() {
+        let path = matches.get_one::<String>("path").unwrap();
+        let action = match matches.get_flag("reveal") {
+            true => Action::Reveal,
+            false => Action::Open,
+        };
+        if let Err(err) = action.run(path) {
+            eprintln!("xf: failed to open '{path}': {err}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(("bench", matches)) = matches.subcommand() {
+        let file_system = FileSystem::from(matches.get_one::<String>("path").unwrap())
+            .with_sorter(Natural)
+            .with_max_depth(matches.get_one::<usize>("depth").copied());
+        Report::run(&file_system, &Colorizer::default_theme())
+            .print(&mut std::io::stdout())
+            .unwrap();
+        return;
+    }
 
-    let path = matches
+    #[cfg(feature = "serve")]
+    if let Some(("serve", matches)) = matches.subcommand() {
+        xf::serve::serve(
+            matches.get_one::<String>("path").unwrap(),
+            matches.get_one::<String>("addr").unwrap().as_str(),
+        )
+        .unwrap();
+        return;
+    }
+
+    let mut config = Config::load().unwrap_or_else(|err| {
+        eprintln!("failed to load config: {err}");
+        Config::default()
+    });
+
+    let mut path = matches
         .get_one::<String>("path")
         .cloned()
         .unwrap_or(".".to_string());
-    let mut file_system = FileSystem::from(path).with_sorter(Directory::default());
 
-    if matches.get_flag("all") {
-        if let Some(f) = matches.get_one::<String>("filter") {
-            file_system.set_filter(Directory::default().or(()).and(Match::new(f).unwrap()))
-        } else {
-            file_system.set_filter(Directory::default().or(()))
+    if matches.get_flag("root") {
+        let root = match config.root_markers.as_deref() {
+            Some(markers) => find_root(Path::new(&path), markers),
+            None => find_root(Path::new(&path), DEFAULT_MARKERS),
+        };
+        match root {
+            Some(root) => path = root.display().to_string(),
+            None => {
+                eprintln!("no project root found above '{path}'");
+                std::process::exit(1);
+            }
         }
-    } else if let Some(f) = matches.get_one::<String>("filter") {
-        file_system.set_filter(Match::new(f).unwrap())
     }
 
-    if matches.get_flag("last-modified") {
-        file_system.set_sorter(DateTime(Directory::default()));
+    let color_mode = *matches.get_one::<ColorMode>("color").unwrap();
+    let mut stdout: Box<dyn Write> = match color_mode.enabled() {
+        true => Box::new(std::io::stdout()),
+        false => Box::new(StripAnsi::new(std::io::stdout())),
+    };
+
+    // There is no single root on windows so `/` lists the drives instead
+    if matches.get_flag("drives") || (cfg!(windows) && matches!(path.as_str(), "/" | "\\")) {
+        print_drives(&mut stdout, &drives().unwrap()).unwrap();
+        return;
+    }
+    let mut listing = Listing::builder()
+        .path(&path)
+        .dereference(matches.get_flag("dereference"))
+        .hidden_dirs(matches.get_flag("hidden-dirs"))
+        .strict(matches.get_flag("strict"));
+    if let Some(level) = matches.get_one::<usize>("level") {
+        listing = listing.recursive(*level);
+    }
+
+    // Trees keep walking directories that don't match and only show the
+    // branches that lead to a match
+    let pattern = matches.get_one::<String>("filter");
+    let pattern = match matches.get_flag("tree") {
+        true => {
+            if let Some(f) = pattern {
+                listing = listing.matching(Match::new(f).unwrap());
+            }
+            None
+        }
+        false => pattern,
+    };
+
+    // Filters are kept apart by the flag that added them so `--debug-entry`
+    // can report each verdict
+    let mut filters: Vec<(&str, Arc<dyn Filter>)> = vec![if matches.get_flag("all") {
+        match pattern {
+            Some(f) => (
+                "--all --filter",
+                Arc::new(Directory::default().or(()).and(Match::new(f).unwrap())),
+            ),
+            None => ("--all", Arc::new(Directory::default().or(()))),
+        }
+    } else if let Some(f) = pattern {
+        ("--filter", Arc::new(Match::new(f).unwrap()))
+    } else {
+        ("hidden", Arc::new(Not::<Hidden>::default()))
+    }];
+
+    if let Some(pattern) = matches.get_one::<String>("grep") {
+        filters.push(("--grep", Arc::new(Contains::new(pattern).unwrap())));
+    }
+    if matches.get_flag("tracked") {
+        filters.push(("--tracked", Arc::new(GitTracked::default())));
+    }
+    if matches.get_flag("modified") {
+        filters.push(("--modified", Arc::new(GitModified::default())));
+    }
+    if matches.get_flag("untracked") {
+        filters.push(("--untracked", Arc::new(GitUntracked::default())));
+    }
+    if matches.get_flag("system") {
+        filters.push(("--system", Arc::new(System)));
+    }
+    if matches.get_flag("readonly") {
+        filters.push(("--readonly", Arc::new(ReadOnly)));
+    }
+    if matches.get_flag("archive") {
+        filters.push(("--archive", Arc::new(Archive)));
+    }
+    let code_owners = match matches.get_flag("codeowners") || matches.contains_id("owned-by") {
+        true => match CodeOwners::find(Path::new(&path)) {
+            Ok(Some(code_owners)) => Some(code_owners),
+            Ok(None) => {
+                eprintln!("no CODEOWNERS file found above '{path}'");
+                std::process::exit(1);
+            }
+            Err(err) => {
+                eprintln!("failed to load CODEOWNERS: {err}");
+                std::process::exit(1);
+            }
+        },
+        false => None,
+    };
+    if let (Some(owner), Some(code_owners)) =
+        (matches.get_one::<String>("owned-by"), code_owners.as_ref())
+    {
+        filters.push((
+            "--owned-by",
+            Arc::new(OwnedBy::new(code_owners.clone(), owner)),
+        ));
+    }
+    if matches.get_flag("codeowners") {
+        listing = listing.code_owners(code_owners);
+    }
+    listing = listing.filter(
+        filters
+            .iter()
+            .map(|(_, filter)| Box::new(filter.clone()) as Box<dyn Filter>)
+            .collect::<All>(),
+    );
+
+    match Config::load_local(Path::new(&path)) {
+        Ok(Some(local)) => config = config.merge(local),
+        Ok(None) => {}
+        Err(err) => eprintln!("failed to load config: {err}"),
     }
+    let profile_theme = match matches.get_flag("no-profiles") {
+        true => Vec::new(),
+        false => config.profile_theme(Path::new(&path)),
+    };
 
+    let ignore_case = matches.get_flag("ignore-case");
+    let mut sorter: Box<dyn SortStrategy> = if let Some(keys) = matches.get_one::<String>("sort") {
+        Box::new(keys.parse::<Chain>().unwrap().ignore_case(ignore_case))
+    } else if matches.get_flag("by-extension") && ignore_case {
+        Box::new(Directory(Extension(IgnoreCase(Natural))))
+    } else if matches.get_flag("by-extension") {
+        Box::new(Directory(Extension::<Natural>::default()))
+    } else if let Some(order) = config.sort {
+        Box::new(order.ignore_case(ignore_case))
+    } else if ignore_case {
+        Box::new(Directory(IgnoreCase(Natural)))
+    } else {
+        Box::new(Directory::default())
+    };
     if matches.get_flag("reverse") {
-        file_system.set_sorter(Reverse(Directory(Reverse(Natural))));
-    }
-
-    if matches.get_flag("by-size") {
-        file_system.set_sorter(Size(Directory::default()));
-    }
-
-    let colorizer = Colorizer::default()
-        .group("DIR", [GroupMatch::Directory], Style::default().blue())
-        .group(
-            "HIDDEN",
-            [
-                GroupMatch::Hidden,
-                GroupMatch::starts_with("."),
-                GroupMatch::extensions(["lock"]),
-            ],
-            Style::default().fg::<Gray>(),
-        )
-        .group(
-            "IMAGE",
-            [GroupMatch::extensions([
-                "jpg", "png", "gif", "webp", "avif", "ico",
-            ])],
-            Style::default().magenta(),
-        )
-        .group(
-            "CONFIG",
-            [GroupMatch::filenames(["Cargo.toml", "config.toml"])],
-            Style::default().yellow().underline(),
-        )
-        .group(
-            "EXE",
-            [
-                GroupMatch::Executable,
-                GroupMatch::extensions(["exe", "sh"]),
-            ],
-            Style::default().green(),
-        );
+        sorter = Box::new(Reverse(sorter));
+    }
 
-    if matches.get_flag("recursive") {
-        xf::format::Tree::new(file_system, matches.get_flag("long"))
-            .print(colorizer)
-            .unwrap();
-    } else if matches.get_flag("long") {
-        xf::format::List::new(file_system).print(colorizer).unwrap();
+    // Weights would reintroduce sorting when the OS order was requested
+    if config.weights.is_empty() || sorter.preserves_order() {
+        listing = listing.sort(sorter);
+    } else {
+        listing = listing.sort(Weighted::new(sorter, config.weights).unwrap());
+    }
+
+    if matches.get_flag("hash") {
+        listing = listing.hash(if matches.get_flag("no-cache") {
+            HashCache::in_memory()
+        } else {
+            HashCache::load().unwrap_or_else(|err| {
+                eprintln!("failed to load hash cache: {err}");
+                HashCache::in_memory()
+            })
+        });
+    }
+
+    let format = if matches.get_flag("json") {
+        Format::Json
+    } else if matches.get_flag("html") {
+        Format::Html
+    } else if matches.get_flag("tree") || matches.get_flag("flat-indent") {
+        Format::Tree
+    } else if matches.get_flag("print0") {
+        Format::Print0
+    } else if matches.get_flag("one") {
+        Format::Names
+    } else if matches.get_flag("commas") {
+        Format::Commas
+    } else if matches.contains_id("columns")
+        || matches.get_flag("blocks")
+        || matches.get_flag("created")
+        || matches.get_flag("accessed")
+        || matches.get_flag("codeowners")
+        || (matches.get_flag("long") && !matches.get_flag("grid"))
+    {
+        Format::List
     } else {
-        xf::format::Grid::new(file_system).print(colorizer).unwrap();
+        Format::Grid
+    };
+
+    let mut columns = matches.get_one::<Vec<Column>>("columns").cloned();
+    if matches.get_flag("blocks") {
+        let mut with_blocks = match columns {
+            Some(columns) => columns,
+            None if matches.get_flag("long") => Column::DEFAULT.to_vec(),
+            None => vec![Column::Name],
+        };
+        if !with_blocks.contains(&Column::Blocks) {
+            with_blocks.insert(0, Column::Blocks);
+        }
+        columns = Some(with_blocks);
+    }
+    // Extra timestamps go after the modified date, or before the name without one
+    for (flag, column) in [("accessed", Column::Accessed), ("created", Column::Created)] {
+        if !matches.get_flag(flag) {
+            continue;
+        }
+        let mut with_date = columns.unwrap_or(Column::DEFAULT.to_vec());
+        if !with_date.contains(&column) {
+            let at = match with_date.iter().position(|c| *c == Column::Date) {
+                Some(date) => date + 1,
+                None => with_date
+                    .iter()
+                    .position(|c| *c == Column::Name)
+                    .unwrap_or(with_date.len()),
+            };
+            with_date.insert(at, column);
+        }
+        columns = Some(with_date);
+    }
+    if matches.get_flag("no-owner") {
+        let mut without = columns.unwrap_or(Column::DEFAULT.to_vec());
+        without.retain(|column| !matches!(column, Column::Owner | Column::Group));
+        columns = Some(without);
+    }
+    if let Some(columns) = columns {
+        listing = listing.columns(columns);
+    }
+
+    #[cfg(feature = "media")]
+    {
+        listing = listing.media(matches.get_flag("media"));
+    }
+
+    let mut listing = listing.format(format);
+    if let Some(template) = matches.get_one::<String>("format") {
+        listing = listing.template(template);
+    }
+
+    let dir_counts = matches
+        .get_one::<usize>("dir-counts")
+        .map(|huge| ThemeGroup::child_counts(*huge))
+        .unwrap_or_default();
+    let mut theme = Colorizer::themed(
+        config
+            .theme
+            .into_iter()
+            .chain(profile_theme)
+            .chain(dir_counts),
+    )
+    .color_mode(color_mode)
+    .slash(matches.get_flag("slash"))
+    .indicators(matches.get_flag("classify"))
+    .dir_sizes(matches.get_flag("du"))
+    .icons(matches.get_flag("icons").then(Icons::nerd_font))
+    .hyperlinks(matches.get_flag("hyperlink"))
+    .accessible(matches.get_flag("accessible"))
+    .known_folders(Some(
+        KnownFolders::resolve().show_labels(matches.get_flag("folder-labels")),
+    ))
+    .symlink_size(
+        matches
+            .get_one::<SymlinkSize>("symlink-size")
+            .copied()
+            .or(config.symlink_size)
+            .unwrap_or_default(),
+    )
+    .size_units(match matches.get_flag("si") {
+        true => SizeUnits::Si,
+        false => SizeUnits::Binary,
+    })
+    .time_style(
+        matches
+            .get_one::<TimeStyle>("time-style")
+            .cloned()
+            .unwrap_or_default(),
+    )
+    .path_display(
+        matches
+            .get_one::<PathDisplay>("paths")
+            .copied()
+            .unwrap_or_default(),
+        &path,
+    );
+    if let Some(marker) = config.symlink_marker {
+        theme = theme.symlink_marker(marker);
+    }
+
+    if let Some(entry) = matches.get_one::<String>("debug-entry") {
+        let filters = filters
+            .iter()
+            .map(|(name, filter)| (*name, filter.as_ref()))
+            .collect::<Vec<_>>();
+        if let Err(err) = print_entry(
+            &mut stdout,
+            Path::new(entry),
+            matches.get_flag("dereference"),
+            &theme,
+            &filters,
+        ) {
+            eprintln!("failed to inspect '{entry}': {err}");
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    let listing = listing
+        .fill(match matches.get_flag("down") {
+            true => Fill::Down,
+            false => Fill::Across,
+        })
+        .long(matches.get_flag("long"))
+        .lines(matches.get_flag("lines"))
+        .exec(matches.get_one::<String>("exec-column").map(|command| {
+            let exec = ExecColumn::new(command);
+            match matches.get_one::<Duration>("exec-timeout") {
+                Some(timeout) => exec.timeout(*timeout),
+                None => exec,
+            }
+        }))
+        .header(matches.get_flag("header"))
+        .flat_indent(matches.get_flag("flat-indent"))
+        .memory_limit(matches.get_one::<usize>("memory-limit").copied())
+        .branches(
+            matches
+                .get_one::<Branches>("branches")
+                .cloned()
+                .or(config.branches)
+                .unwrap_or_default(),
+        )
+        .compact_dirs(matches.get_flag("compact-dirs"))
+        .summaries(matches.get_flag("summaries"))
+        .sections(matches.get_flag("recursive"))
+        .nested(matches.get_flag("tree"))
+        .diagnostics(matches.get_flag("diagnostics"))
+        .flush_every(*matches.get_one::<usize>("flush-every").unwrap())
+        .disk_usage(matches.get_flag("disk-usage"))
+        .timings(matches.get_flag("timings"))
+        .directory(matches.get_flag("directory"))
+        .theme(theme);
+    let result = match path.as_str() {
+        #[cfg(feature = "sftp")]
+        url if url.starts_with("sftp://") => list_remote(listing, url, matches.get_flag("all")),
+        #[cfg(feature = "object-store")]
+        url if xf::store::is_store_url(url) => list_bucket(
+            listing,
+            url,
+            match matches.get_flag("tree") {
+                true => matches.get_one::<usize>("level").copied(),
+                false => Some(1),
+            },
+            matches.get_flag("all"),
+        ),
+        _ => listing.run(),
+    };
+    if let Err(err) = result {
+        // Output piped into something like `head` that stopped reading early
+        if err
+            .downcast_ref::<std::io::Error>()
+            .is_some_and(|err| err.kind() == std::io::ErrorKind::BrokenPipe)
+        {
+            return;
+        }
+        eprintln!("xf: {err}");
+        std::process::exit(1);
+    }
+}
+
+/// List a remote directory with the layout and theme of the listing
+///
+/// Dotfiles are the only entries that are filtered out, remote entries are
+/// sorted by name.
+#[cfg(feature = "sftp")]
+fn list_remote(
+    listing: xf::listing::ListingBuilder,
+    url: &str,
+    all: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut entries = xf::sftp::list(&url.parse()?)?;
+    entries.retain(|entry| all || !entry.name.starts_with('.'));
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    let items = entries
+        .iter()
+        .map(|entry| entry as &dyn xf::listable::Listable)
+        .collect::<Vec<_>>();
+    // The local file system is only there for the listing options
+    listing
+        .path(".")
+        .build()
+        .render_items(&mut std::io::stdout().lock(), &items)
+}
+
+/// List a prefix of a cloud bucket with the layout and theme of the listing,
+/// prefixes are read `depth` levels deep
+///
+/// Dotfiles are the only objects that are filtered out.
+#[cfg(feature = "object-store")]
+fn list_bucket(
+    listing: xf::listing::ListingBuilder,
+    url: &str,
+    depth: Option<usize>,
+    all: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    fn hide(entries: &mut Vec<xf::store::StoreEntry>) {
+        entries.retain(|entry| !entry.name.starts_with('.'));
+        for entry in entries {
+            hide(&mut entry.children);
+        }
+    }
+
+    let mut entries = xf::store::list(url, depth)?;
+    if !all {
+        hide(&mut entries);
+    }
+    let items = entries
+        .iter()
+        .map(|entry| entry as &dyn xf::listable::Listable)
+        .collect::<Vec<_>>();
+    listing
+        .path(".")
+        .build()
+        .render_items(&mut std::io::stdout().lock(), &items)
+}
+
+fn snapshot(matches: &clap::ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    match matches.subcommand() {
+        Some(("save", matches)) => {
+            let name = matches.get_one::<String>("name").unwrap();
+            let path = matches.get_one::<String>("path").unwrap();
+            let snapshot = Snapshot::capture(&FileSystem::from(path))?;
+            let file = snapshot.save(name)?;
+            println!(
+                "saved {} entries to {}",
+                snapshot.records.len(),
+                file.display()
+            );
+        }
+        Some(("diff", matches)) => {
+            let name = matches.get_one::<String>("name").unwrap();
+            let saved = Snapshot::load(name)?;
+            let current = match matches.get_one::<String>("path") {
+                Some(path) => Snapshot::capture(&FileSystem::from(path))?,
+                None => Snapshot::capture(&FileSystem::from(&saved.root))?,
+            };
+            print_diff(&mut std::io::stdout(), &saved.diff(&current))?;
+        }
+        _ => unreachable!(),
     }
+    Ok(())
 }
@@ -0,0 +1,296 @@
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::Path,
+    time::Duration,
+};
+
+/// Dimensions and/or duration of a media file
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MediaInfo {
+    /// Width and height in pixels
+    pub dimensions: Option<(u32, u32)>,
+    pub duration: Option<Duration>,
+}
+
+impl std::fmt::Display for MediaInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.dimensions, self.duration) {
+            (Some((width, height)), _) => write!(f, "{width}x{height}"),
+            (None, Some(duration)) => {
+                let seconds = duration.as_secs();
+                if seconds >= 3600 {
+                    write!(
+                        f,
+                        "{}:{:02}:{:02}",
+                        seconds / 3600,
+                        (seconds % 3600) / 60,
+                        seconds % 60
+                    )
+                } else {
+                    write!(f, "{}:{:02}", seconds / 60, seconds % 60)
+                }
+            }
+            (None, None) => write!(f, "-"),
+        }
+    }
+}
+
+/// Read the dimensions or duration of a media file from its header
+///
+/// Supports PNG, GIF, BMP, JPEG, and WebP images along with WAV, FLAC, and
+/// MP4/MOV/M4A durations. Returns `None` for anything else.
+pub fn probe<P: AsRef<Path>>(path: P) -> Option<MediaInfo> {
+    let mut file = File::open(path).ok()?;
+    let mut header = [0u8; 32];
+    let read = file.read(&mut header).ok()?;
+    let header = &header[..read];
+
+    if header.starts_with(b"\x89PNG\r\n\x1a\n") && read >= 24 {
+        return Some(image(be32(&header[16..]), be32(&header[20..])));
+    }
+    if (header.starts_with(b"GIF87a") || header.starts_with(b"GIF89a")) && read >= 10 {
+        return Some(image(le16(&header[6..]) as u32, le16(&header[8..]) as u32));
+    }
+    if header.starts_with(b"BM") && read >= 26 {
+        return Some(image(
+            le32(&header[18..]),
+            (le32(&header[22..]) as i32).unsigned_abs(),
+        ));
+    }
+    if header.starts_with(b"\xff\xd8") {
+        return jpeg(&mut file).ok().flatten();
+    }
+    if header.starts_with(b"RIFF") && read >= 16 && &header[8..12] == b"WEBP" {
+        return webp(header);
+    }
+    if header.starts_with(b"RIFF") && read >= 12 && &header[8..12] == b"WAVE" {
+        return wav(&mut file).ok().flatten();
+    }
+    if header.starts_with(b"fLaC") && read >= 26 {
+        return flac(header);
+    }
+    if read >= 8
+        && matches!(
+            &header[4..8],
+            b"ftyp" | b"moov" | b"mdat" | b"free" | b"wide"
+        )
+    {
+        return mp4(&mut file).ok().flatten();
+    }
+
+    None
+}
+
+fn image(width: u32, height: u32) -> MediaInfo {
+    MediaInfo {
+        dimensions: Some((width, height)),
+        duration: None,
+    }
+}
+
+fn be16(bytes: &[u8]) -> u16 {
+    u16::from_be_bytes([bytes[0], bytes[1]])
+}
+
+fn be32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+fn be64(bytes: &[u8]) -> u64 {
+    u64::from_be_bytes(bytes[..8].try_into().unwrap())
+}
+
+fn le16(bytes: &[u8]) -> u16 {
+    u16::from_le_bytes([bytes[0], bytes[1]])
+}
+
+fn le32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+/// Walk the JPEG markers until a start of frame segment is found
+fn jpeg(file: &mut File) -> io::Result<Option<MediaInfo>> {
+    file.seek(SeekFrom::Start(2))?;
+    let mut marker = [0u8; 4];
+    loop {
+        file.read_exact(&mut marker)?;
+        if marker[0] != 0xff {
+            return Ok(None);
+        }
+        let length = be16(&marker[2..]) as i64;
+        match marker[1] {
+            // SOF0-SOF15 excluding DHT, JPG, and DAC
+            0xc0..=0xcf if !matches!(marker[1], 0xc4 | 0xc8 | 0xcc) => {
+                let mut frame = [0u8; 5];
+                file.read_exact(&mut frame)?;
+                return Ok(Some(image(
+                    be16(&frame[3..]) as u32,
+                    be16(&frame[1..]) as u32,
+                )));
+            }
+            // Start of scan, image data follows without a size
+            0xda => return Ok(None),
+            _ => {
+                file.seek(SeekFrom::Current(length - 2))?;
+            }
+        }
+    }
+}
+
+/// Dimensions from the first chunk, each variant keeps them at a different
+/// offset
+fn webp(header: &[u8]) -> Option<MediaInfo> {
+    match &header[12..16] {
+        b"VP8 " if header.len() >= 30 => Some(image(
+            (le16(&header[26..]) & 0x3fff) as u32,
+            (le16(&header[28..]) & 0x3fff) as u32,
+        )),
+        b"VP8L" if header.len() >= 25 => {
+            let bits = le32(&header[21..]);
+            Some(image((bits & 0x3fff) + 1, ((bits >> 14) & 0x3fff) + 1))
+        }
+        b"VP8X" if header.len() >= 31 => Some(image(
+            (le32(&header[24..]) & 0xffffff) + 1,
+            (le32(&header[27..]) & 0xffffff) + 1,
+        )),
+        _ => None,
+    }
+}
+
+/// Duration from the byte rate in the `fmt ` chunk and the size of the `data`
+/// chunk
+fn wav(file: &mut File) -> io::Result<Option<MediaInfo>> {
+    file.seek(SeekFrom::Start(12))?;
+    let mut byte_rate = None;
+    let mut chunk = [0u8; 8];
+    while file.read_exact(&mut chunk).is_ok() {
+        let size = le32(&chunk[4..]) as i64;
+        match &chunk[..4] {
+            b"fmt " => {
+                let mut format = [0u8; 12];
+                file.read_exact(&mut format)?;
+                byte_rate = Some(le32(&format[8..]));
+                file.seek(SeekFrom::Current(size - 12 + (size & 1)))?;
+            }
+            b"data" => {
+                return Ok(byte_rate.filter(|rate| *rate > 0).map(|rate| MediaInfo {
+                    dimensions: None,
+                    duration: Some(Duration::from_secs_f64(size as f64 / rate as f64)),
+                }));
+            }
+            _ => {
+                file.seek(SeekFrom::Current(size + (size & 1)))?;
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Duration from the sample rate and total samples in `STREAMINFO`
+fn flac(header: &[u8]) -> Option<MediaInfo> {
+    // fLaC + block header (4) puts STREAMINFO at offset 8
+    let info = &header[8..];
+    let sample_rate = (be32(&info[10..]) >> 12) as u64;
+    let samples = ((info[13] as u64 & 0x0f) << 32) | be32(&info[14..]) as u64;
+    (sample_rate > 0).then(|| MediaInfo {
+        dimensions: None,
+        duration: Some(Duration::from_secs_f64(samples as f64 / sample_rate as f64)),
+    })
+}
+
+/// Duration from the `mvhd` box inside of the top level `moov` box
+fn mp4(file: &mut File) -> io::Result<Option<MediaInfo>> {
+    let length = file.metadata()?.len();
+    let mut offset = 0u64;
+    let mut header = [0u8; 8];
+
+    let mut end = length;
+    // Sizes come from the file so a corrupt one can't be trusted to fit
+    while offset
+        .checked_add(8)
+        .is_some_and(|header_end| header_end <= end)
+    {
+        file.seek(SeekFrom::Start(offset))?;
+        file.read_exact(&mut header)?;
+        let mut size = be32(&header) as u64;
+        let mut body = offset + 8;
+        if size == 1 {
+            let mut large = [0u8; 8];
+            file.read_exact(&mut large)?;
+            size = be64(&large);
+            body += 8;
+        } else if size == 0 {
+            size = end - offset;
+        }
+        let Some(next) = offset.checked_add(size).filter(|_| size >= 8) else {
+            return Ok(None);
+        };
+
+        match &header[4..] {
+            // Descend into the movie box
+            b"moov" => {
+                end = next;
+                offset = body;
+            }
+            b"mvhd" => {
+                let mut mvhd = [0u8; 32];
+                file.read_exact(&mut mvhd)?;
+                let (timescale, duration) = if mvhd[0] == 1 {
+                    (be32(&mvhd[20..]) as u64, be64(&mvhd[24..]))
+                } else {
+                    (be32(&mvhd[12..]) as u64, be32(&mvhd[16..]) as u64)
+                };
+                return Ok((timescale > 0).then(|| MediaInfo {
+                    dimensions: None,
+                    duration: Some(Duration::from_secs_f64(duration as f64 / timescale as f64)),
+                }));
+            }
+            _ => offset = next,
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn headers() {
+        let base = TempDir::new("media");
+        let probe_bytes = |name: &str, bytes: &[u8]| {
+            std::fs::write(base.join(name), bytes).unwrap();
+            probe(base.join(name))
+        };
+
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        png.extend(640u32.to_be_bytes());
+        png.extend(480u32.to_be_bytes());
+        assert_eq!(probe_bytes("a.png", &png), Some(image(640, 480)));
+
+        let mut webp = b"RIFF\0\0\0\0WEBPVP8X".to_vec();
+        webp.resize(24, 0);
+        webp.extend([99, 0, 0, 49, 0, 0, 0]);
+        assert_eq!(webp.len(), 31);
+        assert_eq!(probe_bytes("a.webp", &webp), Some(image(100, 50)));
+
+        // Truncated headers are unknown rather than out of bounds reads
+        assert_eq!(probe_bytes("short.webp", &webp[..30]), None);
+        assert_eq!(probe_bytes("tiny.webp", &webp[..16]), None);
+        assert_eq!(probe_bytes("short.png", &png[..20]), None);
+        assert_eq!(probe_bytes("empty", b""), None);
+
+        // 64 bit box sizes that would overflow the offset
+        let mut mp4 = b"\0\0\0\x10ftypisom\0\0\0\0".to_vec();
+        mp4.extend(b"\0\0\0\x01free");
+        mp4.extend(u64::MAX.to_be_bytes());
+        mp4.extend(b"\0\0\0\x01moov");
+        mp4.extend(u64::MAX.to_be_bytes());
+        assert_eq!(probe_bytes("huge.mp4", &mp4), None);
+        let moov = mp4.split_off(16);
+        mp4.extend(&moov[16..]);
+        assert_eq!(probe_bytes("huge-moov.mp4", &mp4), None);
+    }
+}
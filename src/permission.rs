@@ -1,4 +1,4 @@
-use std::path::Path;
+use std::{path::Path, sync::OnceLock};
 
 use crate::style::ModeChar;
 
@@ -46,7 +46,10 @@ impl From<&Path> for Attributes {
         };
 
         #[cfg(any(target_os = "linux", target_os = "macos"))]
-        return Self::default();
+        return {
+            let _ = value;
+            Self::default()
+        };
     }
 }
 
@@ -78,6 +81,73 @@ impl Perms {
     pub fn attributes(&self) -> &Attributes {
         &self.attributes
     }
+
+    /// Unix style `rwxrwxrwx` mode bits of the user, group, and everyone
+    pub fn mode(&self) -> u16 {
+        ((self.user.permissions.bits() as u16) << 6)
+            | ((self.group.permissions.bits() as u16) << 3)
+            | self.everyone.permissions.bits() as u16
+    }
+}
+
+/// Name of the user running the process and the groups they belong to
+///
+/// Resolved once and reused for the rest of the process.
+pub fn current_user() -> &'static (String, Vec<String>) {
+    static CURRENT: OnceLock<(String, Vec<String>)> = OnceLock::new();
+    CURRENT.get_or_init(|| {
+        #[cfg(any(target_os = "linux", target_os = "macos"))]
+        {
+            let name = users::get_current_username()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let groups = users::get_user_groups(&name, users::get_current_gid())
+                .unwrap_or_default()
+                .iter()
+                .map(|group| group.name().to_string_lossy().to_string())
+                .collect();
+            (name, groups)
+        }
+
+        #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+        {
+            (std::env::var("USERNAME").unwrap_or_default(), Vec::new())
+        }
+    })
+}
+
+/// Parse a mode mask from a symbolic spec, `o+w` or `ug+x`, or octal digits,
+/// `002`
+pub fn parse_mode_mask(spec: &str) -> Result<u16, String> {
+    if let Some((who, what)) = spec.split_once('+') {
+        let mut rights = 0u16;
+        for c in what.chars() {
+            rights |= match c {
+                'r' => 0o4,
+                'w' => 0o2,
+                'x' => 0o1,
+                other => return Err(format!("invalid permission '{other}' in '{spec}'")),
+            };
+        }
+
+        let who = if who.is_empty() { "a" } else { who };
+        let mut mask = 0u16;
+        for c in who.chars() {
+            mask |= match c {
+                'u' => rights << 6,
+                'g' => rights << 3,
+                'o' => rights,
+                'a' => (rights << 6) | (rights << 3) | rights,
+                other => return Err(format!("invalid class '{other}' in '{spec}'")),
+            };
+        }
+        Ok(mask)
+    } else {
+        u16::from_str_radix(spec, 8)
+            .ok()
+            .filter(|mask| *mask <= 0o777)
+            .ok_or_else(|| format!("invalid mode mask '{spec}'"))
+    }
 }
 
 impl std::fmt::Display for Perms {
@@ -96,7 +166,8 @@ impl TryFrom<&Path> for Perms {
         #[cfg(any(target_os = "linux", target_os = "macos"))]
         {
             use std::os::unix::fs::{MetadataExt, PermissionsExt};
-            let meta = value.metadata().unwrap();
+            // Broken links fall back to the permissions of the link itself
+            let meta = value.metadata().or_else(|_| value.symlink_metadata())?;
             let permissions = meta.permissions();
             let st_mode = permissions.mode();
 
@@ -0,0 +1,38 @@
+use std::path::{Path, PathBuf};
+
+/// Files and directories that mark the root of a project, in priority order
+pub const DEFAULT_MARKERS: &[&str] = &[".git", "Cargo.toml", "package.json"];
+
+/// Root of the project enclosing `path`
+///
+/// Markers are tried in order and the nearest ancestor containing the first
+/// marker found anywhere above `path` wins, so a repository root is preferred
+/// over the package of a monorepo that `path` is in.
+pub fn find_root<S: AsRef<str>>(path: &Path, markers: &[S]) -> Option<PathBuf> {
+    let path = std::path::absolute(path).ok()?;
+    markers.iter().find_map(|marker| {
+        path.ancestors()
+            .find(|dir| dir.join(marker.as_ref()).exists())
+            .map(Path::to_path_buf)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn nearest_root_by_marker_priority() {
+        let base = TempDir::new("root");
+        let package = base.join("packages").join("app");
+        std::fs::create_dir_all(package.join("src")).unwrap();
+        std::fs::create_dir_all(base.join(".git")).unwrap();
+        std::fs::write(package.join("package.json"), "{}").unwrap();
+
+        let src = package.join("src");
+        assert_eq!(find_root(&src, DEFAULT_MARKERS), Some(base.to_path_buf()));
+        assert_eq!(find_root(&src, &["package.json"]), Some(package.clone()));
+        assert_eq!(find_root(&src, &["xf.marker"]), None);
+    }
+}
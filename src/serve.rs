@@ -0,0 +1,266 @@
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    path::{Component, Path, PathBuf},
+};
+
+use hashbrown::HashMap;
+
+use crate::{
+    filter::{Binary, Contains, Filter, Match, Not},
+    format::{encode, escape, Json},
+    style::humansize,
+    Directory, Entry, FileSystem, Hidden,
+};
+
+/// Serve a read-only file browser for `root` over HTTP
+///
+/// Directories are rendered as an HTML page, or as the [`Json`] formatter's
+/// output with `?format=json`. The query parameters `filter`, `grep`, and
+/// `all` map to the command line flags of the same names. Files are sent as is.
+/// Paths outside of `root` are rejected.
+pub fn serve<P: AsRef<Path>, A: ToSocketAddrs>(root: P, addr: A) -> io::Result<()> {
+    let root = dunce::canonicalize(root)?;
+    let listener = TcpListener::bind(addr)?;
+    eprintln!(
+        "serving {} on http://{}",
+        root.display(),
+        listener.local_addr()?
+    );
+
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let root = root.clone();
+        std::thread::spawn(move || {
+            if let Err(err) = handle(&root, stream) {
+                eprintln!("request failed: {err}");
+            }
+        });
+    }
+    Ok(())
+}
+
+/// A parsed request line
+struct Request {
+    path: String,
+    query: HashMap<String, String>,
+}
+
+impl Request {
+    fn read(stream: &TcpStream) -> io::Result<Option<Self>> {
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+
+        // Drain the headers, nothing in them is used
+        let mut header = String::new();
+        while reader.read_line(&mut header)? > 2 {
+            header.clear();
+        }
+
+        let mut parts = line.split_whitespace();
+        let (Some("GET"), Some(target)) = (parts.next(), parts.next()) else {
+            return Ok(None);
+        };
+
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        Ok(Some(Self {
+            path: decode(path),
+            query: query
+                .split('&')
+                .filter(|pair| !pair.is_empty())
+                .map(|pair| {
+                    let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                    (decode(key), decode(value))
+                })
+                .collect(),
+        }))
+    }
+
+    fn filter(&self) -> Result<Box<dyn Filter>, regex::Error> {
+        let all = self.query.contains_key("all");
+        let mut filter: Box<dyn Filter> = match (all, self.query.get("filter")) {
+            (true, Some(f)) => Box::new(Directory::default().or(()).and(Match::new(f)?)),
+            (true, None) => Box::new(()),
+            (false, Some(f)) => Box::new(Match::new(f)?),
+            (false, None) => Box::new(Not::<Hidden>::default()),
+        };
+        if let Some(pattern) = self.query.get("grep") {
+            filter = Box::new(filter.and(Contains::new(pattern)?));
+        }
+        Ok(filter)
+    }
+}
+
+fn handle(root: &Path, mut stream: TcpStream) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(request) = Request::read(&stream)? else {
+        return respond(
+            &mut stream,
+            "405 Method Not Allowed",
+            "text/plain",
+            b"GET only",
+        );
+    };
+
+    let Some(path) = resolve(root, &request.path) else {
+        return respond(&mut stream, "404 Not Found", "text/plain", b"not found");
+    };
+
+    if path.is_file() {
+        let content = std::fs::read(&path)?;
+        let mime = if content.contains(&0) {
+            "application/octet-stream"
+        } else {
+            "text/plain; charset=utf-8"
+        };
+        return respond(&mut stream, "200 OK", mime, &content);
+    }
+
+    let filter = match request.filter() {
+        Ok(filter) => filter,
+        Err(err) => {
+            return respond(
+                &mut stream,
+                "400 Bad Request",
+                "text/plain",
+                err.to_string().as_bytes(),
+            )
+        }
+    };
+    let file_system = FileSystem::from(&path)
+        .with_filter(filter)
+        .with_sorter(Directory::default());
+
+    if request.query.get("format").map(String::as_str) == Some("json") {
+        let mut body = Vec::new();
+        Json::new(file_system).write(&mut body)?;
+        respond(&mut stream, "200 OK", "application/json", &body)
+    } else {
+        let body = page(root, &request, &file_system.entries()?);
+        respond(
+            &mut stream,
+            "200 OK",
+            "text/html; charset=utf-8",
+            body.as_bytes(),
+        )
+    }
+}
+
+fn respond(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &[u8],
+) -> Result<(), Box<dyn std::error::Error>> {
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    )?;
+    stream.write_all(body)?;
+    Ok(())
+}
+
+/// Map the request path onto `root`, rejecting anything that escapes it
+fn resolve(root: &Path, path: &str) -> Option<PathBuf> {
+    let relative = Path::new(path.trim_start_matches('/'));
+    if relative
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_)))
+    {
+        return None;
+    }
+
+    let path = dunce::canonicalize(root.join(relative)).ok()?;
+    path.starts_with(root).then_some(path)
+}
+
+/// Percent decode a url component, `+` is treated as a space
+fn decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or_default();
+                match u8::from_str_radix(hex, 16) {
+                    Ok(byte) => {
+                        decoded.push(byte);
+                        i += 2;
+                    }
+                    Err(_) => decoded.push(b'%'),
+                }
+            }
+            b'+' => decoded.push(b' '),
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn page(root: &Path, request: &Request, entries: &[Entry]) -> String {
+    let base = request.path.trim_end_matches('/');
+    // The path was decoded so each segment is encoded again for the links
+    let href_base = base.split('/').map(encode).collect::<Vec<_>>().join("/");
+    let mut rows = String::new();
+    if !base.is_empty() {
+        rows.push_str("<tr><td><a href=\"..\">..</a></td><td></td></tr>\n");
+    }
+    for entry in entries {
+        let name = entry.file_name();
+        let slash = if entry.is_dir() { "/" } else { "" };
+        let size = if entry.is_dir() {
+            String::new()
+        } else {
+            humansize(entry.metadata().len())
+        };
+        rows.push_str(&format!(
+            "<tr><td><a href=\"{href_base}/{href}{slash}\">{name}{slash}</a></td><td>{size}</td></tr>\n",
+            href = encode(name),
+            name = escape(name),
+        ));
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"><title>{title}</title></head>\n<body>\n<h1>{title}</h1>\n<table>\n{rows}</table>\n</body>\n</html>\n",
+        title = escape(&format!(
+            "{}{}",
+            root.file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            if base.is_empty() { "/" } else { base }
+        )),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn hostile_paths() {
+        let base = TempDir::new("serve");
+        let dir = base.join("\"><img src=x onerror=alert(1)>");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a b#?%.txt"), "").unwrap();
+
+        let request = Request {
+            path: "/\"><img src=x onerror=alert(1)>/".to_string(),
+            query: HashMap::new(),
+        };
+        let entries = FileSystem::from(&dir).entries().unwrap();
+        let html = page(base.path(), &request, &entries);
+
+        assert!(!html.contains("<img"));
+        assert!(html.contains(
+            "href=\"/%22%3E%3Cimg%20src%3Dx%20onerror%3Dalert%281%29%3E/a%20b%23%3F%25.txt\""
+        ));
+        assert!(html.contains(">a b#?%.txt</a>"));
+        assert!(html.contains("&quot;&gt;&lt;img src=x onerror=alert(1)&gt;"));
+    }
+}
@@ -0,0 +1,371 @@
+use std::{
+    borrow::Cow,
+    io::{self, Write},
+    process::{Command, Stdio},
+    str::FromStr,
+    time::SystemTime,
+};
+
+use chrono::{Datelike, Local, NaiveDate, NaiveTime, TimeZone};
+
+use crate::listable::{Kind, Listable};
+
+/// Location of a remote directory, `sftp://user@host:port/path`
+///
+/// The user and port are optional, and a missing path lists the home
+/// directory of the user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SftpUrl {
+    pub user: Option<String>,
+    pub host: String,
+    pub port: Option<u16>,
+    pub path: String,
+}
+
+impl SftpUrl {
+    /// `user@host` as passed to the `sftp` client
+    fn destination(&self) -> String {
+        match self.user.as_ref() {
+            Some(user) => format!("{user}@{}", self.host),
+            None => self.host.clone(),
+        }
+    }
+}
+
+impl FromStr for SftpUrl {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rest = s
+            .strip_prefix("sftp://")
+            .ok_or_else(|| format!("'{s}' is not an sftp:// url"))?;
+        let (authority, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, ""),
+        };
+        let (user, host) = match authority.rsplit_once('@') {
+            Some((user, host)) => (Some(user.to_string()), host),
+            None => (None, authority),
+        };
+        let (host, port) = match host.rsplit_once(':') {
+            Some((host, port)) => (
+                host,
+                Some(
+                    port.parse::<u16>()
+                        .map_err(|_| format!("invalid port '{port}' in '{s}'"))?,
+                ),
+            ),
+            None => (host, None),
+        };
+        if host.is_empty() {
+            return Err(format!("missing host in '{s}'"));
+        }
+        // ssh would read these as options, `-oProxyCommand=...` runs a command
+        if host.starts_with('-') || user.as_ref().is_some_and(|user| user.starts_with('-')) {
+            return Err(format!("host and user in '{s}' can't start with '-'"));
+        }
+        let path = percent_decode(path);
+        // A newline would start another batch command, `!cmd` runs it locally
+        if path.chars().any(char::is_control) {
+            return Err(format!("path in '{s}' can't contain control characters"));
+        }
+        Ok(Self {
+            user,
+            host: host.to_string(),
+            port,
+            path: match path.as_str() {
+                "" | "/~" | "/~/" => ".".to_string(),
+                path => path.strip_prefix("/~/").unwrap_or(path).to_string(),
+            },
+        })
+    }
+}
+
+/// Percent decode the path of a url, `%20` is a space
+fn percent_decode(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let byte = bytes
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match byte {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// A file in a remote directory listed over SFTP
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteEntry {
+    pub name: String,
+    pub kind: Kind,
+    /// Permission bits without the file type
+    pub mode: u32,
+    pub size: u64,
+    pub owner: String,
+    pub group: String,
+    /// Only minute precision is available, and only the day for old files
+    pub modified: Option<SystemTime>,
+}
+
+impl RemoteEntry {
+    /// Parse a line of `ls -l` output from the `sftp` client,
+    /// `drwxr-xr-x    2 user group 4096 Oct 16 15:11 name`
+    fn parse(line: &str, now: chrono::DateTime<Local>) -> Option<Self> {
+        let mut rest = line.trim_start();
+        let mut fields = Vec::with_capacity(8);
+        for _ in 0..8 {
+            let end = rest.find(char::is_whitespace)?;
+            fields.push(&rest[..end]);
+            rest = rest[end..].trim_start();
+        }
+        let name = rest.trim_end_matches(['\r', '\n']);
+        if name.is_empty() {
+            return None;
+        }
+
+        let perms = fields[0];
+        let kind = match perms.chars().next()? {
+            'd' => Kind::Dir,
+            'l' => Kind::Link,
+            '-' => Kind::File,
+            _ => Kind::Other,
+        };
+        Some(Self {
+            name: name.to_string(),
+            kind,
+            mode: parse_mode(perms)?,
+            size: fields[4].parse().ok()?,
+            owner: fields[2].to_string(),
+            group: fields[3].to_string(),
+            modified: parse_date(fields[5], fields[6], fields[7], now),
+        })
+    }
+}
+
+impl Listable for RemoteEntry {
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.name)
+    }
+
+    fn kind(&self) -> Kind {
+        self.kind
+    }
+
+    fn size(&self) -> Option<u64> {
+        (self.kind != Kind::Dir).then_some(self.size)
+    }
+
+    fn modified(&self) -> io::Result<SystemTime> {
+        self.modified
+            .ok_or_else(|| io::ErrorKind::Unsupported.into())
+    }
+
+    fn mode(&self) -> Option<u32> {
+        Some(self.mode)
+    }
+}
+
+/// List a remote directory with the system `sftp` client
+///
+/// Authentication is left to the client, so keys, agents, and the ssh config
+/// work as usual. Batch mode can't prompt for passwords. `.` and `..` are left
+/// out.
+pub fn list(url: &SftpUrl) -> Result<Vec<RemoteEntry>, Box<dyn std::error::Error>> {
+    if url.path.chars().any(char::is_control) {
+        return Err(format!(
+            "path '{}' can't contain control characters",
+            url.path.escape_debug()
+        )
+        .into());
+    }
+
+    let mut command = Command::new("sftp");
+    command.args(["-q", "-b", "-"]);
+    if let Some(port) = url.port {
+        command.args(["-P", &port.to_string()]);
+    }
+    let mut child = command
+        .arg("--")
+        .arg(url.destination())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("failed to run sftp: {err}"))?;
+
+    let path = url.path.replace('\\', "\\\\").replace('"', "\\\"");
+    child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(format!("ls -la \"{path}\"\n").as_bytes())?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("sftp {}: {}", url.destination(), stderr.trim()).into());
+    }
+
+    let now = Local::now();
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        // Batch mode echoes each command
+        .filter(|line| !line.starts_with("sftp>"))
+        .filter_map(|line| RemoteEntry::parse(line, now))
+        .filter(|entry| entry.name != "." && entry.name != "..")
+        .collect())
+}
+
+/// Permission bits of `rwxr-xr-x` style permissions after the type character
+fn parse_mode(perms: &str) -> Option<u32> {
+    let chars = perms.chars().skip(1).take(9).collect::<Vec<_>>();
+    if chars.len() != 9 {
+        return None;
+    }
+    let mut mode = 0;
+    for (i, c) in chars.iter().enumerate() {
+        let bit = 1 << (8 - i);
+        match (i % 3, c) {
+            (_, '-') | (2, 'S' | 'T') => {}
+            (0, 'r') | (1, 'w') | (2, 'x' | 's' | 't') => mode |= bit,
+            _ => return None,
+        }
+    }
+    Some(mode)
+}
+
+/// Date of `ls -l` output, `Oct 16 15:11` within the last six months and
+/// `Oct 16  2024` otherwise
+fn parse_date(
+    month: &str,
+    day: &str,
+    time_or_year: &str,
+    now: chrono::DateTime<Local>,
+) -> Option<SystemTime> {
+    let month = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ]
+    .iter()
+    .position(|m| *m == month)? as u32
+        + 1;
+    let day = day.parse().ok()?;
+
+    let (year, time) = match time_or_year.split_once(':') {
+        Some((hour, minute)) => {
+            let time = NaiveTime::from_hms_opt(hour.parse().ok()?, minute.parse().ok()?, 0)?;
+            // Times without a year are in the past, so a date later in the
+            // year than today is from last year
+            let this_year = NaiveDate::from_ymd_opt(now.year(), month, day)?;
+            match this_year > now.date_naive() {
+                true => (now.year() - 1, time),
+                false => (now.year(), time),
+            }
+        }
+        None => (time_or_year.parse().ok()?, NaiveTime::MIN),
+    };
+    let date = NaiveDate::from_ymd_opt(year, month, day)?.and_time(time);
+    Local
+        .from_local_datetime(&date)
+        .earliest()
+        .map(SystemTime::from)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_urls() {
+        assert_eq!(
+            "sftp://me@example.com:2222/var/log".parse::<SftpUrl>(),
+            Ok(SftpUrl {
+                user: Some("me".to_string()),
+                host: "example.com".to_string(),
+                port: Some(2222),
+                path: "/var/log".to_string(),
+            })
+        );
+        let home = "sftp://example.com".parse::<SftpUrl>().unwrap();
+        assert_eq!(
+            (home.user, home.port, home.path.as_str()),
+            (None, None, ".")
+        );
+        assert_eq!(
+            "sftp://host/~/projects".parse::<SftpUrl>().unwrap().path,
+            "projects"
+        );
+        assert!("sftp://:22/".parse::<SftpUrl>().is_err());
+        assert!("sftp://host:ssh/".parse::<SftpUrl>().is_err());
+        assert!("sftp://-oProxyCommand=touch%20x/"
+            .parse::<SftpUrl>()
+            .is_err());
+        assert!("sftp://-oProxyCommand=id@host/".parse::<SftpUrl>().is_err());
+
+        assert_eq!(
+            "sftp://host/srv/my%20files/%7e"
+                .parse::<SftpUrl>()
+                .unwrap()
+                .path,
+            "/srv/my files/~"
+        );
+        assert_eq!(
+            "sftp://host/100%/%zz".parse::<SftpUrl>().unwrap().path,
+            "/100%/%zz"
+        );
+        assert!("sftp://host/tmp%0A!touch%20x".parse::<SftpUrl>().is_err());
+        assert!("sftp://host/tmp\r!id".parse::<SftpUrl>().is_err());
+        let url = SftpUrl {
+            path: "/tmp\n!id".to_string(),
+            ..SftpUrl::from_str("sftp://example.com").unwrap()
+        };
+        assert!(list(&url).is_err_and(|err| err.to_string().contains("control")));
+    }
+
+    #[test]
+    fn parse_listing() {
+        let now = Local.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let dir = RemoteEntry::parse(
+            "drwxr-xr-x    2 me       staff        4096 Feb 20 15:11 some dir",
+            now,
+        )
+        .unwrap();
+        assert_eq!(dir.name, "some dir");
+        assert_eq!(dir.kind, Kind::Dir);
+        assert_eq!(dir.mode, 0o755);
+        assert_eq!(dir.owner, "me");
+        assert_eq!(
+            dir.modified,
+            Some(SystemTime::from(
+                Local.with_ymd_and_hms(2024, 2, 20, 15, 11, 0).unwrap()
+            ))
+        );
+
+        // Later in the year than now without a year is from last year
+        let old = RemoteEntry::parse("-rwsr-x--T 1 me staff 12 Dec 24 09:30 run", now).unwrap();
+        assert_eq!(old.mode, 0o750);
+        assert_eq!(old.size, 12);
+        assert_eq!(
+            old.modified,
+            Some(SystemTime::from(
+                Local.with_ymd_and_hms(2023, 12, 24, 9, 30, 0).unwrap()
+            ))
+        );
+
+        let dated = RemoteEntry::parse("lrwxrwxrwx 1 me staff 4 Oct 16  2019 link", now).unwrap();
+        assert_eq!(dated.kind, Kind::Link);
+        assert!(dated.modified.is_some());
+
+        assert!(RemoteEntry::parse("sftp> ls -la", now).is_none());
+    }
+}
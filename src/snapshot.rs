@@ -0,0 +1,405 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use hashbrown::HashMap;
+use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+
+use crate::{style::humansize, Entry, FileId, FileSystem};
+
+/// Recorded state of a single entry in a [`Snapshot`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Record {
+    /// Path relative to the snapshot root
+    pub path: PathBuf,
+    pub dir: bool,
+    pub size: u64,
+    /// Modified time as seconds since the unix epoch
+    pub modified: Option<u64>,
+    /// Identity of the file, shared by hard links and kept across renames
+    #[serde(default)]
+    pub file_id: Option<FileId>,
+}
+
+impl Record {
+    fn new(root: &Path, entry: &Entry) -> Self {
+        Self {
+            path: entry
+                .path()
+                .strip_prefix(root)
+                .unwrap_or(entry.path())
+                .to_path_buf(),
+            dir: entry.is_dir(),
+            size: if entry.is_dir() {
+                0
+            } else {
+                entry.metadata().len()
+            },
+            modified: entry
+                .metadata()
+                .modified()
+                .ok()
+                .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs()),
+            file_id: entry.file_id(),
+        }
+    }
+}
+
+/// Paths, sizes, and modified times of a directory tree at a point in time
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub root: PathBuf,
+    /// Seconds since the unix epoch when the snapshot was taken
+    pub created: u64,
+    pub records: Vec<Record>,
+}
+
+/// Difference for a single path between two snapshots
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    Added(Record),
+    Removed(Record),
+    Grew {
+        before: Record,
+        after: Record,
+    },
+    Shrank {
+        before: Record,
+        after: Record,
+    },
+    /// Moved or renamed, see [`Snapshot::diff`]
+    Renamed {
+        before: Record,
+        after: Record,
+    },
+}
+
+impl Change {
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Added(record) | Self::Removed(record) => &record.path,
+            Self::Grew { after, .. } | Self::Shrank { after, .. } | Self::Renamed { after, .. } => {
+                &after.path
+            }
+        }
+    }
+}
+
+impl Snapshot {
+    /// Directory the named snapshots are stored in
+    pub fn dir() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("xf").join("snapshots"))
+    }
+
+    /// Walk the file system recursively, see [`FileSystem::walk`], and record
+    /// every entry
+    pub fn capture(file_system: &FileSystem) -> Result<Self, Box<dyn std::error::Error>> {
+        let records = file_system
+            .walk()?
+            .iter()
+            .map(|entry| Record::new(&file_system.path, entry))
+            .collect();
+
+        Ok(Self {
+            root: file_system.path.clone(),
+            created: std::time::SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            records,
+        })
+    }
+
+    fn file(name: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        Ok(Self::dir()
+            .ok_or("could not determine the snapshot directory")?
+            .join(format!("{name}.json")))
+    }
+
+    /// Save the snapshot under the given name
+    pub fn save(&self, name: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let path = Self::file(name)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(path)
+    }
+
+    /// Load a previously saved snapshot
+    pub fn load(name: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = Self::file(name)?;
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| format!("snapshot '{name}' ({}): {e}", path.display()))?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Changes that happened between this snapshot and a newer one
+    ///
+    /// Only growth and shrinkage are reported for paths that exist in both.
+    /// Removed and added entries that are the same file are reported as
+    /// renamed instead, see [`renames`].
+    pub fn diff(&self, newer: &Snapshot) -> Vec<Change> {
+        let before = self
+            .records
+            .iter()
+            .map(|r| (r.path.as_path(), r))
+            .collect::<HashMap<_, _>>();
+        let after = newer
+            .records
+            .iter()
+            .map(|r| (r.path.as_path(), r))
+            .collect::<HashMap<_, _>>();
+
+        let mut changes = Vec::new();
+        for record in newer.records.iter() {
+            match before.get(record.path.as_path()) {
+                None => changes.push(Change::Added(record.clone())),
+                Some(old) if record.size > old.size => changes.push(Change::Grew {
+                    before: (*old).clone(),
+                    after: record.clone(),
+                }),
+                Some(old) if record.size < old.size => changes.push(Change::Shrank {
+                    before: (*old).clone(),
+                    after: record.clone(),
+                }),
+                _ => {}
+            }
+        }
+        for record in self.records.iter() {
+            if !after.contains_key(record.path.as_path()) {
+                changes.push(Change::Removed(record.clone()));
+            }
+        }
+
+        let mut changes = renames(changes);
+        changes.sort_by(|a, b| a.path().cmp(b.path()));
+        changes
+    }
+}
+
+/// Pair removed and added records that are the same file into renames
+///
+/// Records are matched by an identical size and modified time, which a move
+/// keeps, as long as only one pair matches. When both have a file id it has to
+/// match as well, since the file system reuses the ids of deleted files. Entries inside a renamed directory that kept their name are
+/// dropped since the directory's rename already covers them.
+fn renames(changes: Vec<Change>) -> Vec<Change> {
+    let mut removed = Vec::new();
+    let mut rest = Vec::new();
+    for change in changes {
+        match change {
+            Change::Removed(record) => removed.push(Some(record)),
+            other => rest.push(other),
+        }
+    }
+
+    let same = |before: &Record, after: &Record| {
+        before.dir == after.dir
+            && before.size == after.size
+            && before.modified == after.modified
+            && match (before.file_id, after.file_id) {
+                (Some(before), Some(after)) => before == after,
+                _ => !before.dir,
+            }
+    };
+
+    let mut changes = Vec::with_capacity(rest.len() + removed.len());
+    let mut moved = HashMap::new();
+    for change in rest {
+        let Change::Added(after) = change else {
+            changes.push(change);
+            continue;
+        };
+        let mut matches = removed
+            .iter()
+            .enumerate()
+            .filter(|(_, before)| before.as_ref().is_some_and(|before| same(before, &after)));
+        match (matches.next(), matches.next()) {
+            (Some((i, _)), None) => {
+                let before = removed[i].take().unwrap();
+                if before.dir {
+                    moved.insert(before.path.clone(), after.path.clone());
+                }
+                changes.push(Change::Renamed { before, after });
+            }
+            _ => changes.push(Change::Added(after)),
+        }
+    }
+    changes.extend(removed.into_iter().flatten().map(Change::Removed));
+
+    changes.retain(|change| match change {
+        Change::Renamed { before, after } => {
+            let parents = before.path.parent().zip(after.path.parent());
+            !(before.path.file_name() == after.path.file_name()
+                && parents.is_some_and(|(before, after)| {
+                    moved.get(before).is_some_and(|moved| moved == after)
+                }))
+        }
+        _ => true,
+    });
+    changes
+}
+
+/// Print a list of changes, one per line
+pub fn print_diff<W: Write>(out: &mut W, changes: &[Change]) -> std::io::Result<()> {
+    for change in changes {
+        match change {
+            Change::Added(record) => writeln!(
+                out,
+                "{} {} {}",
+                "+".green(),
+                record.path.display(),
+                humansize(record.size).dimmed()
+            )?,
+            Change::Removed(record) => writeln!(
+                out,
+                "{} {} {}",
+                "-".red(),
+                record.path.display(),
+                humansize(record.size).dimmed()
+            )?,
+            Change::Grew { before, after } => writeln!(
+                out,
+                "{} {} {} -> {} {}",
+                "↑".yellow(),
+                after.path.display(),
+                humansize(before.size).dimmed(),
+                humansize(after.size),
+                format!("(+{})", humansize(after.size - before.size)).yellow()
+            )?,
+            Change::Shrank { before, after } => writeln!(
+                out,
+                "{} {} {} -> {} {}",
+                "↓".cyan(),
+                after.path.display(),
+                humansize(before.size).dimmed(),
+                humansize(after.size),
+                format!("(-{})", humansize(before.size - after.size)).cyan()
+            )?,
+            Change::Renamed { before, after } => writeln!(
+                out,
+                "{} {} -> {}",
+                "renamed".magenta(),
+                before.path.display(),
+                after.path.display()
+            )?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{sort::Natural, testing::TempDir};
+
+    fn record(path: &str, dir: bool, size: u64, inode: Option<u64>) -> Record {
+        Record {
+            path: path.into(),
+            dir,
+            size,
+            modified: Some(100),
+            file_id: inode.map(|inode| FileId { device: 1, inode }),
+        }
+    }
+
+    fn snapshot(records: Vec<Record>) -> Snapshot {
+        Snapshot {
+            root: "/root".into(),
+            created: 0,
+            records,
+        }
+    }
+
+    #[test]
+    fn detect_renames() {
+        let before = snapshot(vec![
+            record("old", true, 0, Some(1)),
+            record("old/a.rs", false, 10, Some(2)),
+            record("b.txt", false, 5, Some(3)),
+            record("c.txt", false, 7, None),
+            record("gone", false, 1, Some(4)),
+        ]);
+        let after = snapshot(vec![
+            record("new", true, 0, Some(1)),
+            record("new/a.rs", false, 10, Some(2)),
+            record("renamed.txt", false, 5, Some(3)),
+            record("d.txt", false, 7, None),
+            record("fresh", false, 1, Some(5)),
+        ]);
+
+        let changes = before.diff(&after);
+        let renamed = |from: &str, to: &str| Change::Renamed {
+            before: before
+                .records
+                .iter()
+                .find(|r| r.path == Path::new(from))
+                .unwrap()
+                .clone(),
+            after: after
+                .records
+                .iter()
+                .find(|r| r.path == Path::new(to))
+                .unwrap()
+                .clone(),
+        };
+        assert_eq!(
+            changes,
+            vec![
+                renamed("c.txt", "d.txt"),
+                Change::Added(after.records[4].clone()),
+                Change::Removed(before.records[4].clone()),
+                renamed("old", "new"),
+                renamed("b.txt", "renamed.txt"),
+            ]
+        );
+    }
+
+    #[test]
+    fn reused_file_ids() {
+        let mut edited = record("fresh", false, 5, Some(3));
+        edited.modified = Some(200);
+        let before = snapshot(vec![record("deleted", false, 5, Some(3))]);
+        let after = snapshot(vec![edited]);
+        assert_eq!(
+            before.diff(&after),
+            vec![
+                Change::Removed(before.records[0].clone()),
+                Change::Added(after.records[0].clone()),
+            ]
+        );
+    }
+
+    #[test]
+    fn capture_walks_like_the_file_system() {
+        let base = TempDir::new("snapshot-capture");
+        std::fs::create_dir_all(base.join(".git")).unwrap();
+        std::fs::write(base.join(".git").join("HEAD"), "").unwrap();
+        std::fs::create_dir_all(base.join("d")).unwrap();
+        std::fs::write(base.join("d").join("b"), "12").unwrap();
+        std::fs::write(base.join("a"), "").unwrap();
+
+        let file_system = FileSystem::from(base.path())
+            .with_sorter(Natural)
+            .with_filter(());
+        let paths = |file_system: &FileSystem| {
+            Snapshot::capture(file_system)
+                .unwrap()
+                .records
+                .into_iter()
+                .map(|record| record.path.display().to_string())
+                .collect::<Vec<_>>()
+        };
+        // Hidden directories are only walked into when asked for
+        assert_eq!(paths(&file_system), [".git", "a", "d", "d/b"]);
+        assert_eq!(
+            paths(&file_system.with_hidden_dirs(true)),
+            [".git", ".git/HEAD", "a", "d", "d/b"]
+        );
+    }
+}
@@ -1,8 +1,10 @@
-use std::{cmp::Ordering, os::windows::fs::MetadataExt};
+use std::{cmp::Ordering, fmt::Display, path::PathBuf, str::FromStr, sync::Mutex};
+
+use hashbrown::HashMap;
 
 use chrono::Local;
 
-use crate::{Directory, Entry, Hidden};
+use crate::{filter::Glob, Directory, Entry, Hidden};
 
 /// Helper to determine state of a char from an iterator
 pub trait IterChar {
@@ -34,8 +36,55 @@ impl IterChar for str {
 }
 
 /// Implement to allow a struct be a sorter for [`crate::Entry`]
-pub trait SortStrategy {
+///
+/// Sorters are shared between threads, so any internal state needs to be
+/// synchronized.
+pub trait SortStrategy: Send + Sync {
     fn compare(&self, first: &Entry, second: &Entry) -> Ordering;
+
+    /// Skip sorting and keep entries in the order the OS returned them
+    #[inline]
+    fn preserves_order(&self) -> bool {
+        false
+    }
+
+    /// Key the entries are primarily sorted by and whether it is descending,
+    /// used to mark the sorted column in headers
+    #[inline]
+    fn column(&self) -> Option<(SortKey, bool)> {
+        None
+    }
+}
+
+impl<T: SortStrategy + ?Sized> SortStrategy for Box<T> {
+    fn compare(&self, first: &Entry, second: &Entry) -> Ordering {
+        (**self).compare(first, second)
+    }
+
+    fn preserves_order(&self) -> bool {
+        (**self).preserves_order()
+    }
+
+    fn column(&self) -> Option<(SortKey, bool)> {
+        (**self).column()
+    }
+}
+
+/// Leave entries in directory read order, the fastest option for scripts
+///
+/// The order depends on the OS and file system and is not guaranteed to be
+/// stable between runs.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Unsorted;
+
+impl SortStrategy for Unsorted {
+    fn compare(&self, _first: &Entry, _second: &Entry) -> Ordering {
+        Ordering::Equal
+    }
+
+    fn preserves_order(&self) -> bool {
+        true
+    }
 }
 
 // Default sorter sorts by comparing file names as strings
@@ -70,50 +119,220 @@ impl SortStrategy for () {
 pub struct Natural;
 impl SortStrategy for Natural {
     fn compare(&self, first: &Entry, second: &Entry) -> Ordering {
-        // ab102c -> a b 102 c
-        // ab20a -> a b 20 a
-        let mut i = 0usize;
-        let mut j = 0usize;
-
-        let first = first.file_name();
-        let second = second.file_name();
-
-        let _ = second[j..j + 1];
-        while i < first.len() && j < second.len() {
-            if first[i..i + 1].is_ascii_digit() && second[j..j + 1].is_ascii_digit() {
-                let u = i;
-                let v = j;
-                while i < first.len() && first[i..i + 1].is_ascii_digit() {
-                    i += 1;
-                }
-                while j < second.len() && second[j..j + 1].is_ascii_digit() {
-                    j += 1;
-                }
-
-                let u = first[u..i].parse::<usize>().unwrap();
-                let v = second[v..j].parse::<usize>().unwrap();
-
-                match u.cmp(&v) {
-                    Ordering::Equal => {}
-                    other => return other,
-                }
-            } else {
-                // If comparison is not equal return it immediatly
-                match first[i..i + 1].cmp(&second[j..j + 1]) {
-                    Ordering::Equal => {}
-                    other => return other,
-                }
+        natural(first.file_name(), second.file_name())
+    }
+
+    fn column(&self) -> Option<(SortKey, bool)> {
+        Some((SortKey::Name, false))
+    }
+}
+
+/// Sort by name in [`Natural`] order ignoring case, so `README` sorts
+/// between `main.rs` and `src` instead of before both
+///
+/// Names that only differ in case are ordered by the inner sorter.
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub struct IgnoreCase<T = Natural>(pub T);
+
+impl<T: SortStrategy> SortStrategy for IgnoreCase<T> {
+    fn compare(&self, first: &Entry, second: &Entry) -> Ordering {
+        natural(
+            &first.file_name().to_lowercase(),
+            &second.file_name().to_lowercase(),
+        )
+        .then_with(|| self.0.compare(first, second))
+    }
+
+    fn column(&self) -> Option<(SortKey, bool)> {
+        Some((SortKey::Name, false))
+    }
+}
+
+/// Compare two names in [`Natural`] order
+fn natural(first: &str, second: &str) -> Ordering {
+    // ab102c -> a b 102 c
+    // ab20a -> a b 20 a
+    let mut i = 0usize;
+    let mut j = 0usize;
+
+    // Bytes compare in the same order as the characters they encode, and
+    // digits are always a single byte
+    let first = first.as_bytes();
+    let second = second.as_bytes();
+
+    while i < first.len() && j < second.len() {
+        if first[i].is_ascii_digit() && second[j].is_ascii_digit() {
+            let u = i;
+            let v = j;
+            while i < first.len() && first[i].is_ascii_digit() {
+                i += 1;
+            }
+            while j < second.len() && second[j].is_ascii_digit() {
+                j += 1;
+            }
+
+            match compare_digits(&first[u..i], &second[v..j]) {
+                Ordering::Equal => continue,
+                other => return other,
+            }
+        }
+
+        // If comparison is not equal return it immediatly
+        match first[i].cmp(&second[j]) {
+            Ordering::Equal => {}
+            other => return other,
+        }
+        i += 1;
+        j += 1;
+    }
+
+    match (i < first.len(), j < second.len()) {
+        (false, true) => Ordering::Less,
+        (true, false) => Ordering::Greater,
+        _ => Ordering::Equal,
+    }
+}
+
+/// Compare runs of digits by their value, however long they are
+fn compare_digits(first: &[u8], second: &[u8]) -> Ordering {
+    let trim = |digits: &[u8]| -> usize { digits.iter().take_while(|d| **d == b'0').count() };
+    let first = &first[trim(first)..];
+    let second = &second[trim(second)..];
+    first
+        .len()
+        .cmp(&second.len())
+        .then_with(|| first.cmp(second))
+}
+
+/// Sorter that follows the version order of GNU `ls -v`
+///
+/// Unlike [`Natural`], names are compared the way `filevercmp` does. Dotfiles
+/// come first, file suffixes like `.tar.gz` only break ties, letters sort
+/// before other symbols, and `~` sorts before everything so `1.0~rc1` comes
+/// before `1.0`.
+///
+/// # Example
+///
+/// ```plaintext
+/// app-1.2.0.tar.gz
+/// app-1.10.2.tar.gz
+/// app-1.10.2~rc1.tar.gz
+/// ```
+///
+/// Will be sorted as
+///
+/// ```plaintext
+/// app-1.2.0.tar.gz
+/// app-1.10.2~rc1.tar.gz
+/// app-1.10.2.tar.gz
+/// ```
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub struct Version;
+
+impl Version {
+    /// Compare two names in version order
+    pub fn compare_names(first: &str, second: &str) -> Ordering {
+        Self::compare_files(first.as_bytes(), second.as_bytes()).then_with(|| first.cmp(second))
+    }
+
+    /// `filevercmp` from gnulib, names that only differ in ways it ignores
+    /// compare equal
+    fn compare_files(a: &[u8], b: &[u8]) -> Ordering {
+        // `.` and `..` are always first, followed by the other dotfiles
+        let rank = |name: &[u8]| match name {
+            b"" => 0,
+            b"." => 1,
+            b".." => 2,
+            [b'.', ..] => 3,
+            _ => 4,
+        };
+        let (a, b) = match (rank(a), rank(b)) {
+            (x, y) if x != y => return x.cmp(&y),
+            (0..=2, _) => return Ordering::Equal,
+            (3, _) => (&a[1..], &b[1..]),
+            _ => (a, b),
+        };
+
+        let (a_stem, b_stem) = (stem_len(a), stem_len(b));
+        match compare_versions(&a[..a_stem], &b[..b_stem]) {
+            // Suffixes only break ties
+            Ordering::Equal if a_stem < a.len() || b_stem < b.len() => compare_versions(a, b),
+            other => other,
+        }
+    }
+}
+
+impl SortStrategy for Version {
+    fn compare(&self, first: &Entry, second: &Entry) -> Ordering {
+        Self::compare_names(first.file_name(), second.file_name())
+    }
+
+    fn column(&self) -> Option<(SortKey, bool)> {
+        Some((SortKey::Name, false))
+    }
+}
+
+/// Length of `name` without its suffix, trailing runs of `.` followed by a
+/// letter or `~` and then letters, digits, or `~`, like `.tar.gz`
+fn stem_len(name: &[u8]) -> usize {
+    let mut stem = 0;
+    let mut i = 0;
+    while i < name.len() {
+        i += 1;
+        stem = i;
+        while i + 1 < name.len()
+            && name[i] == b'.'
+            && (name[i + 1].is_ascii_alphabetic() || name[i + 1] == b'~')
+        {
+            i += 2;
+            while i < name.len() && (name[i].is_ascii_alphanumeric() || name[i] == b'~') {
+                i += 1;
+            }
+        }
+    }
+    stem
+}
+
+/// Weight of a byte outside of a digit run, `~` before the end of a name,
+/// then letters, then everything else
+fn version_weight(name: &[u8], i: usize) -> i32 {
+    match name.get(i) {
+        None => 0,
+        Some(c) if c.is_ascii_digit() => 0,
+        Some(c) if c.is_ascii_alphabetic() => *c as i32,
+        Some(b'~') => -1,
+        Some(c) => *c as i32 + 256,
+    }
+}
+
+/// Compare alternating runs of non-digits and digits, `verrevcmp` from dpkg
+fn compare_versions(a: &[u8], b: &[u8]) -> Ordering {
+    let digit = |name: &[u8], i: usize| name.get(i).is_some_and(u8::is_ascii_digit);
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        // Only steps past bytes of equal weight, so never past the end
+        while (i < a.len() && !digit(a, i)) || (j < b.len() && !digit(b, j)) {
+            match version_weight(a, i).cmp(&version_weight(b, j)) {
+                Ordering::Equal => {}
+                other => return other,
             }
             i += 1;
             j += 1;
         }
 
-        match (i < first.len(), j < second.len()) {
-            (false, true) => Ordering::Less,
-            (true, false) => Ordering::Greater,
-            _ => Ordering::Equal,
+        let (u, v) = (i, j);
+        while digit(a, i) {
+            i += 1;
+        }
+        while digit(b, j) {
+            j += 1;
+        }
+        match compare_digits(&a[u..i], &b[v..j]) {
+            Ordering::Equal => {}
+            other => return other,
         }
     }
+    Ordering::Equal
 }
 
 pub trait Matches {
@@ -177,6 +396,81 @@ impl<T: SortStrategy> SortStrategy for Extension<T> {
             (None, None) => self.0.compare(first, second),
         }
     }
+
+    fn column(&self) -> Option<(SortKey, bool)> {
+        Some((SortKey::Extension, false))
+    }
+}
+
+/// Sort by the full extension, so `.tar.gz` and `.d.ts` are compared as one
+/// instead of by their last part, with extensionless entries first
+///
+/// Dotfiles can optionally be grouped ahead of everything else with
+/// [`Suffix::dotfiles_first`].
+pub struct Suffix<T = Natural> {
+    inner: T,
+    dotfiles_first: bool,
+}
+
+impl Default for Suffix {
+    fn default() -> Self {
+        Self::new(Natural)
+    }
+}
+
+impl<T> Suffix<T> {
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            dotfiles_first: false,
+        }
+    }
+
+    /// Place dotfiles before all other entries
+    pub fn dotfiles_first(mut self, state: bool) -> Self {
+        self.dotfiles_first = state;
+        self
+    }
+}
+
+impl<T: SortStrategy> SortStrategy for Suffix<T> {
+    fn compare(&self, first: &Entry, second: &Entry) -> Ordering {
+        let dotfiles = match self.dotfiles_first {
+            true => second.is_dot().cmp(&first.is_dot()),
+            false => Ordering::Equal,
+        };
+        dotfiles
+            .then_with(|| suffix(first.file_name()).cmp(&suffix(second.file_name())))
+            .then_with(|| self.inner.compare(first, second))
+    }
+
+    fn column(&self) -> Option<(SortKey, bool)> {
+        Some((SortKey::Suffix, false))
+    }
+}
+
+/// Full extension of a file name, `tar.gz` for `backup.tar.gz`
+///
+/// Parts before the last extension are only included while they look like an
+/// extension themselves, short and not a version number, so `v1.2.zip` is a
+/// `zip` and `notes.2024.txt` is a `txt`.
+fn suffix(name: &str) -> Option<&str> {
+    // A leading dot marks a dotfile rather than an extension
+    let stem = name.strip_prefix('.').unwrap_or(name);
+    let mut start = stem.rfind('.')? + 1;
+    if start == stem.len() {
+        return None;
+    }
+    if let Some(dot) = stem[..start - 1].rfind('.') {
+        let part = &stem[dot + 1..start - 1];
+        let extension_like = (1..=4).contains(&part.len())
+            && part.chars().all(|c| c.is_ascii_alphanumeric())
+            && !part.chars().all(|c| c.is_ascii_digit());
+        if extension_like && dot > 0 {
+            start = dot + 1;
+        }
+    }
+    Some(&stem[start..])
 }
 
 pub trait Grouping<T = ()> {
@@ -216,7 +510,7 @@ impl<T: Default, D: Default> Default for Group<T, D> {
     }
 }
 
-impl<T: Grouping, D: SortStrategy> SortStrategy for Group<T, D> {
+impl<T: Grouping + Send + Sync, D: SortStrategy> SortStrategy for Group<T, D> {
     fn compare(&self, first: &Entry, second: &Entry) -> Ordering {
         let f = T::get_group_index(first);
         let s = T::get_group_index(second);
@@ -231,74 +525,141 @@ impl<T: Grouping, D: SortStrategy> SortStrategy for Group<T, D> {
             (None, None) => self.1.compare(first, second),
         }
     }
+
+    fn column(&self) -> Option<(SortKey, bool)> {
+        self.1.column()
+    }
+}
+
+/// Where metadata sorters place entries that are missing the value they sort by
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MissingPolicy {
+    First,
+    #[default]
+    Last,
+    /// Compare against entries with a value using the wrapped strategy
+    Fallback,
+}
+
+impl MissingPolicy {
+    /// Compare two optional values, using `inner` to break ties and place
+    /// missing values when the policy is [`MissingPolicy::Fallback`]
+    pub fn compare<V: Ord>(
+        &self,
+        first: Option<V>,
+        second: Option<V>,
+        inner: impl FnOnce() -> Ordering,
+    ) -> Ordering {
+        match (first, second, self) {
+            (Some(f), Some(s), _) => f.cmp(&s).then_with(inner),
+            (None, None, _) | (_, _, Self::Fallback) => inner(),
+            (Some(_), None, Self::Last) | (None, Some(_), Self::First) => Ordering::Less,
+            (Some(_), None, Self::First) | (None, Some(_), Self::Last) => Ordering::Greater,
+        }
+    }
 }
 
-pub struct Date<T = Natural>(pub T);
+fn modified(entry: &Entry) -> Option<chrono::DateTime<Local>> {
+    entry.metadata().modified().map(|t| t.into()).ok()
+}
+
+/// Sort by the day an entry was modified
+pub struct Date<T = Natural>(pub T, pub MissingPolicy);
 
 impl Default for Date {
     fn default() -> Self {
-        Self(Natural)
+        Self::new(Natural)
+    }
+}
+
+impl<T> Date<T> {
+    pub fn new(inner: T) -> Self {
+        Self(inner, MissingPolicy::default())
+    }
+
+    pub fn missing(mut self, policy: MissingPolicy) -> Self {
+        self.1 = policy;
+        self
     }
 }
 
 impl<T: SortStrategy> SortStrategy for Date<T> {
     fn compare(&self, first: &Entry, second: &Entry) -> Ordering {
-        let f: Option<chrono::DateTime<Local>> = first.metadata().modified().map(|t| t.into()).ok();
-        let s: Option<chrono::DateTime<Local>> =
-            second.metadata().modified().map(|t| t.into()).ok();
+        self.1.compare(
+            modified(first).map(|d| d.date_naive()),
+            modified(second).map(|d| d.date_naive()),
+            || self.0.compare(first, second),
+        )
+    }
 
-        match (f, s) {
-            (Some(_), None) => Ordering::Less,
-            (None, Some(_)) => Ordering::Greater,
-            (Some(f), Some(s)) => f.date_naive().cmp(&s.date_naive()),
-            (None, None) => self.0.compare(first, second),
-        }
+    fn column(&self) -> Option<(SortKey, bool)> {
+        Some((SortKey::Modified, false))
     }
 }
 
-pub struct Time<T = Natural>(pub T);
+/// Sort by the time of day an entry was modified
+pub struct Time<T = Natural>(pub T, pub MissingPolicy);
 
 impl Default for Time {
     fn default() -> Self {
-        Self(Natural)
+        Self::new(Natural)
+    }
+}
+
+impl<T> Time<T> {
+    pub fn new(inner: T) -> Self {
+        Self(inner, MissingPolicy::default())
+    }
+
+    pub fn missing(mut self, policy: MissingPolicy) -> Self {
+        self.1 = policy;
+        self
     }
 }
 
 impl<T: SortStrategy> SortStrategy for Time<T> {
     fn compare(&self, first: &Entry, second: &Entry) -> Ordering {
-        let f: Option<chrono::DateTime<Local>> = first.metadata().modified().map(|t| t.into()).ok();
-        let s: Option<chrono::DateTime<Local>> =
-            second.metadata().modified().map(|t| t.into()).ok();
+        self.1.compare(
+            modified(first).map(|d| d.time()),
+            modified(second).map(|d| d.time()),
+            || self.0.compare(first, second),
+        )
+    }
 
-        match (f, s) {
-            (Some(_), None) => Ordering::Less,
-            (None, Some(_)) => Ordering::Greater,
-            (Some(f), Some(s)) => f.time().cmp(&s.time()),
-            (None, None) => self.0.compare(first, second),
-        }
+    fn column(&self) -> Option<(SortKey, bool)> {
+        Some((SortKey::Modified, false))
     }
 }
 
-pub struct DateTime<T = Natural>(pub T);
+/// Sort by when an entry was modified
+pub struct DateTime<T = Natural>(pub T, pub MissingPolicy);
 
 impl Default for DateTime {
     fn default() -> Self {
-        Self(Natural)
+        Self::new(Natural)
+    }
+}
+
+impl<T> DateTime<T> {
+    pub fn new(inner: T) -> Self {
+        Self(inner, MissingPolicy::default())
+    }
+
+    pub fn missing(mut self, policy: MissingPolicy) -> Self {
+        self.1 = policy;
+        self
     }
 }
 
 impl<T: SortStrategy> SortStrategy for DateTime<T> {
     fn compare(&self, first: &Entry, second: &Entry) -> Ordering {
-        let f: Option<chrono::DateTime<Local>> = first.metadata().modified().map(|t| t.into()).ok();
-        let s: Option<chrono::DateTime<Local>> =
-            second.metadata().modified().map(|t| t.into()).ok();
+        self.1.compare(modified(first), modified(second), || {
+            self.0.compare(first, second)
+        })
+    }
 
-        match (f, s) {
-            (Some(_), None) => Ordering::Less,
-            (None, Some(_)) => Ordering::Greater,
-            (Some(f), Some(s)) => f.cmp(&s),
-            (None, None) => self.0.compare(first, second),
-        }
+    fn column(&self) -> Option<(SortKey, bool)> {
+        Some((SortKey::Modified, false))
     }
 }
 
@@ -318,6 +679,14 @@ impl<T: SortStrategy> SortStrategy for Reverse<T> {
             Ordering::Equal => Ordering::Equal,
         }
     }
+
+    fn preserves_order(&self) -> bool {
+        self.0.preserves_order()
+    }
+
+    fn column(&self) -> Option<(SortKey, bool)> {
+        self.0.column().map(|(key, descending)| (key, !descending))
+    }
 }
 
 pub struct Size<T = Natural>(pub T);
@@ -330,8 +699,8 @@ impl Default for Size {
 
 impl<T: SortStrategy> SortStrategy for Size<T> {
     fn compare(&self, first: &Entry, second: &Entry) -> Ordering {
-        let fs = first.metadata().file_size();
-        let ss = second.metadata().file_size();
+        let fs = first.metadata().len();
+        let ss = second.metadata().len();
 
         match fs.cmp(&ss) {
             Ordering::Equal => self.0.compare(first, second),
@@ -339,4 +708,561 @@ impl<T: SortStrategy> SortStrategy for Size<T> {
             Ordering::Less => Ordering::Greater,
         }
     }
+
+    fn column(&self) -> Option<(SortKey, bool)> {
+        Some((SortKey::Size, true))
+    }
+}
+
+/// Sort text files by their number of lines, most lines first
+///
+/// Entries without a line count, see [`Entry::line_count`], come last. Counts
+/// are cached so each file is only read once.
+pub struct Lines<T = Natural> {
+    inner: T,
+    counts: Mutex<HashMap<PathBuf, Option<usize>>>,
+}
+
+impl Default for Lines {
+    fn default() -> Self {
+        Self::new(Natural)
+    }
+}
+
+impl<T> Lines<T> {
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            counts: Mutex::default(),
+        }
+    }
+
+    fn count(&self, entry: &Entry) -> Option<usize> {
+        *self
+            .counts
+            .lock()
+            .unwrap()
+            .entry(entry.path().to_path_buf())
+            .or_insert_with(|| entry.line_count())
+    }
+}
+
+impl<T: SortStrategy> SortStrategy for Lines<T> {
+    fn compare(&self, first: &Entry, second: &Entry) -> Ordering {
+        match (self.count(first), self.count(second)) {
+            (Some(f), Some(s)) => match s.cmp(&f) {
+                Ordering::Equal => self.inner.compare(first, second),
+                other => other,
+            },
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => self.inner.compare(first, second),
+        }
+    }
+
+    fn column(&self) -> Option<(SortKey, bool)> {
+        Some((SortKey::Lines, true))
+    }
+}
+
+/// Sort entries by a user defined weight where lower weights come first
+///
+/// Weights are assigned by the first glob that matches the file name, entries
+/// that don't match any glob have a weight of `0`. Entries with the same weight
+/// are compared with the wrapped strategy.
+///
+/// # Example
+///
+/// ```plaintext
+/// README* = -100
+/// *.lock = 10
+/// ```
+pub struct Weighted<T = Natural> {
+    weights: Vec<(Glob, i64)>,
+    inner: T,
+}
+
+impl<T> Weighted<T> {
+    pub fn new<I, S>(inner: T, weights: I) -> Result<Self, regex::Error>
+    where
+        I: IntoIterator<Item = (S, i64)>,
+        S: AsRef<str>,
+    {
+        Ok(Self {
+            weights: weights
+                .into_iter()
+                .map(|(pattern, weight)| Ok((Glob::new(pattern)?, weight)))
+                .collect::<Result<Vec<_>, regex::Error>>()?,
+            inner,
+        })
+    }
+
+    pub fn weight(&self, entry: &Entry) -> i64 {
+        self.weights
+            .iter()
+            .find(|(glob, _)| glob.is_match(entry.file_name()))
+            .map(|(_, weight)| *weight)
+            .unwrap_or_default()
+    }
+}
+
+impl<T: SortStrategy> SortStrategy for Weighted<T> {
+    fn compare(&self, first: &Entry, second: &Entry) -> Ordering {
+        match self.weight(first).cmp(&self.weight(second)) {
+            Ordering::Equal => self.inner.compare(first, second),
+            other => other,
+        }
+    }
+
+    // Weights override the inner order so no column matches it
+    fn column(&self) -> Option<(SortKey, bool)> {
+        match self.weights.is_empty() {
+            true => self.inner.column(),
+            false => None,
+        }
+    }
+}
+
+/// A single key of a [`Chain`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SortKey {
+    /// Natural order of the file name
+    Name,
+    /// Version order of the file name, see [`Version`]
+    Version,
+    /// Extension with extensionless entries first
+    Extension,
+    /// Full extension like `tar.gz`, see [`Suffix`]
+    Suffix,
+    /// Dotfiles first
+    Dotfiles,
+    /// Largest entries first like [`Size`] and `ls -S`
+    Size,
+    /// Oldest entries first
+    Modified,
+    /// Directories first
+    Directory,
+    /// Fewest lines first, see [`Entry::line_count`]
+    Lines,
+}
+
+impl SortKey {
+    pub const ALL: [SortKey; 9] = [
+        Self::Name,
+        Self::Version,
+        Self::Extension,
+        Self::Suffix,
+        Self::Dotfiles,
+        Self::Size,
+        Self::Modified,
+        Self::Directory,
+        Self::Lines,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Name => "name",
+            Self::Version => "version",
+            Self::Extension => "ext",
+            Self::Suffix => "suffix",
+            Self::Dotfiles => "dotfiles",
+            Self::Size => "size",
+            Self::Modified => "modified",
+            Self::Directory => "dir",
+            Self::Lines => "lines",
+        }
+    }
+}
+
+impl Display for SortKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for SortKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "name" => Ok(Self::Name),
+            "version" => Ok(Self::Version),
+            "ext" | "extension" => Ok(Self::Extension),
+            "suffix" => Ok(Self::Suffix),
+            "dotfiles" => Ok(Self::Dotfiles),
+            "size" => Ok(Self::Size),
+            "modified" | "time" | "date" => Ok(Self::Modified),
+            "dir" | "type" => Ok(Self::Directory),
+            "lines" => Ok(Self::Lines),
+            other => Err(format!(
+                "unknown sort key '{other}', expected one of {}",
+                Self::ALL
+                    .iter()
+                    .map(SortKey::name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+}
+
+/// Sort by each key in order, moving to the next key on ties
+///
+/// Parsed from a comma separated list of keys where a `-` prefix reverses that
+/// key, `-size,name`. An empty chain, parsed from `none`, leaves
+/// entries in directory read order like [`Unsorted`].
+pub struct Chain {
+    keys: Vec<(SortKey, bool)>,
+    ignore_case: bool,
+    lines: Lines<Unsorted>,
+}
+
+impl Clone for Chain {
+    fn clone(&self) -> Self {
+        Self {
+            keys: self.keys.clone(),
+            ignore_case: self.ignore_case,
+            lines: Lines::new(Unsorted),
+        }
+    }
+}
+
+impl std::fmt::Debug for Chain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Chain").field(&self.to_string()).finish()
+    }
+}
+
+// Line counts are only a cache so chains are equal when their keys are
+impl PartialEq for Chain {
+    fn eq(&self, other: &Self) -> bool {
+        self.keys == other.keys && self.ignore_case == other.ignore_case
+    }
+}
+
+impl Default for Chain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Chain {
+    pub fn new() -> Self {
+        Self {
+            keys: Vec::new(),
+            ignore_case: false,
+            lines: Lines::new(Unsorted),
+        }
+    }
+
+    /// Compare names ignoring case, see [`IgnoreCase`]
+    pub fn ignore_case(mut self, ignore_case: bool) -> Self {
+        self.ignore_case = ignore_case;
+        self
+    }
+
+    /// Add a key to the end of the chain, `reversed` like a `-` prefix
+    pub fn then(mut self, key: SortKey, reversed: bool) -> Self {
+        self.keys.push((key, reversed));
+        self
+    }
+
+    /// Keys along with whether they are reversed
+    pub fn keys(&self) -> &[(SortKey, bool)] {
+        &self.keys
+    }
+
+    fn compare_key(&self, key: SortKey, first: &Entry, second: &Entry) -> Ordering {
+        let equal = || Ordering::Equal;
+        match key {
+            SortKey::Name if self.ignore_case => IgnoreCase(Natural).compare(first, second),
+            SortKey::Name => Natural.compare(first, second),
+            SortKey::Version => Version.compare(first, second),
+            SortKey::Extension => Extension(Unsorted).compare(first, second),
+            SortKey::Suffix => Suffix::new(Unsorted).compare(first, second),
+            SortKey::Dotfiles => second.is_dot().cmp(&first.is_dot()),
+            SortKey::Size => second.metadata().len().cmp(&first.metadata().len()),
+            SortKey::Modified => {
+                MissingPolicy::Last.compare(modified(first), modified(second), equal)
+            }
+            SortKey::Directory => second.is_dir().cmp(&first.is_dir()),
+            SortKey::Lines => MissingPolicy::Last.compare(
+                self.lines.count(first),
+                self.lines.count(second),
+                equal,
+            ),
+        }
+    }
+}
+
+impl SortStrategy for Chain {
+    fn compare(&self, first: &Entry, second: &Entry) -> Ordering {
+        for (key, descending) in self.keys.iter() {
+            let order = self.compare_key(*key, first, second);
+            let order = if *descending { order.reverse() } else { order };
+            if order != Ordering::Equal {
+                return order;
+            }
+        }
+        Ordering::Equal
+    }
+
+    fn preserves_order(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    fn column(&self) -> Option<(SortKey, bool)> {
+        // Sizes are largest first, which is descending by value
+        self.keys.first().map(|(key, reversed)| match key {
+            SortKey::Size => (*key, !reversed),
+            _ => (*key, *reversed),
+        })
+    }
+}
+
+impl Display for Chain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.keys.is_empty() {
+            return f.write_str("none");
+        }
+        for (i, (key, descending)) in self.keys.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            if *descending {
+                f.write_str("-")?;
+            }
+            write!(f, "{key}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Chain {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim() == "none" {
+            return Ok(Self::new());
+        }
+
+        let mut chain = Self::new();
+        for key in s.split(',').map(str::trim) {
+            let (key, descending) = match key.strip_prefix('-') {
+                Some(key) => (key, true),
+                None => (key, false),
+            };
+            chain = chain.then(key.parse()?, descending);
+        }
+        Ok(chain)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_chain() {
+        let chain = "-size, name".parse::<Chain>().unwrap();
+        assert_eq!(
+            chain.keys(),
+            &[(SortKey::Size, true), (SortKey::Name, false)]
+        );
+        assert_eq!(chain.to_string(), "-size,name");
+        assert!("none".parse::<Chain>().unwrap().preserves_order());
+        assert!("size,colour".parse::<Chain>().is_err());
+    }
+
+    #[test]
+    fn chain_round_trip() {
+        for text in ["name", "-size,name", "dir,-modified,ext", "lines", "none"] {
+            let chain = text.parse::<Chain>().unwrap();
+            assert_eq!(chain.to_string(), text);
+            assert_eq!(chain.to_string().parse::<Chain>().unwrap(), chain);
+        }
+        // Aliases are written back with their canonical name
+        assert_eq!(
+            "type, -time,extension"
+                .parse::<Chain>()
+                .unwrap()
+                .to_string(),
+            "dir,-modified,ext"
+        );
+    }
+
+    #[test]
+    fn compound_suffixes() {
+        assert_eq!(suffix("backup.tar.gz"), Some("tar.gz"));
+        assert_eq!(suffix("index.d.ts"), Some("d.ts"));
+        assert_eq!(suffix("main.rs"), Some("rs"));
+        assert_eq!(suffix("v1.2.zip"), Some("zip"));
+        assert_eq!(suffix("notes.today.txt"), Some("txt"));
+        assert_eq!(suffix(".tar.gz"), Some("gz"));
+        assert_eq!(suffix(".bashrc"), None);
+        assert_eq!(suffix("Makefile"), None);
+        assert_eq!(suffix("trailing."), None);
+    }
+
+    #[test]
+    fn version_order() {
+        let mut names = vec![
+            "app-1.10.2.tar.gz",
+            "app-1.2.0.tar.gz",
+            "app-1.10.2~rc1.tar.gz",
+            "app-1.10.2.tar.bz2",
+            "file10",
+            ".hidden",
+            "file9",
+            "file009",
+            "a-b",
+            "a.b",
+            "ab",
+        ];
+        names.sort_by(|a, b| Version::compare_names(a, b));
+        assert_eq!(
+            names,
+            [
+                ".hidden",
+                "a.b",
+                "ab",
+                "app-1.2.0.tar.gz",
+                "app-1.10.2~rc1.tar.gz",
+                "app-1.10.2.tar.bz2",
+                "app-1.10.2.tar.gz",
+                // Letters sort before other symbols
+                "a-b",
+                "file009",
+                "file9",
+                "file10",
+            ]
+        );
+    }
+
+    #[test]
+    fn case_folding() {
+        assert_eq!(natural("README", "main.rs"), Ordering::Less);
+        assert_eq!(
+            natural(&"README".to_lowercase(), "main.rs"),
+            Ordering::Greater
+        );
+        assert_eq!(natural("file2", "file10"), Ordering::Less);
+    }
+
+    #[test]
+    fn ignore_case_entries() {
+        let base = crate::testing::TempDir::new("sort-ignore-case");
+        for name in ["README", "readme", "main.rs", "Zeta"] {
+            std::fs::write(base.join(name), "").unwrap();
+        }
+        let mut entries = std::fs::read_dir(&base)
+            .unwrap()
+            .map(|entry| Entry::try_from(entry.unwrap().path().as_path()).unwrap())
+            .collect::<Vec<_>>();
+        // Case insensitive file systems can't hold names that only differ in case
+        if entries.len() < 4 {
+            return;
+        }
+
+        let mut sorted = |sorter: &dyn SortStrategy| {
+            entries.sort_by(|a, b| sorter.compare(a, b));
+            entries
+                .iter()
+                .map(|entry| entry.file_name().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(sorted(&Natural), ["README", "Zeta", "main.rs", "readme"]);
+        // Names that only differ in case fall back to the inner sorter
+        assert_eq!(
+            sorted(&IgnoreCase(Natural)),
+            ["main.rs", "README", "readme", "Zeta"]
+        );
+        assert_eq!(
+            sorted(&IgnoreCase(Reverse(Natural))),
+            ["main.rs", "readme", "README", "Zeta"]
+        );
+
+        let chain = || "name".parse::<Chain>().unwrap();
+        assert_eq!(
+            sorted(&chain().ignore_case(true)),
+            ["main.rs", "README", "readme", "Zeta"]
+        );
+        assert_eq!(sorted(&chain()), ["README", "Zeta", "main.rs", "readme"]);
+    }
+
+    #[test]
+    fn size_key() {
+        let base = crate::testing::TempDir::new("sort-size-key");
+        for (name, content) in [("small", "1"), ("large", "12345"), ("medium", "123")] {
+            std::fs::write(base.join(name), content).unwrap();
+        }
+        let mut entries = std::fs::read_dir(&base)
+            .unwrap()
+            .map(|entry| Entry::try_from(entry.unwrap().path().as_path()).unwrap())
+            .collect::<Vec<_>>();
+        let mut sorted = |sorter: &dyn SortStrategy| {
+            entries.sort_by(|a, b| sorter.compare(a, b));
+            entries
+                .iter()
+                .map(|entry| entry.file_name().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        // Same order as the Size sorter
+        let size = "size".parse::<Chain>().unwrap();
+        assert_eq!(sorted(&size), ["large", "medium", "small"]);
+        assert_eq!(sorted(&Size::default()), ["large", "medium", "small"]);
+        assert_eq!(size.column(), Size::default().column());
+
+        let reversed = "-size".parse::<Chain>().unwrap();
+        assert_eq!(sorted(&reversed), ["small", "medium", "large"]);
+        assert_eq!(reversed.column(), Some((SortKey::Size, false)));
+    }
+
+    #[test]
+    fn weighted_column() {
+        let unweighted = Weighted::new(Size::default(), Vec::<(&str, i64)>::new()).unwrap();
+        assert_eq!(unweighted.column(), Some((SortKey::Size, true)));
+        let weighted = Weighted::new(Size::default(), [("*.md", -1)]).unwrap();
+        assert_eq!(weighted.column(), None);
+    }
+
+    #[test]
+    fn digit_runs() {
+        assert_eq!(compare_digits(b"20", b"102"), Ordering::Less);
+        assert_eq!(compare_digits(b"007", b"7"), Ordering::Equal);
+        assert_eq!(
+            compare_digits(b"99999999999999999999999", b"100000000000000000000000"),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn missing_policy() {
+        let tie = || Ordering::Equal;
+        assert_eq!(
+            MissingPolicy::Last.compare(Some(1), None, tie),
+            Ordering::Less
+        );
+        assert_eq!(
+            MissingPolicy::Last.compare(None, Some(1), tie),
+            Ordering::Greater
+        );
+        assert_eq!(
+            MissingPolicy::First.compare(Some(1), None, tie),
+            Ordering::Greater
+        );
+        assert_eq!(
+            MissingPolicy::First.compare(None, Some(1), tie),
+            Ordering::Less
+        );
+        assert_eq!(
+            MissingPolicy::Fallback.compare(Some(1), None, || Ordering::Less),
+            Ordering::Less
+        );
+        assert_eq!(
+            MissingPolicy::Last.compare(Some(1), Some(1), || Ordering::Greater),
+            Ordering::Greater
+        );
+    }
 }
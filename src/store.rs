@@ -0,0 +1,209 @@
+use std::{borrow::Cow, ffi::OsString, future::Future, io, pin::Pin, time::SystemTime};
+
+use object_store::{path::Path, ObjectStore, ObjectStoreScheme};
+use url::Url;
+
+use crate::listable::{Kind, Listable};
+
+/// A prefix or object in a bucket, prefixes are listed as directories
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoreEntry {
+    pub name: String,
+    pub kind: Kind,
+    pub size: Option<u64>,
+    pub modified: Option<SystemTime>,
+    /// Objects and prefixes below a prefix, only read up to the depth
+    pub children: Vec<StoreEntry>,
+}
+
+impl Listable for StoreEntry {
+    fn name(&self) -> Cow<'_, str> {
+        Cow::Borrowed(&self.name)
+    }
+
+    fn kind(&self) -> Kind {
+        self.kind
+    }
+
+    fn size(&self) -> Option<u64> {
+        self.size
+    }
+
+    fn modified(&self) -> io::Result<SystemTime> {
+        self.modified
+            .ok_or_else(|| io::ErrorKind::Unsupported.into())
+    }
+
+    fn children(&self) -> Vec<&dyn Listable> {
+        self.children
+            .iter()
+            .map(|child| child as &dyn Listable)
+            .collect()
+    }
+}
+
+/// Whether `path` addresses a cloud bucket, `s3://`, `gs://`, `az://`, or
+/// one of the other schemes `object_store` understands
+pub fn is_store_url(path: &str) -> bool {
+    Url::parse(path)
+        .ok()
+        .and_then(|url| ObjectStoreScheme::parse(&url).ok())
+        .is_some_and(|(scheme, _)| {
+            matches!(
+                scheme,
+                ObjectStoreScheme::AmazonS3
+                    | ObjectStoreScheme::GoogleCloudStorage
+                    | ObjectStoreScheme::MicrosoftAzure
+            )
+        })
+}
+
+/// List a prefix of a bucket, `s3://bucket/logs/2024`
+///
+/// Credentials and regions come from the usual environment variables of each
+/// provider, e.g. `AWS_ACCESS_KEY_ID` and `AWS_REGION`. Prefixes are read
+/// `depth` levels deep, all the way down when it's `None`.
+pub fn list(
+    url: &str,
+    depth: Option<usize>,
+) -> Result<Vec<StoreEntry>, Box<dyn std::error::Error>> {
+    let url = Url::parse(url)?;
+    let options = provider_options(std::env::vars_os());
+    let (store, prefix) = object_store::parse_url_opts(&url, options)?;
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    Ok(runtime.block_on(list_prefix(
+        store.as_ref(),
+        &prefix,
+        depth.unwrap_or(usize::MAX),
+    ))?)
+}
+
+/// Config of every provider from the environment
+///
+/// Config keys are the lowercase environment variable names. Variables of
+/// other programs, and ones that aren't unicode, are skipped.
+fn provider_options<I>(vars: I) -> Vec<(String, String)>
+where
+    I: IntoIterator<Item = (OsString, OsString)>,
+{
+    vars.into_iter()
+        .filter_map(|(key, value)| Some((key.into_string().ok()?, value.into_string().ok()?)))
+        .map(|(key, value)| (key.to_ascii_lowercase(), value))
+        .filter(|(key, _)| {
+            ["aws_", "google_", "azure_"]
+                .iter()
+                .any(|p| key.starts_with(p))
+        })
+        .collect()
+}
+
+/// Objects and prefixes directly below `prefix`, with prefixes filled in
+/// `depth - 1` more levels, sorted by name
+pub fn list_prefix<'a>(
+    store: &'a dyn ObjectStore,
+    prefix: &'a Path,
+    depth: usize,
+) -> Pin<Box<dyn Future<Output = object_store::Result<Vec<StoreEntry>>> + Send + 'a>> {
+    Box::pin(async move {
+        let prefix = (!prefix.as_ref().is_empty()).then_some(prefix);
+        let listed = store.list_with_delimiter(prefix).await?;
+
+        let mut entries = Vec::with_capacity(listed.common_prefixes.len() + listed.objects.len());
+        for nested in &listed.common_prefixes {
+            let children = match depth > 1 {
+                true => list_prefix(store, nested, depth - 1).await?,
+                false => Vec::new(),
+            };
+            entries.push(StoreEntry {
+                name: nested.filename().unwrap_or_default().to_string(),
+                kind: Kind::Dir,
+                size: None,
+                modified: None,
+                children,
+            });
+        }
+        entries.extend(listed.objects.into_iter().map(|object| StoreEntry {
+            name: object.location.filename().unwrap_or_default().to_string(),
+            kind: Kind::File,
+            size: Some(object.size),
+            modified: Some(object.last_modified.into()),
+            children: Vec::new(),
+        }));
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(entries)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use object_store::memory::InMemory;
+
+    use super::*;
+
+    fn names(entries: &[StoreEntry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.name.as_str()).collect()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn environment_options() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let vars = [
+            ("AWS_REGION", "eu-west-1"),
+            ("GOOGLE_SERVICE_ACCOUNT", "account.json"),
+            ("PATH", "/usr/bin"),
+        ]
+        .map(|(key, value)| (OsString::from(key), OsString::from(value)))
+        .into_iter()
+        .chain([
+            (
+                OsString::from("AWS_PROFILE"),
+                OsString::from_vec(vec![0xff]),
+            ),
+            (OsString::from_vec(vec![0xff]), OsString::from("value")),
+        ]);
+        assert_eq!(
+            provider_options(vars),
+            [
+                ("aws_region".to_string(), "eu-west-1".to_string()),
+                (
+                    "google_service_account".to_string(),
+                    "account.json".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn prefixes_as_directories() {
+        let store = InMemory::new();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            for key in ["z.txt", "logs/a.log", "logs/2024/b.log", "data/c.csv"] {
+                store.put(&Path::from(key), "12345".into()).await.unwrap();
+            }
+
+            let root = list_prefix(&store, &Path::default(), 1).await.unwrap();
+            assert_eq!(names(&root), ["data", "logs", "z.txt"]);
+            assert_eq!((root[0].kind, root[0].size), (Kind::Dir, None));
+            assert!(root[1].children.is_empty());
+            assert_eq!((root[2].kind, root[2].size), (Kind::File, Some(5)));
+            assert!(root[2].modified.is_some());
+
+            let logs = list_prefix(&store, &Path::from("logs"), 2).await.unwrap();
+            assert_eq!(names(&logs), ["2024", "a.log"]);
+            assert_eq!(names(&logs[0].children), ["b.log"]);
+        });
+
+        assert!(is_store_url("s3://bucket/logs"));
+        assert!(is_store_url("gs://bucket"));
+        assert!(!is_store_url("sftp://host/path"));
+        assert!(!is_store_url("src/main.rs"));
+    }
+}
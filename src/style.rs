@@ -1,15 +1,40 @@
-use std::ops::Range;
+use std::{
+    borrow::Cow,
+    fs,
+    io::IsTerminal,
+    ops::Range,
+    path::{Component, Path, PathBuf, MAIN_SEPARATOR},
+    str::FromStr,
+    sync::{Arc, OnceLock},
+    time::SystemTime,
+};
 
-use chrono::Datelike;
+use chrono::{
+    format::{Item, StrftimeItems},
+    Datelike,
+};
 use hashbrown::{HashMap, HashSet};
-use owo_colors::{colors::xterm::Gray, OwoColorize, Style};
+use owo_colors::{
+    colors::xterm::{Gray, Rose},
+    OwoColorize, Style,
+};
 
-use crate::{permission::AccessRights, Entry};
+use crate::{
+    clock::{Clock, SystemClock},
+    disk::DirSizes,
+    icons::Icons,
+    known::{KnownFolder, KnownFolders},
+    listable::{Kind, Listable},
+    permission::{current_user, parse_mode_mask, AccessRights},
+    text, Entry,
+};
 
 pub struct GroupStyle {
+    name: String,
     matcher_map: HashMap<&'static str, usize>,
     matchers: Vec<GroupMatch>,
     style: Style,
+    icon: Option<char>,
 }
 
 impl GroupStyle {
@@ -18,6 +43,9 @@ impl GroupStyle {
             match (&mut self.matchers[*index], matcher) {
                 (GroupMatch::Filename(curr), GroupMatch::Filename(new)) => curr.extend(new),
                 (GroupMatch::Extension(curr), GroupMatch::Extension(new)) => curr.extend(new),
+                (GroupMatch::Owner(curr), GroupMatch::Owner(new)) => curr.extend(new),
+                (GroupMatch::Permission(curr), GroupMatch::Permission(new)) => curr.extend(new),
+                (GroupMatch::Children(curr), GroupMatch::Children(new)) => curr.extend(new),
                 _ => unreachable!(),
             }
         } else {
@@ -36,9 +64,19 @@ impl GroupStyle {
         false
     }
 
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     pub fn style(&self) -> Style {
         self.style
     }
+
+    /// Icon for entries in the group that don't have a file name or extension
+    /// specific icon
+    pub fn icon(&self) -> Option<char> {
+        self.icon
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, strum_macros::EnumIs)]
@@ -46,10 +84,20 @@ pub enum GroupMatch {
     Directory,
     Hidden,
     Executable,
+    /// Files whose contents look like binary data, see [`Entry::is_binary`]
+    Binary,
     StartsWith(String),
     EndsWith(String),
     Filename(HashSet<String>),
     Extension(HashSet<String>),
+    /// Entries owned by any of the users
+    Owner(HashSet<String>),
+    /// Entries whose mode, see [`crate::permission::Perms::mode`], has every
+    /// bit of any of the masks set
+    Permission(HashSet<u16>),
+    /// Directories whose [`Entry::child_count`] is within any of the inclusive
+    /// ranges, `None` for no upper bound
+    Children(Vec<(usize, Option<usize>)>),
 }
 
 impl GroupMatch {
@@ -71,6 +119,21 @@ impl GroupMatch {
         )
     }
 
+    pub fn owners<I: IntoIterator<Item = S>, S: AsRef<str>>(owners: I) -> Self {
+        Self::Owner(owners.into_iter().map(|v| v.as_ref().to_string()).collect())
+    }
+
+    /// Match entries with every bit of `mask` set, `0o002` for world writable
+    pub fn permission(mask: u16) -> Self {
+        Self::Permission(HashSet::from([mask]))
+    }
+
+    /// Match directories with `min` to `max` visible children, see
+    /// [`Entry::child_count`]
+    pub fn children(min: usize, max: Option<usize>) -> Self {
+        Self::Children(vec![(min, max)])
+    }
+
     pub fn starts_with<S: ToString>(pattern: S) -> Self {
         Self::StartsWith(pattern.to_string())
     }
@@ -79,6 +142,7 @@ impl GroupMatch {
         Self::EndsWith(pattern.to_string())
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn as_ref(&self) -> &'static str {
         match self {
             Self::Filename(_) => "Filename",
@@ -86,8 +150,12 @@ impl GroupMatch {
             Self::Directory => "Directory",
             Self::Hidden => "Hidden",
             Self::Executable => "Executable",
+            Self::Binary => "Binary",
             Self::StartsWith(_) => "StartsWith",
             Self::EndsWith(_) => "EndsWith",
+            Self::Owner(_) => "Owner",
+            Self::Permission(_) => "Permission",
+            Self::Children(_) => "Children",
         }
     }
 
@@ -100,17 +168,526 @@ impl GroupMatch {
             Self::EndsWith(ew) => entry.file_name().ends_with(ew),
             Self::Hidden => entry.is_hidden(),
             Self::Executable => entry.is_executable(),
+            Self::Binary => entry.is_binary(),
+            Self::Owner(owners) => owners.contains(&entry.permissions().user().name),
+            Self::Permission(masks) => {
+                let mode = entry.permissions().mode();
+                masks.iter().any(|mask| mode & mask == *mask)
+            }
+            Self::Children(ranges) => entry.child_count().is_some_and(|count| {
+                ranges
+                    .iter()
+                    .any(|(min, max)| count >= *min && max.is_none_or(|max| count <= max))
+            }),
         }
     }
 }
 
+/// A group defined in the `[theme]` table of the config file
+///
+/// ```toml
+/// [theme.ROOT]
+/// fg = "red"
+/// bold = true
+/// owner = ["root"]
+///
+/// [theme.WORLD_WRITABLE]
+/// bg = "yellow"
+/// permission = "o+w"
+///
+/// [theme.CROWDED]
+/// fg = "red"
+/// children = [">=500"]
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThemeGroup {
+    pub name: String,
+    pub matchers: Vec<GroupMatch>,
+    pub style: Style,
+    pub icon: Option<char>,
+}
+
+impl ThemeGroup {
+    pub fn from_toml(name: &str, table: &toml::Table) -> Result<Self, String> {
+        let mut group = Self {
+            name: name.to_string(),
+            matchers: Vec::new(),
+            style: Style::default(),
+            icon: None,
+        };
+
+        let flag = |key: &str, value: &toml::Value| {
+            value
+                .as_bool()
+                .ok_or_else(|| format!("'theme.{name}.{key}' must be a boolean"))
+        };
+        let text = |key: &str, value: &toml::Value| {
+            value
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| format!("'theme.{name}.{key}' must be a string"))
+        };
+        let list = |key: &str, value: &toml::Value| match value {
+            toml::Value::String(value) => Ok(vec![value.clone()]),
+            toml::Value::Array(values) => values
+                .iter()
+                .map(|v| text(key, v))
+                .collect::<Result<Vec<_>, _>>(),
+            _ => Err(format!(
+                "'theme.{name}.{key}' must be a string or array of strings"
+            )),
+        };
+        let color = |key: &str, value: &toml::Value| {
+            text(key, value)?
+                .parse::<owo_colors::DynColors>()
+                .map_err(|_| format!("'theme.{name}.{key}' is not a valid color"))
+        };
+
+        for (key, value) in table {
+            match key.as_str() {
+                "fg" => group.style = group.style.color(color(key, value)?),
+                "bg" => group.style = group.style.on_color(color(key, value)?),
+                "bold" if flag(key, value)? => group.style = group.style.bold(),
+                "dimmed" if flag(key, value)? => group.style = group.style.dimmed(),
+                "italic" if flag(key, value)? => group.style = group.style.italic(),
+                "underline" if flag(key, value)? => group.style = group.style.underline(),
+                "bold" | "dimmed" | "italic" | "underline" => {}
+                "icon" => {
+                    let icon = text(key, value)?;
+                    let mut chars = icon.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(icon), None) => group.icon = Some(icon),
+                        _ => return Err(format!("'theme.{name}.icon' must be a single character")),
+                    }
+                }
+                "directory" | "hidden" | "executable" | "binary" => {
+                    if flag(key, value)? {
+                        group.matchers.push(match key.as_str() {
+                            "directory" => GroupMatch::Directory,
+                            "hidden" => GroupMatch::Hidden,
+                            "executable" => GroupMatch::Executable,
+                            _ => GroupMatch::Binary,
+                        });
+                    }
+                }
+                "starts_with" => group
+                    .matchers
+                    .push(GroupMatch::starts_with(text(key, value)?)),
+                "ends_with" => group
+                    .matchers
+                    .push(GroupMatch::ends_with(text(key, value)?)),
+                "filenames" => group
+                    .matchers
+                    .push(GroupMatch::filenames(list(key, value)?)),
+                "extensions" => group
+                    .matchers
+                    .push(GroupMatch::extensions(list(key, value)?)),
+                "owner" => group.matchers.push(GroupMatch::owners(list(key, value)?)),
+                "children" => {
+                    let ranges = list(key, value)?
+                        .iter()
+                        .map(|spec| parse_count_range(spec))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    group.matchers.push(GroupMatch::Children(ranges));
+                }
+                "permission" => {
+                    let masks = list(key, value)?
+                        .iter()
+                        .map(|spec| parse_mode_mask(spec))
+                        .collect::<Result<HashSet<_>, _>>()?;
+                    group.matchers.push(GroupMatch::Permission(masks));
+                }
+                other => return Err(format!("unknown key 'theme.{name}.{other}'")),
+            }
+        }
+
+        Ok(group)
+    }
+}
+
+impl ThemeGroup {
+    /// Dimmed empty directories and highlighted directories with at least
+    /// `huge` visible children, matched before the default directory style
+    pub fn child_counts(huge: usize) -> Vec<Self> {
+        vec![
+            Self {
+                name: "EMPTY_DIR".to_string(),
+                matchers: vec![GroupMatch::children(0, Some(0))],
+                style: Style::default().blue().dimmed(),
+                icon: None,
+            },
+            Self {
+                name: "HUGE_DIR".to_string(),
+                matchers: vec![GroupMatch::children(huge, None)],
+                style: Style::default().blue().bold().underline(),
+                icon: None,
+            },
+        ]
+    }
+}
+
+/// Parse a range of child counts, `0`, `>=1000`, `<10`, or `10..100` with
+/// the end excluded like rust ranges
+fn parse_count_range(spec: &str) -> Result<(usize, Option<usize>), String> {
+    let spec = spec.trim();
+    let count = |value: &str| {
+        value
+            .trim()
+            .parse::<usize>()
+            .map_err(|_| format!("invalid child count range '{spec}'"))
+    };
+    if let Some(min) = spec.strip_prefix(">=") {
+        Ok((count(min)?, None))
+    } else if let Some(min) = spec.strip_prefix('>') {
+        Ok((count(min)? + 1, None))
+    } else if let Some(max) = spec.strip_prefix("<=") {
+        Ok((0, Some(count(max)?)))
+    } else if let Some(max) = spec.strip_prefix('<') {
+        let max = count(max)?;
+        match max.checked_sub(1) {
+            Some(max) => Ok((0, Some(max))),
+            None => Err(format!("child count range '{spec}' is empty")),
+        }
+    } else if let Some((min, max)) = spec.split_once("..") {
+        let (min, max) = (count(min)?, count(max)?);
+        match max > min {
+            true => Ok((min, Some(max - 1))),
+            false => Err(format!("child count range '{spec}' is empty")),
+        }
+    } else {
+        let exact = count(spec)?;
+        Ok((exact, Some(exact)))
+    }
+}
+
+/// When output is styled with colors and other escape sequences
+///
+/// The library defaults to always so output written to buffers keeps its
+/// styling, the command line defaults to auto. Auto also turns colors off when
+/// `NO_COLOR` is set or the terminal is `dumb`, see [`ColorMode::plain_env`].
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Only when stdout is a terminal
+    Auto,
+    #[default]
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    /// Whether output should be styled
+    pub fn enabled(&self) -> bool {
+        match self {
+            Self::Auto => !Self::plain_env() && std::io::stdout().is_terminal(),
+            Self::Always => true,
+            Self::Never => false,
+        }
+    }
+
+    /// Whether the environment asks for plain output, `NO_COLOR` is set to
+    /// anything but an empty string or `TERM` is `dumb`
+    pub fn plain_env() -> bool {
+        std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+            || std::env::var_os("TERM").is_some_and(|term| term == "dumb")
+    }
+}
+
+impl FromStr for ColorMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            other => Err(format!(
+                "unknown color mode '{other}', expected auto, always, or never"
+            )),
+        }
+    }
+}
+
+/// What the size column shows for symbolic links
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymlinkSize {
+    /// A marker instead of a size, `^` unless themed
+    #[default]
+    Marker,
+    /// Size of the file the link points to, the marker when it can't be resolved
+    Target,
+    /// Length of the link itself
+    Link,
+}
+
+impl FromStr for SymlinkSize {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "marker" => Ok(Self::Marker),
+            "target" => Ok(Self::Target),
+            "link" => Ok(Self::Link),
+            other => Err(format!(
+                "unknown symlink size '{other}', expected marker, target, or link"
+            )),
+        }
+    }
+}
+
+/// How file names are displayed
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathDisplay {
+    /// Only the file name
+    #[default]
+    Name,
+    /// Path from the root of the listing, e.g. `src/format/mod.rs`
+    Relative,
+    /// Absolute canonical path, symlinks are shown themselves and not resolved
+    Absolute,
+}
+
+impl FromStr for PathDisplay {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "name" => Ok(Self::Name),
+            "relative" => Ok(Self::Relative),
+            "absolute" => Ok(Self::Absolute),
+            other => Err(format!(
+                "unknown path display '{other}', expected name, relative, or absolute"
+            )),
+        }
+    }
+}
+
+/// How timestamps are formatted in the date columns
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub enum TimeStyle {
+    /// `%e %b %H:%M` for this year and `%e %b  %Y` for older dates
+    #[default]
+    Default,
+    /// `%m-%d %H:%M` for this year and `%Y-%m-%d` for older dates
+    Iso,
+    /// `%Y-%m-%d %H:%M`
+    LongIso,
+    /// `%Y-%m-%d %H:%M:%S.%f %z`
+    FullIso,
+    /// Time since the date, `3 days ago`
+    Relative,
+    /// Custom strftime format
+    Custom(String),
+}
+
+impl TimeStyle {
+    pub fn format(&self, time: SystemTime) -> String {
+        self.format_at(time, SystemTime::now())
+    }
+
+    /// Format `time` as if the current time were `now`, which decides whether
+    /// the year is shown and what relative times are relative to
+    pub fn format_at(&self, time: SystemTime, now: SystemTime) -> String {
+        let date = chrono::DateTime::<chrono::Local>::from(time);
+        let this_year = date.year() == chrono::DateTime::<chrono::Local>::from(now).year();
+        match self {
+            Self::Default if this_year => date.format("%e %b %H:%M").to_string(),
+            Self::Default => date.format("%e %b  %Y").to_string(),
+            Self::Iso if this_year => date.format("%m-%d %H:%M").to_string(),
+            Self::Iso => date.format("%Y-%m-%d").to_string(),
+            Self::LongIso => date.format("%Y-%m-%d %H:%M").to_string(),
+            Self::FullIso => date.format("%Y-%m-%d %H:%M:%S%.9f %z").to_string(),
+            Self::Relative => relative_time(time, now),
+            Self::Custom(format) => date.format(format).to_string(),
+        }
+    }
+
+    /// Widest a formatted date is expected to be
+    pub fn width(&self) -> usize {
+        match self {
+            Self::Default => 12,
+            Self::Iso => 11,
+            Self::LongIso => 16,
+            Self::FullIso => 35,
+            Self::Relative => 14,
+            Self::Custom(_) => text::width(&self.format(SystemTime::now())),
+        }
+    }
+}
+
+impl FromStr for TimeStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "default" => Ok(Self::Default),
+            "iso" => Ok(Self::Iso),
+            "long-iso" => Ok(Self::LongIso),
+            "full-iso" => Ok(Self::FullIso),
+            "relative" => Ok(Self::Relative),
+            custom if custom.starts_with('+') || custom.contains('%') => {
+                let format = custom.strip_prefix('+').unwrap_or(custom);
+                if StrftimeItems::new(format).any(|item| item == Item::Error) {
+                    return Err(format!("invalid time format '{format}'"));
+                }
+                Ok(Self::Custom(format.to_string()))
+            }
+            other => Err(format!(
+                "unknown time style '{other}', expected default, iso, long-iso, full-iso, relative, or +FORMAT"
+            )),
+        }
+    }
+}
+
+/// Largest whole unit of time since `time`, `3 days ago` or `in 2 hours`
+fn relative_time(time: SystemTime, now: SystemTime) -> String {
+    let (seconds, future) = match now.duration_since(time) {
+        Ok(elapsed) => (elapsed.as_secs(), false),
+        Err(err) => (err.duration().as_secs(), true),
+    };
+    if seconds < 10 {
+        return "just now".to_string();
+    }
+
+    let (amount, unit) = [
+        (365 * 24 * 60 * 60, "year"),
+        (30 * 24 * 60 * 60, "month"),
+        (7 * 24 * 60 * 60, "week"),
+        (24 * 60 * 60, "day"),
+        (60 * 60, "hour"),
+        (60, "minute"),
+        (1, "second"),
+    ]
+    .into_iter()
+    .find(|(length, _)| seconds >= *length)
+    .map(|(length, unit)| (seconds / length, unit))
+    .unwrap_or((seconds, "second"));
+
+    let plural = if amount == 1 { "" } else { "s" };
+    match future {
+        true => format!("in {amount} {unit}{plural}"),
+        false => format!("{amount} {unit}{plural} ago"),
+    }
+}
+
 #[derive(Default)]
 pub struct Colorizer {
     groups: HashMap<String, usize>,
     group_styles: Vec<GroupStyle>,
+    slash: bool,
+    indicators: bool,
+    icons: Option<Icons>,
+    hyperlinks: bool,
+    known_folders: Option<KnownFolders>,
+    accessible: bool,
+    symlink_size: SymlinkSize,
+    symlink_marker: Option<String>,
+    size_units: SizeUnits,
+    time_style: TimeStyle,
+    path_display: PathDisplay,
+    root: PathBuf,
+    color_mode: ColorMode,
+    colored: OnceLock<bool>,
+    clock: Option<Arc<dyn Clock>>,
+    dir_sizes: Option<DirSizes>,
 }
 
 impl Colorizer {
+    /// Theme used by the `xf` command line
+    pub fn default_theme() -> Self {
+        Self::default().builtin_groups()
+    }
+
+    /// Default theme with the user's groups matched before the built in ones
+    pub fn themed<I: IntoIterator<Item = ThemeGroup>>(groups: I) -> Self {
+        let mut colorizer = Self::default();
+        for group in groups {
+            colorizer = colorizer.group(&group.name, group.matchers, group.style);
+            if let Some(icon) = group.icon {
+                colorizer = colorizer.group_icon(&group.name, icon);
+            }
+        }
+        colorizer.builtin_groups()
+    }
+
+    fn builtin_groups(self) -> Self {
+        self.group("DIR", [GroupMatch::Directory], Style::default().blue())
+            .group(
+                "HIDDEN",
+                [
+                    GroupMatch::Hidden,
+                    GroupMatch::starts_with("."),
+                    GroupMatch::extensions(["lock"]),
+                ],
+                Style::default().fg::<Gray>(),
+            )
+            .group(
+                "IMAGE",
+                [GroupMatch::extensions([
+                    "jpg", "png", "gif", "webp", "avif", "ico",
+                ])],
+                Style::default().magenta(),
+            )
+            .group(
+                "CONFIG",
+                [GroupMatch::filenames(["Cargo.toml", "config.toml"])],
+                Style::default().yellow().underline(),
+            )
+            .group(
+                "EXE",
+                [
+                    GroupMatch::Executable,
+                    GroupMatch::extensions(["exe", "sh"]),
+                ],
+                Style::default().green(),
+            )
+            .group_icon("IMAGE", '\u{f1c5}')
+            .group_icon("EXE", '\u{f489}')
+    }
+
+    /// Append a `/` to directory names
+    pub fn slash(mut self, slash: bool) -> Self {
+        self.slash = slash;
+        self
+    }
+
+    /// When output is styled, see [`ColorMode`]
+    pub fn color_mode(mut self, color_mode: ColorMode) -> Self {
+        self.color_mode = color_mode;
+        self.colored = OnceLock::new();
+        self
+    }
+
+    /// Whether output should be styled under the [`ColorMode`]
+    ///
+    /// Decided once, every styled helper renders plain text when it's off.
+    pub fn colored(&self) -> bool {
+        *self.colored.get_or_init(|| self.color_mode.enabled())
+    }
+
+    /// `text` in the `style`, or as is when output isn't [`Colorizer::colored`]
+    pub fn paint<D: std::fmt::Display>(&self, text: D, style: Style) -> String {
+        match self.colored() {
+            true => text.style(style).to_string(),
+            false => text.to_string(),
+        }
+    }
+
+    /// Flag character of the permissions column, see [`ModeChar`]
+    fn mode_char_paint(&self, set: bool, mode: char, style: Style) -> String {
+        match set {
+            true => self.paint(mode, style),
+            false => self.paint(bool::DASH, Style::new().dimmed()),
+        }
+    }
+
+    /// Append a type indicator to names like `ls -F`, `/` for directories,
+    /// `*` for executables, `@` for symbolic links, `|` for pipes, and `=`
+    /// for sockets
+    pub fn indicators(mut self, indicators: bool) -> Self {
+        self.indicators = indicators;
+        self
+    }
+
     pub fn group<S: AsRef<str>, I: IntoIterator<Item = GroupMatch>>(
         mut self,
         name: S,
@@ -127,6 +704,9 @@ impl Colorizer {
                 match (m.get_mut(matcher.as_ref()).unwrap(), matcher) {
                     (GroupMatch::Filename(curr), GroupMatch::Filename(new)) => curr.extend(new),
                     (GroupMatch::Extension(curr), GroupMatch::Extension(new)) => curr.extend(new),
+                    (GroupMatch::Owner(curr), GroupMatch::Owner(new)) => curr.extend(new),
+                    (GroupMatch::Permission(curr), GroupMatch::Permission(new)) => curr.extend(new),
+                    (GroupMatch::Children(curr), GroupMatch::Children(new)) => curr.extend(new),
                     _ => unreachable!(),
                 }
             } else {
@@ -136,45 +716,340 @@ impl Colorizer {
 
         let m = m.into_iter().collect::<Vec<_>>();
         self.group_styles.push(GroupStyle {
+            name: name.as_ref().to_string(),
             matcher_map: m.iter().enumerate().map(|(i, (k, _))| (*k, i)).collect(),
             matchers: m.into_iter().map(|(_, v)| v).collect(),
             style,
+            icon: None,
         });
         self
     }
 
+    /// Set the icon of a group, see [`GroupStyle::icon`]
+    pub fn group_icon<S: AsRef<str>>(mut self, name: S, icon: char) -> Self {
+        if let Some(index) = self.groups.get(name.as_ref()) {
+            self.group_styles[*index].icon = Some(icon);
+        }
+        self
+    }
+
+    /// Give well known user folders their own icon, style, and label, `None`
+    /// disables them
+    pub fn known_folders(mut self, known_folders: Option<KnownFolders>) -> Self {
+        self.known_folders = known_folders;
+        self
+    }
+
+    /// Spell out what is otherwise only conveyed by color or glyphs for screen
+    /// readers
+    ///
+    /// Names are prefixed with their type, `[DIR]`, sizes always include a unit,
+    /// and trees use indentation with depth markers instead of box drawing.
+    pub fn accessible(mut self, accessible: bool) -> Self {
+        self.accessible = accessible;
+        self
+    }
+
+    pub fn is_accessible(&self) -> bool {
+        self.accessible
+    }
+
+    /// What the size column shows for symbolic links
+    pub fn symlink_size(mut self, symlink_size: SymlinkSize) -> Self {
+        self.symlink_size = symlink_size;
+        self
+    }
+
+    /// Text shown in the size column for symbolic links with
+    /// [`SymlinkSize::Marker`], defaults to `^`
+    pub fn symlink_marker<S: ToString>(mut self, marker: S) -> Self {
+        self.symlink_marker = Some(marker.to_string());
+        self
+    }
+
+    /// Units the size columns are shown in, defaults to [`SizeUnits::Binary`]
+    pub fn size_units(mut self, size_units: SizeUnits) -> Self {
+        self.size_units = size_units;
+        self
+    }
+
+    /// Show the total size of everything below a directory in the size
+    /// column instead of the size of the directory itself, see [`DirSizes`]
+    pub fn dir_sizes(mut self, dir_sizes: bool) -> Self {
+        self.dir_sizes = dir_sizes.then(DirSizes::new);
+        self
+    }
+
+    /// Clock dates are formatted against, the system clock by default
+    pub fn clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// Current time of the [`Colorizer::clock`]
+    pub fn now(&self) -> SystemTime {
+        match self.clock.as_ref() {
+            Some(clock) => clock.now(),
+            None => SystemClock.now(),
+        }
+    }
+
+    /// Format of the date columns, defaults to [`TimeStyle::Default`]
+    pub fn time_style(mut self, time_style: TimeStyle) -> Self {
+        self.time_style = time_style;
+        self
+    }
+
+    /// Width of the date column for the [`TimeStyle`]
+    pub fn date_width(&self) -> usize {
+        self.time_style.width()
+    }
+
+    /// Size in the units set by [`Colorizer::size_units`], spelled out in
+    /// accessible mode
+    pub fn size_text(&self, value: u64) -> String {
+        match self.accessible {
+            false => humansize_in(value, self.size_units),
+            true => humansize_labeled_in(value, self.size_units),
+        }
+    }
+
+    /// Show names as paths relative to `root`, or as absolute paths
+    pub fn path_display<P: Into<PathBuf>>(mut self, path_display: PathDisplay, root: P) -> Self {
+        self.path_display = path_display;
+        self.root = root.into();
+        self
+    }
+
+    /// Unstyled name of the entry as set by [`Colorizer::path_display`]
+    pub fn display_name<'a>(&self, entry: &'a Entry) -> Cow<'a, str> {
+        match self.path_display {
+            PathDisplay::Name => Cow::Borrowed(entry.file_name()),
+            PathDisplay::Relative => match entry.path().strip_prefix(&self.root) {
+                Ok(relative) => relative.to_string_lossy(),
+                Err(_) => entry.path().to_string_lossy(),
+            },
+            // Only the parent is resolved so links aren't replaced by their target
+            PathDisplay::Absolute => match entry.path().parent().map(fs::canonicalize) {
+                Some(Ok(parent)) => Cow::Owned(
+                    parent
+                        .join(entry.file_name())
+                        .to_string_lossy()
+                        .into_owned(),
+                ),
+                _ => entry.path().to_string_lossy(),
+            },
+        }
+    }
+
+    /// Wrap file names in OSC 8 hyperlinks to their `file://` url
+    ///
+    /// The escape sequences take up no columns so [`Colorizer::file_width`] is
+    /// unchanged.
+    pub fn hyperlinks(mut self, hyperlinks: bool) -> Self {
+        self.hyperlinks = hyperlinks;
+        self
+    }
+
+    /// Prefix file names with icons, `None` disables them
+    pub fn icons(mut self, icons: Option<Icons>) -> Self {
+        self.icons = icons;
+        self
+    }
+
     pub fn add<S: AsRef<str>>(mut self, name: S, matcher: GroupMatch) -> Self {
         if let Some(index) = self.groups.get(&name.as_ref().to_string()) {
             self.group_styles[*index].add_matcher(matcher);
         }
         self
     }
+
+    /// Style of a group by name, `None` when there is no such group
+    pub fn group_style(&self, name: &str) -> Option<Style> {
+        self.groups
+            .get(name)
+            .map(|index| self.group_styles[*index].style())
+    }
+
+    /// First group, in registration order, that matches the entry
+    pub fn group_of(&self, entry: &Entry) -> Option<&GroupStyle> {
+        self.group_styles.iter().find(|group| group.matches(entry))
+    }
+
+    /// Name of the group the entry is classified as
+    ///
+    /// This is the same lookup used to pick the entry's style, so it can be used
+    /// to render entries without relying on ANSI output.
+    pub fn classify(&self, entry: &Entry) -> Option<&str> {
+        self.group_of(entry).map(GroupStyle::name)
+    }
+}
+
+/// Whether sizes are shown in powers of 1024 or 1000
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeUnits {
+    /// `K`, `M`, and `G` are multiples of 1024
+    #[default]
+    Binary,
+    /// SI units, `k`, `M`, and `G` are multiples of 1000
+    Si,
+}
+
+impl SizeUnits {
+    fn base(&self) -> f64 {
+        match self {
+            Self::Binary => 1_024.0,
+            Self::Si => 1_000.0,
+        }
+    }
+
+    fn suffixes(&self) -> [&'static str; 5] {
+        match self {
+            Self::Binary => ["K", "M", "G", "T", "P"],
+            Self::Si => ["k", "M", "G", "T", "P"],
+        }
+    }
 }
 
 #[inline]
 pub fn humansize(value: u64) -> String {
-    match value {
-        0 => "-".to_string(),
-        // Bytes
-        1..1_024 => value.to_string(),
-        // Kilobytes
-        1_024..1_048_576 => format!("{}K", (value as f32 / 1_024.0).round() as u8),
-        // Megabytes
-        1_048_576..1_073_741_824 => format!("{}M", (value as f32 / 1_048_576.0).round() as u8),
-        // Gigbytes
-        1_073_741_824..1_099_511_627_776 => {
-            format!("{}G", (value as f32 / 1_099_511_627_776.0).round() as u8)
-        }
-        // Terabytes
-        1_099_511_627_776..1_125_899_906_842_624 => {
-            format!("{}T", (value as f32 / 1_099_511_627_776.0).round() as u8)
-        }
-        // Petabytes
-        _ => format!(
-            "{}P",
-            (value as f32 / 1_125_899_906_842_624.0).round() as u8
-        ),
+    humansize_in(value, SizeUnits::Binary)
+}
+
+/// Same as [`humansize`] in the given units
+pub fn humansize_in(value: u64, units: SizeUnits) -> String {
+    let base = units.base();
+    if value == 0 {
+        return "-".to_string();
+    } else if (value as f64) < base {
+        return value.to_string();
+    }
+
+    let mut size = value as f64;
+    let mut suffix = "";
+    for unit in units.suffixes() {
+        if size < base {
+            break;
+        }
+        size /= base;
+        suffix = unit;
+    }
+    format!("{}{suffix}", size.round() as u16)
+}
+
+/// Like [`humansize`] but always with a unit, `0 B`, `512 B`, `4 KB`
+pub fn humansize_labeled(value: u64) -> String {
+    humansize_labeled_in(value, SizeUnits::Binary)
+}
+
+/// Same as [`humansize_labeled`] in the given units, `4 kB` for SI units
+pub fn humansize_labeled_in(value: u64, units: SizeUnits) -> String {
+    match humansize_in(value, units) {
+        size if (value as f64) < units.base() => {
+            format!("{} B", if value == 0 { "0" } else { &size })
+        }
+        size => {
+            let (number, unit) = size.split_at(size.len() - 1);
+            format!("{number} {unit}B")
+        }
+    }
+}
+
+/// A single segment of a rendered path along with the path it points to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Crumb {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// Split a path into breadcrumb segments starting at its root
+///
+/// Paths inside of the home directory start with a `~` segment.
+pub fn breadcrumbs(path: &Path) -> Vec<Crumb> {
+    let mut crumbs = Vec::new();
+    let mut rest = path;
+
+    if let Some(home) = dirs::home_dir() {
+        if let Ok(stripped) = path.strip_prefix(&home) {
+            crumbs.push(Crumb {
+                name: "~".to_string(),
+                path: home,
+            });
+            rest = stripped;
+        }
+    }
+
+    let mut current = crumbs.last().map(|c| c.path.clone()).unwrap_or_default();
+    for component in rest.components() {
+        current.push(component);
+        match component {
+            // Keep the root attached to a drive prefix, `C:\`
+            Component::RootDir if !crumbs.is_empty() => {
+                let last = crumbs.last_mut().unwrap();
+                last.name.push(MAIN_SEPARATOR);
+                last.path = current.clone();
+            }
+            Component::RootDir => crumbs.push(Crumb {
+                name: MAIN_SEPARATOR.to_string(),
+                path: current.clone(),
+            }),
+            other => crumbs.push(Crumb {
+                name: other.as_os_str().to_string_lossy().to_string(),
+                path: current.clone(),
+            }),
+        }
+    }
+
+    crumbs
+}
+
+/// Type indicator appended by [`Colorizer::indicators`]
+fn indicator(entry: &Entry) -> &'static str {
+    let file_type = entry.metadata().file_type();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        if file_type.is_fifo() {
+            return "|";
+        } else if file_type.is_socket() {
+            return "=";
+        }
+    }
+    if entry.is_dir() {
+        "/"
+    } else if file_type.is_symlink() {
+        "@"
+    } else if entry.is_executable() {
+        "*"
+    } else {
+        ""
+    }
+}
+
+/// Wrap text in an OSC 8 hyperlink escape sequence
+pub fn hyperlink(url: &str, text: &str) -> String {
+    format!("\x1b]8;;{url}\x1b\\{text}\x1b]8;;\x1b\\")
+}
+
+/// Percent encoded `file://` url of an absolute version of the path
+pub fn file_url(path: &Path) -> String {
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let path = path.to_string_lossy().replace('\\', "/");
+
+    let mut url = String::from("file://");
+    if !path.starts_with('/') {
+        // Windows drive paths, `file:///C:/Users`
+        url.push('/');
+    }
+    for b in path.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' | b':' => {
+                url.push(b as char)
+            }
+            _ => url.push_str(&format!("%{b:02X}")),
+        }
     }
+    url
 }
 
 pub trait Spacer {
@@ -216,81 +1091,244 @@ impl ModeChar for bool {
 
 impl Colorizer {
     pub fn file(&self, entry: &Entry) -> String {
-        let mut style = Style::default();
-        for m in self.group_styles.iter() {
-            if m.matches(entry) {
-                style = m.style();
-                break;
+        let group = self.group_of(entry);
+        let known = self.known_folder(entry);
+        let style = match known {
+            Some((known, _)) => known.get_style(),
+            None => group.map(GroupStyle::style).unwrap_or_default(),
+        };
+
+        let icon = match self.icons.as_ref() {
+            Some(icons) => {
+                let icon = known
+                    .map(|(_, folder)| folder.icon())
+                    .or_else(|| icons.specific(entry))
+                    .or_else(|| group.and_then(GroupStyle::icon))
+                    .unwrap_or_else(|| icons.fallback(entry));
+                format!("{} ", self.paint(icon, style))
             }
+            None => String::new(),
+        };
+
+        let name = self.paint(self.display_name(entry), style);
+        let name = if self.hyperlinks && self.colored() {
+            hyperlink(&file_url(entry.path()), &name)
+        } else {
+            name
+        };
+
+        let label = known
+            .and_then(|(known, folder)| known.entry_label(entry, folder))
+            .map(|label| self.paint(format!(" ({label})"), Style::new().dimmed()))
+            .unwrap_or_default();
+
+        format!(
+            "{}{icon}{name}{}{label}",
+            self.type_tag(entry),
+            self.suffix(entry)
+        )
+    }
+
+    /// ` -> target` of a symbolic link styled by the group of its target, or
+    /// red when the link is broken
+    pub fn link_target(&self, entry: &Entry) -> Option<String> {
+        let target = entry.link_target()?;
+        let text = target.display().to_string();
+        let text = if entry.is_broken_link() {
+            self.paint(text, Style::new().red().dimmed())
+        } else {
+            let resolved = entry.path().parent().unwrap_or(Path::new("")).join(&target);
+            let style = Entry::try_from(resolved.as_path())
+                .ok()
+                .and_then(|target| self.group_of(&target).map(GroupStyle::style))
+                .unwrap_or_default();
+            self.paint(text, style)
+        };
+        Some(format!(
+            " {} {text}",
+            self.paint("->", Style::new().dimmed())
+        ))
+    }
+
+    /// Number of columns taken up by [`Colorizer::link_target`]
+    pub fn link_target_width(&self, entry: &Entry) -> usize {
+        entry
+            .link_target()
+            .map(|target| text::width(&target.display().to_string()) + 4)
+            .unwrap_or_default()
+    }
+
+    /// Spelled out type of the entry in accessible mode, `[DIR] `
+    fn type_tag(&self, entry: &Entry) -> &'static str {
+        if !self.accessible {
+            ""
+        } else if entry.metadata().is_symlink() {
+            "[LINK] "
+        } else if entry.is_dir() {
+            "[DIR] "
+        } else if entry.is_executable() {
+            "[EXE] "
+        } else {
+            "[FILE] "
         }
+    }
 
-        entry.file_name().style(style).to_string()
+    fn known_folder(&self, entry: &Entry) -> Option<(&KnownFolders, KnownFolder)> {
+        let known = self.known_folders.as_ref()?;
+        known.of(entry).map(|folder| (known, folder))
     }
 
-    pub fn file_size(&self, entry: &Entry) -> String {
-        if entry.metadata().is_symlink() {
-            format!("   {}", '^'.fg::<Gray>())
+    fn suffix(&self, entry: &Entry) -> &'static str {
+        if self.indicators {
+            return indicator(entry);
+        }
+        if self.slash && entry.is_dir() {
+            "/"
         } else {
-            let hs = humansize(entry.metadata().len());
-            format!(
-                "{}{}",
-                (0..4usize.saturating_sub(hs.len())).spacer(),
-                hs.fg::<Gray>()
-            )
+            ""
+        }
+    }
+
+    /// Number of columns the rendered file name will take up
+    pub fn file_width(&self, entry: &Entry) -> usize {
+        // Icons are a single column glyph followed by a space
+        let icon = if self.icons.is_some() { 2 } else { 0 };
+        let label = self
+            .known_folder(entry)
+            .and_then(|(known, folder)| known.entry_label(entry, folder))
+            .map(|label| text::width(label) + 3)
+            .unwrap_or_default();
+        self.type_tag(entry).len()
+            + icon
+            + text::width(&self.display_name(entry))
+            + self.suffix(entry).len()
+            + label
+    }
+
+    /// Unstyled text of the size column for an entry
+    pub fn file_size_text(&self, entry: &Entry) -> String {
+        if let Some(sizes) = self.dir_sizes.as_ref().filter(|_| entry.is_dir()) {
+            return self.size_text(sizes.size(entry.path()));
+        }
+        let size = match (entry.metadata().is_symlink(), self.symlink_size) {
+            (false, _) | (true, SymlinkSize::Link) => Some(entry.metadata().len()),
+            (true, SymlinkSize::Target) => fs::metadata(entry.path()).ok().map(|meta| meta.len()),
+            (true, SymlinkSize::Marker) => None,
+        };
+        match (size, self.accessible) {
+            (Some(size), _) => self.size_text(size),
+            (None, false) => self.symlink_marker.as_deref().unwrap_or("^").to_string(),
+            (None, true) => "link".to_string(),
+        }
+    }
+
+    /// Width of the size column in the list and grid formats
+    pub fn size_width(&self) -> usize {
+        if self.accessible {
+            7
+        } else {
+            4
+        }
+    }
+
+    /// Render a path as styled breadcrumb segments, `~/projects/xf`
+    ///
+    /// Each segment links to its directory when hyperlinks are enabled.
+    pub fn breadcrumb(&self, path: &Path) -> String {
+        let mut result = String::new();
+        let mut previous: Option<String> = None;
+        for crumb in breadcrumbs(path) {
+            if previous.is_some_and(|p| !p.ends_with(MAIN_SEPARATOR)) {
+                result.push_str(&self.paint(MAIN_SEPARATOR, Style::new().dimmed()));
+            }
+            let name = self.paint(&crumb.name, Style::new().fg::<Rose>());
+            if self.hyperlinks && self.colored() {
+                result.push_str(&hyperlink(&file_url(&crumb.path), &name));
+            } else {
+                result.push_str(&name);
+            }
+            previous = Some(crumb.name);
         }
+        result
+    }
+
+    pub fn file_size(&self, entry: &Entry) -> String {
+        self.file_size_aligned(entry, self.size_width())
+    }
+
+    /// Size column right aligned to `width` characters
+    pub fn file_size_aligned(&self, entry: &Entry, width: usize) -> String {
+        let hs = self.file_size_text(entry);
+        format!(
+            "{}{}",
+            (0..width.saturating_sub(text::width(&hs))).spacer(),
+            self.paint(hs, Style::new().fg::<Gray>())
+        )
+    }
+
+    /// Timestamp in the [`TimeStyle`], `-` when the platform doesn't report it
+    pub fn date_text(&self, time: std::io::Result<SystemTime>) -> String {
+        time.map(|time| self.time_style.format_at(time, self.now()))
+            .unwrap_or("-".to_string())
+    }
+
+    /// Unstyled text of the modified date column for an entry
+    pub fn date_modified_text(&self, entry: &Entry) -> String {
+        self.date_text(entry.metadata().modified())
     }
 
     pub fn date_modified(&self, entry: &Entry) -> String {
-        let date = entry
-            .metadata()
-            .modified()
-            .map(|m| {
-                let date = chrono::DateTime::<chrono::Local>::from(m);
-                if date.year() < chrono::Local::now().year() {
-                    date.format("%e %b  %Y")
-                } else {
-                    date.format("%e %b %H:%M")
-                }
-                .to_string()
-            })
-            .unwrap_or("-".to_string());
+        self.date_modified_aligned(entry, self.date_width())
+    }
+
+    /// Modified date column right aligned to `width` characters
+    pub fn date_modified_aligned(&self, entry: &Entry, width: usize) -> String {
+        self.date_aligned(entry.metadata().modified(), width)
+    }
 
+    /// Any timestamp column right aligned to `width` characters, e.g. the
+    /// created or accessed time
+    pub fn date_aligned(&self, time: std::io::Result<SystemTime>, width: usize) -> String {
+        let date = self.date_text(time);
         format!(
             "{}{}",
-            (0..12usize.saturating_sub(date.len())).spacer(),
-            date.blue()
+            (0..width.saturating_sub(text::width(&date))).spacer(),
+            self.paint(date, Style::new().blue())
         )
     }
 
     fn access_rights(&self, buffer: &mut String, rights: &AccessRights) {
-        buffer.push_str(
-            rights
-                .readable()
-                .mode_char_color('r', Style::default().yellow())
-                .to_string()
-                .as_str(),
-        );
-        buffer.push_str(
-            rights
-                .writable()
-                .mode_char_color('w', Style::default().red())
-                .to_string()
-                .as_str(),
-        );
-        buffer.push_str(
-            rights
-                .executable()
-                .mode_char_color('x', Style::default().green())
-                .to_string()
-                .as_str(),
-        );
+        buffer.push_str(&self.mode_char_paint(rights.readable(), 'r', Style::new().yellow()));
+        buffer.push_str(&self.mode_char_paint(rights.writable(), 'w', Style::new().red()));
+        buffer.push_str(&self.mode_char_paint(rights.executable(), 'x', Style::new().green()));
     }
 
     fn file_type(&self, entry: &Entry) -> String {
         if entry.is_dir() {
-            'd'.blue().to_string()
+            self.paint('d', Style::new().blue())
         } else {
-            '.'.bold().to_string()
+            self.paint('.', Style::new().bold())
+        }
+    }
+
+    /// Owning user aligned to `width`, highlighted when it is the current user
+    pub fn owner_aligned(&self, entry: &Entry, width: usize) -> String {
+        let name = &entry.permissions().user().name;
+        let text = format!("{name:<width$}");
+        match *name == current_user().0 {
+            true => self.paint(text, Style::new().yellow()),
+            false => text,
+        }
+    }
+
+    /// Owning group aligned to `width`, highlighted when the current user is a
+    /// member of it
+    pub fn group_aligned(&self, entry: &Entry, width: usize) -> String {
+        let name = &entry.permissions().group().name;
+        let text = format!("{name:<width$}");
+        match current_user().1.contains(name) {
+            true => self.paint(text, Style::new().yellow()),
+            false => text,
         }
     }
 
@@ -301,4 +1339,228 @@ impl Colorizer {
         self.access_rights(&mut result, &entry.permissions().everyone().permissions);
         result
     }
+
+    /// Name of any [`Listable`], entries are styled by [`Colorizer::file`]
+    ///
+    /// Other items are styled by the group they name, see [`Listable::group`],
+    /// or the `DIR` group when they are a directory.
+    pub fn item(&self, item: &dyn Listable) -> String {
+        if let Some(entry) = item.entry() {
+            return self.file(entry);
+        }
+        let style = item
+            .group()
+            .or((item.kind() == Kind::Dir).then_some("DIR"))
+            .and_then(|group| self.group_style(group))
+            .unwrap_or_default();
+        format!(
+            "{}{}",
+            self.paint(item.name(), style),
+            self.item_suffix(item)
+        )
+    }
+
+    /// Number of columns [`Colorizer::item`] takes up
+    pub fn item_width(&self, item: &dyn Listable) -> usize {
+        match item.entry() {
+            Some(entry) => self.file_width(entry),
+            None => text::width(&item.name()) + self.item_suffix(item).len(),
+        }
+    }
+
+    fn item_suffix(&self, item: &dyn Listable) -> &'static str {
+        match (item.kind(), self.indicators, self.slash) {
+            (Kind::Dir, true, _) | (Kind::Dir, _, true) => "/",
+            (Kind::Link, true, _) => "@",
+            _ => "",
+        }
+    }
+
+    /// Unstyled text of the size column for any [`Listable`]
+    pub fn item_size_text(&self, item: &dyn Listable) -> String {
+        match (item.entry(), item.size()) {
+            (Some(entry), _) => self.file_size_text(entry),
+            (None, Some(size)) => self.size_text(size),
+            (None, None) => "-".to_string(),
+        }
+    }
+
+    /// Size column of any [`Listable`] right aligned to `width` characters
+    pub fn item_size_aligned(&self, item: &dyn Listable, width: usize) -> String {
+        if let Some(entry) = item.entry() {
+            return self.file_size_aligned(entry, width);
+        }
+        let text = self.item_size_text(item);
+        format!(
+            "{}{}",
+            (text::width(&text)..width).spacer(),
+            self.paint(text, Style::new().fg::<Gray>())
+        )
+    }
+
+    /// Permissions of any [`Listable`], items that aren't entries show their
+    /// kind and [`Listable::mode`]
+    pub fn item_permissions(&self, item: &dyn Listable) -> String {
+        match item.entry() {
+            Some(entry) => self.permissions(entry),
+            None => {
+                let mut result = match item.kind() {
+                    Kind::Dir => self.paint('d', Style::new().blue()),
+                    kind => self.paint(kind.char(), Style::new().bold()),
+                };
+                match item.mode() {
+                    Some(mode) => {
+                        for shift in [6, 3, 0] {
+                            let bits = mode >> shift;
+                            result.push_str(&self.mode_char_paint(
+                                bits & 4 != 0,
+                                'r',
+                                Style::new().yellow(),
+                            ));
+                            result.push_str(&self.mode_char_paint(
+                                bits & 2 != 0,
+                                'w',
+                                Style::new().red(),
+                            ));
+                            result.push_str(&self.mode_char_paint(
+                                bits & 1 != 0,
+                                'x',
+                                Style::new().green(),
+                            ));
+                        }
+                    }
+                    None => result.push_str(&self.paint("-".repeat(9), Style::new().dimmed())),
+                }
+                result
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::TempDir;
+
+    #[test]
+    fn size_units() {
+        assert_eq!(humansize(0), "-");
+        assert_eq!(humansize(1_000), "1000");
+        assert_eq!(humansize(1_536), "2K");
+        assert_eq!(humansize(5 * 1_048_576), "5M");
+        assert_eq!(humansize_in(1_000, SizeUnits::Si), "1k");
+        assert_eq!(humansize_in(999, SizeUnits::Si), "999");
+        assert_eq!(humansize_in(5_000_000, SizeUnits::Si), "5M");
+        assert_eq!(humansize_labeled(512), "512 B");
+        assert_eq!(humansize_labeled_in(2_000, SizeUnits::Si), "2 kB");
+    }
+
+    #[test]
+    fn child_count_ranges() {
+        assert_eq!(parse_count_range("0"), Ok((0, Some(0))));
+        assert_eq!(parse_count_range(">=1000"), Ok((1000, None)));
+        assert_eq!(parse_count_range(">9"), Ok((10, None)));
+        assert_eq!(parse_count_range("<10"), Ok((0, Some(9))));
+        assert_eq!(parse_count_range("10..100"), Ok((10, Some(99))));
+        assert!(parse_count_range("<0").is_err());
+        assert!(parse_count_range("many").is_err());
+
+        let base = TempDir::new("child-counts");
+        std::fs::create_dir_all(base.join("empty")).unwrap();
+        std::fs::create_dir_all(base.join("full")).unwrap();
+        for file in ["a", "b", "c", ".hidden"] {
+            std::fs::write(base.join("full").join(file), "").unwrap();
+        }
+
+        let colorizer = Colorizer::themed(ThemeGroup::child_counts(3));
+        let group = |name: &str| {
+            let entry = Entry::try_from(base.join(name).as_path()).unwrap();
+            colorizer.classify(&entry).map(str::to_string)
+        };
+        assert_eq!(group("empty").as_deref(), Some("EMPTY_DIR"));
+        assert_eq!(group("full").as_deref(), Some("HUGE_DIR"));
+        assert_eq!(group("full/a").as_deref(), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn type_indicators() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let base = TempDir::new("indicators");
+        std::fs::create_dir_all(base.join("dir")).unwrap();
+        std::fs::write(base.join("file"), "").unwrap();
+        std::fs::write(base.join("run"), "").unwrap();
+        std::fs::set_permissions(base.join("run"), std::fs::Permissions::from_mode(0o755)).unwrap();
+        std::os::unix::fs::symlink("file", base.join("link")).unwrap();
+
+        let colorizer = Colorizer::default().indicators(true);
+        let name = |name: &str| {
+            let path = base.join(name);
+            let entry =
+                Entry::with_metadata(path.clone(), path.symlink_metadata().unwrap()).unwrap();
+            colorizer.file(&entry)
+        };
+        assert_eq!(name("dir"), format!("{}/", "dir".style(Style::default())));
+        assert!(name("file").ends_with("file"));
+        assert!(name("run").ends_with('*'));
+        assert!(name("link").ends_with('@'));
+    }
+
+    #[test]
+    fn plain_helpers() {
+        let base = TempDir::new("plain");
+        std::fs::create_dir_all(base.join("dir")).unwrap();
+        let entry = Entry::try_from(base.join("dir").as_path()).unwrap();
+
+        let colorizer = Colorizer::default_theme().hyperlinks(true);
+        assert!(colorizer.file(&entry).contains('\x1b'));
+
+        let plain = Colorizer::default_theme()
+            .hyperlinks(true)
+            .color_mode(ColorMode::Never);
+        assert_eq!(plain.file(&entry), "dir");
+
+        let crumbs = colorizer.breadcrumb(&base.join("dir"));
+        assert!(crumbs.contains(&format!("\x1b]8;;{}\x1b\\", file_url(&base.join("dir")))));
+        assert!(crumbs.contains(&format!("\x1b]8;;{}\x1b\\", file_url(&base))));
+        for text in [
+            plain.permissions(&entry),
+            plain.file_size(&entry),
+            plain.date_modified(&entry),
+            plain.breadcrumb(&base),
+            plain.item_permissions(&crate::listable::Row::parent()),
+        ] {
+            assert!(!text.contains('\x1b'), "{text:?}");
+        }
+    }
+
+    #[test]
+    fn time_styles() {
+        use std::time::Duration;
+
+        let now = SystemTime::now();
+        assert_eq!(TimeStyle::Relative.format(now), "just now");
+        assert_eq!(
+            TimeStyle::Relative.format(now - Duration::from_secs(3 * 24 * 60 * 60 + 5)),
+            "3 days ago"
+        );
+        assert_eq!(
+            TimeStyle::Relative.format(now - Duration::from_secs(60 * 60 + 1)),
+            "1 hour ago"
+        );
+
+        assert_eq!(TimeStyle::from_str("long-iso"), Ok(TimeStyle::LongIso));
+        assert_eq!(
+            TimeStyle::from_str("+%Y"),
+            Ok(TimeStyle::Custom("%Y".to_string()))
+        );
+        assert!(TimeStyle::from_str("%Q").is_err());
+        assert!(TimeStyle::from_str("short").is_err());
+
+        let sample = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+        for style in [TimeStyle::Iso, TimeStyle::LongIso, TimeStyle::FullIso] {
+            assert!(style.format(sample).chars().count() <= style.width());
+        }
+    }
 }
@@ -0,0 +1,225 @@
+use std::{
+    ops::Deref,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    clock::FixedClock,
+    listable::{Kind, Listable, Row},
+    listing::ListingBuilder,
+    style::{ColorMode, Colorizer, TimeStyle},
+};
+
+/// Time the [`theme`] formats dates against, 2024-01-01 00:00 UTC
+pub fn fixed_now() -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_067_200)
+}
+
+/// Theme for snapshot tests
+///
+/// Colors are off and dates are relative to [`fixed_now`], so the output is
+/// the same on every platform and in every time zone.
+pub fn theme() -> Colorizer {
+    Colorizer::default_theme()
+        .color_mode(ColorMode::Never)
+        .time_style(TimeStyle::Relative)
+        .clock(FixedClock(fixed_now()))
+}
+
+/// Empty directory under the system temp directory that is removed when
+/// dropped, even when a test panics
+///
+/// Named after `name` and the process id so parallel tests and runs don't
+/// share directories.
+#[derive(Debug)]
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("xf-{name}-{}", std::process::id()));
+        // Left over from a run that was killed before it could clean up
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).expect("failed to create a temp directory");
+        Self(path)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// A directory tree that only exists in memory, for testing formatters
+/// without touching the disk
+///
+/// Entries are listed in the order they were added.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use xf::{listing::{Format, Listing}, testing::{self, MemoryFs}};
+///
+/// let fs = MemoryFs::new()
+///     .file("src/main.rs", 1200)
+///     .modified("src/main.rs", Duration::from_secs(3 * 60 * 60))
+///     .file("Cargo.toml", 300);
+/// let out = fs.render(
+///     Listing::builder()
+///         .format(Format::Tree)
+///         .theme(testing::theme()),
+/// );
+/// assert_eq!(out, "├ src\n│ └ main.rs\n└ Cargo.toml\n");
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MemoryFs {
+    root: Vec<Row>,
+}
+
+impl MemoryFs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a directory along with any missing parents, `src/format`
+    pub fn dir(mut self, path: &str) -> Self {
+        self.node(path, Kind::Dir);
+        self
+    }
+
+    /// Add a file of `size` bytes along with any missing parent directories
+    pub fn file(mut self, path: &str, size: u64) -> Self {
+        self.node(path, Kind::File).size = Some(size);
+        self
+    }
+
+    /// Set the modified time of a path to `age` before [`fixed_now`]
+    pub fn modified(mut self, path: &str, age: Duration) -> Self {
+        self.node(path, Kind::File).modified = Some(fixed_now() - age);
+        self
+    }
+
+    /// Entries at the root of the tree
+    pub fn items(&self) -> Vec<&dyn Listable> {
+        self.root.iter().map(|row| row as &dyn Listable).collect()
+    }
+
+    /// Print the entries with the layout and theme of `listing`, see
+    /// [`crate::listing::Listing::render_items`]
+    ///
+    /// The grid format wraps to 80 columns unless the listing sets a width.
+    pub fn render(&self, listing: ListingBuilder) -> String {
+        let mut out = Vec::new();
+        listing
+            .path(".")
+            .build()
+            .render_items(&mut out, &self.items())
+            .expect("failed to render the in memory tree");
+        String::from_utf8(out).expect("rendered output is utf-8")
+    }
+
+    /// Existing row at `path`, or a new one of `kind` with any missing
+    /// parents added as directories
+    fn node(&mut self, path: &str, kind: Kind) -> &mut Row {
+        let mut parts = path.split('/').filter(|part| !part.is_empty()).peekable();
+        let mut rows = &mut self.root;
+        loop {
+            let name = parts.next().expect("paths have at least one component");
+            let last = parts.peek().is_none();
+            let index = match rows.iter().position(|row| row.name == name) {
+                Some(index) => index,
+                None => {
+                    rows.push(Row::new(name, if last { kind } else { Kind::Dir }));
+                    rows.len() - 1
+                }
+            };
+            if last {
+                return &mut rows[index];
+            }
+            rows = &mut rows[index].children;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        format::{Branches, Column},
+        listing::{Format, Listing},
+    };
+
+    #[test]
+    fn temp_dir_cleanup() {
+        let dir = TempDir::new("temp-dir");
+        std::fs::write(dir.join("file"), "").unwrap();
+        let path = dir.to_path_buf();
+        drop(dir);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn snapshots() {
+        let fs = MemoryFs::new()
+            .dir("empty")
+            .file("src/lib.rs", 2048)
+            .file("src/format/mod.rs", 10)
+            .modified("src/lib.rs", Duration::from_secs(2 * 24 * 60 * 60))
+            .file("README.md", 12);
+
+        assert_eq!(
+            fs.render(
+                Listing::builder()
+                    .format(Format::List)
+                    .columns([Column::Size, Column::Date, Column::Name])
+                    .theme(theme())
+            ),
+            "   - -  empty\n   - -  src\n  12 -  README.md\n"
+        );
+
+        assert_eq!(
+            fs.render(
+                Listing::builder()
+                    .format(Format::Tree)
+                    .long(true)
+                    .branches(Branches::ascii())
+                    .theme(theme())
+            )
+            .lines()
+            .collect::<Vec<_>>(),
+            [
+                "d---------    -          - |-- empty",
+                "d---------    -          - |-- src",
+                ".---------   2K 2 days ago |   |-- lib.rs",
+                "d---------    -          - |   \\-- format",
+                ".---------   10          - |       \\-- mod.rs",
+                ".---------   12          - \\-- README.md",
+            ]
+        );
+
+        assert_eq!(
+            fs.render(Listing::builder().width(Some(20)).theme(theme())),
+            "empty      src\nREADME.md\n"
+        );
+    }
+}
@@ -0,0 +1,143 @@
+use std::borrow::Cow;
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Pieces of a string, escape sequences apart from the text between them
+enum Piece<'a> {
+    Escape(&'a str),
+    Text(&'a str),
+}
+
+/// Split `s` into CSI (`\x1b[...m`) and OSC (`\x1b]...\x1b\\`) escape
+/// sequences and the text between them
+fn pieces(s: &str) -> impl Iterator<Item = Piece<'_>> {
+    let mut rest = s;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let Some(stripped) = rest.strip_prefix('\x1b') else {
+            let end = rest.find('\x1b').unwrap_or(rest.len());
+            let (text, tail) = rest.split_at(end);
+            rest = tail;
+            return Some(Piece::Text(text));
+        };
+
+        let len = match stripped.chars().next() {
+            // Parameters end at the first byte in `@` to `~`
+            Some('[') => stripped[1..]
+                .find(|c: char| ('@'..='~').contains(&c))
+                .map(|end| end + 3),
+            // Terminated by BEL or ST
+            Some(']') => stripped.find(['\x07', '\x1b']).map(|end| {
+                match stripped[end..].starts_with("\x1b\\") {
+                    true => end + 3,
+                    false => end + 2,
+                }
+            }),
+            Some(c) => Some(1 + c.len_utf8()),
+            None => Some(1),
+        }
+        .unwrap_or(rest.len());
+        let (escape, tail) = rest.split_at(len);
+        rest = tail;
+        Some(Piece::Escape(escape))
+    })
+}
+
+/// `s` without any escape sequences
+pub fn strip_ansi(s: &str) -> Cow<'_, str> {
+    if !s.contains('\x1b') {
+        return Cow::Borrowed(s);
+    }
+    Cow::Owned(
+        pieces(s)
+            .filter_map(|piece| match piece {
+                Piece::Text(text) => Some(text),
+                Piece::Escape(_) => None,
+            })
+            .collect(),
+    )
+}
+
+/// Number of terminal columns `s` takes up
+///
+/// Escape sequences take up none, wide characters like CJK and emoji take
+/// up two, and combining marks are part of the character before them.
+pub fn width(s: &str) -> usize {
+    pieces(s)
+        .map(|piece| match piece {
+            Piece::Text(text) => text.graphemes(true).map(UnicodeWidthStr::width).sum(),
+            Piece::Escape(_) => 0,
+        })
+        .sum()
+}
+
+/// Cut `s` down to at most `max` columns, ending in `ellipsis` when anything
+/// was cut
+///
+/// Whole characters are kept so nothing is split in half, and escape
+/// sequences are kept so styles still end where they did.
+pub fn truncate<'a>(s: &'a str, max: usize, ellipsis: &str) -> Cow<'a, str> {
+    if width(s) <= max {
+        return Cow::Borrowed(s);
+    }
+
+    let mut budget = max.saturating_sub(width(ellipsis));
+    let mut out = String::with_capacity(s.len());
+    let mut cut = false;
+    for piece in pieces(s) {
+        match piece {
+            Piece::Escape(escape) => out.push_str(escape),
+            Piece::Text(_) if cut => {}
+            Piece::Text(text) => {
+                for grapheme in text.graphemes(true) {
+                    let width = grapheme.width();
+                    if width > budget {
+                        // The ellipsis goes where the text was cut, before
+                        // any escapes that close its style
+                        out.push_str(ellipsis);
+                        cut = true;
+                        break;
+                    }
+                    budget -= width;
+                    out.push_str(grapheme);
+                }
+            }
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// `s` followed by spaces up to `width` columns
+pub fn pad(s: &str, width: usize) -> String {
+    format!("{s}{}", " ".repeat(width.saturating_sub(self::width(s))))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn measure_and_truncate() {
+        let styled = "\x1b[34mdir\x1b[0m";
+        assert_eq!(strip_ansi(styled), "dir");
+        assert_eq!(strip_ansi("\x1b]8;;file:///a\x1b\\a\x1b]8;;\x1b\\"), "a");
+        assert_eq!(width(styled), 3);
+        assert_eq!(width("日本語.txt"), 10);
+        assert_eq!(width("e\u{301}t\u{301}e\u{301}"), 3);
+
+        assert_eq!(truncate("short", 10, "…"), "short");
+        assert_eq!(truncate("a-long-name.txt", 8, "…"), "a-long-…");
+        assert_eq!(truncate("日本語.txt", 5, "…"), "日本…");
+        assert_eq!(
+            truncate("e\u{301}e\u{301}e\u{301}e\u{301}", 3, "…"),
+            "e\u{301}e\u{301}…"
+        );
+        assert_eq!(truncate(styled, 2, "…"), "\x1b[34md…\x1b[0m");
+
+        assert_eq!(pad("日本", 6), "日本  ");
+        assert_eq!(pad(styled, 5), format!("{styled}  "));
+    }
+}
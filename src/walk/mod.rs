@@ -0,0 +1,348 @@
+use std::{cmp::Ordering, collections::HashMap, path::PathBuf};
+
+use crate::{content, Entry, FileSystem};
+
+#[cfg(feature = "async")]
+mod stream;
+
+impl FileSystem {
+    /// Recursively walk the file system on the current thread
+    ///
+    /// Entries are returned depth first in the same order as the tree format,
+    /// with the filters, sorter, and max depth applied to every directory.
+    /// Nested directories that can't be read are skipped, unless the file
+    /// system is [`FileSystem::with_strict`].
+    pub fn walk(&self) -> Result<Vec<Entry>, Box<dyn std::error::Error>> {
+        let mut walked = Vec::new();
+        self.walk_serial(self.read_dir(&self.path)?, 1, &mut walked)?;
+        Ok(walked)
+    }
+
+    /// Same as [`FileSystem::walk`] with every entry paired with the value `f`
+    /// computes for it
+    ///
+    /// Entries are filtered and sorted before `f` is called so the values stay
+    /// with their entries, and can be printed with [`Template::render_with`].
+    /// Use [`FileSystem::walk_with_by`] to sort by the values.
+    ///
+    /// [`Template::render_with`]: crate::format::Template::render_with
+    pub fn walk_with<T, F>(&self, f: F) -> Result<Vec<(Entry, T)>, Box<dyn std::error::Error>>
+    where
+        F: FnMut(&Entry) -> T,
+    {
+        self.walk_with_by(f, |_, _| Ordering::Equal)
+    }
+
+    /// Same as [`FileSystem::walk_with`] with the entries of every directory
+    /// sorted by `compare`
+    ///
+    /// The values are computed as each directory is read and the sort is
+    /// stable, so entries `compare` considers equal keep the order of the file
+    /// system's sorter.
+    pub fn walk_with_by<T, F, C>(
+        &self,
+        mut f: F,
+        mut compare: C,
+    ) -> Result<Vec<(Entry, T)>, Box<dyn std::error::Error>>
+    where
+        F: FnMut(&Entry) -> T,
+        C: FnMut(&(Entry, T), &(Entry, T)) -> Ordering,
+    {
+        let mut walked = Vec::new();
+        let root = self.read_dir(&self.path)?;
+        self.walk_data(root, 1, &mut f, &mut compare, &mut walked)?;
+        Ok(walked)
+    }
+
+    /// Same as [`FileSystem::walk`] but reads directories across all available threads
+    ///
+    /// Each level of the tree is read in parallel and every directory is
+    /// filtered and sorted on its own. The results are then merged back in
+    /// traversal order so the output is identical to [`FileSystem::walk`].
+    pub fn walk_parallel(&self) -> Result<Vec<Entry>, Box<dyn std::error::Error>> {
+        let root = self.read_dir(&self.path)?;
+
+        let mut children = HashMap::new();
+        let mut level = self.descend(&root, 1);
+        let mut depth = 1;
+        while !level.is_empty() {
+            let paths = level.iter().map(PathBuf::as_path).collect::<Vec<_>>();
+            let read = content::parallel_map(&paths, |path| {
+                self.read_dir(path).map_err(|err| err.to_string())
+            });
+
+            depth += 1;
+            let mut next = Vec::new();
+            for (path, entries) in level.into_iter().zip(read) {
+                match entries {
+                    Ok(entries) => {
+                        next.extend(self.descend(&entries, depth));
+                        children.insert(path, entries);
+                    }
+                    Err(err) if self.strict => return Err(err.into()),
+                    Err(_) => {}
+                }
+            }
+            level = next;
+        }
+
+        let mut walked = Vec::new();
+        merge(root, &mut children, &mut walked);
+        Ok(walked)
+    }
+
+    fn walk_serial(
+        &self,
+        entries: Vec<Entry>,
+        depth: usize,
+        walked: &mut Vec<Entry>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        for entry in entries {
+            let nested = match self.descend(std::slice::from_ref(&entry), depth).pop() {
+                Some(path) => match self.read_dir(&path) {
+                    Ok(nested) => Some(nested),
+                    Err(err) if self.strict => return Err(err),
+                    Err(_) => None,
+                },
+                None => None,
+            };
+
+            walked.push(entry);
+            if let Some(nested) = nested {
+                self.walk_serial(nested, depth + 1, walked)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn walk_data<T, F, C>(
+        &self,
+        entries: Vec<Entry>,
+        depth: usize,
+        f: &mut F,
+        compare: &mut C,
+        walked: &mut Vec<(Entry, T)>,
+    ) -> Result<(), Box<dyn std::error::Error>>
+    where
+        F: FnMut(&Entry) -> T,
+        C: FnMut(&(Entry, T), &(Entry, T)) -> Ordering,
+    {
+        let mut entries = entries
+            .into_iter()
+            .map(|entry| {
+                let data = f(&entry);
+                (entry, data)
+            })
+            .collect::<Vec<_>>();
+        entries.sort_by(|a, b| compare(a, b));
+
+        for (entry, data) in entries {
+            let nested = match self.descend(std::slice::from_ref(&entry), depth).pop() {
+                Some(path) => match self.read_dir(&path) {
+                    Ok(nested) => Some(nested),
+                    Err(err) if self.strict => return Err(err),
+                    Err(_) => None,
+                },
+                None => None,
+            };
+
+            walked.push((entry, data));
+            if let Some(nested) = nested {
+                self.walk_data(nested, depth + 1, f, compare, walked)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Directories in `entries` that are still within the max depth
+    fn descend(&self, entries: &[Entry], depth: usize) -> Vec<PathBuf> {
+        if self.max_depth.is_some_and(|max| depth >= max) {
+            return Vec::new();
+        }
+        entries
+            .iter()
+            .filter(|entry| self.descends_into(entry))
+            .map(|entry| entry.path().to_path_buf())
+            .collect()
+    }
+}
+
+/// Emit each directory's sorted entries in traversal order, following every
+/// directory with its own children
+fn merge(
+    entries: Vec<Entry>,
+    children: &mut HashMap<PathBuf, Vec<Entry>>,
+    walked: &mut Vec<Entry>,
+) {
+    for entry in entries {
+        let nested = children.remove(entry.path());
+        walked.push(entry);
+        if let Some(nested) = nested {
+            merge(nested, children, walked);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        format::Template,
+        sort::{Natural, Size},
+        testing::TempDir,
+        FileSystem,
+    };
+
+    fn render(entries: Vec<crate::Entry>) -> Vec<u8> {
+        entries
+            .iter()
+            .flat_map(|entry| {
+                let mut line = entry.path().as_os_str().as_encoded_bytes().to_vec();
+                line.push(b'\n');
+                line
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parallel_matches_serial() {
+        let base = TempDir::new("walk");
+        for dir in ["b/nested/deep", "a", "c/empty", "c/more"] {
+            std::fs::create_dir_all(base.join(dir)).unwrap();
+        }
+        for (i, file) in [
+            "z.txt",
+            "a/one.rs",
+            "a/two.rs",
+            "b/nested/x",
+            "b/nested/deep/y",
+            "c/more/w",
+        ]
+        .iter()
+        .enumerate()
+        {
+            std::fs::write(base.join(file), "x".repeat(i * 10)).unwrap();
+        }
+
+        let natural = FileSystem::from(base.path()).with_sorter(Natural);
+        let serial = render(natural.walk().unwrap());
+        assert_eq!(serial, render(natural.walk_parallel().unwrap()));
+        assert_eq!(serial.iter().filter(|b| **b == b'\n').count(), 13);
+
+        let sized = FileSystem::from(base.path())
+            .with_sorter(Size::default())
+            .with_max_depth(Some(2));
+        assert_eq!(
+            render(sized.walk().unwrap()),
+            render(sized.walk_parallel().unwrap())
+        );
+    }
+
+    #[test]
+    fn walk_with_data() {
+        let base = TempDir::new("walk-with");
+        std::fs::create_dir_all(base.join("dir")).unwrap();
+        std::fs::write(base.join("dir").join("file"), "12345").unwrap();
+
+        let file_system = FileSystem::from(base.path()).with_sorter(Natural);
+        let walked = file_system
+            .walk_with(|entry| entry.metadata().len() * 2)
+            .unwrap();
+        let template = Template::new(file_system, "{name}={data:>3}").unwrap();
+        let lines = walked
+            .iter()
+            .skip(1)
+            .map(|(entry, data)| template.render_with(entry, data))
+            .collect::<Vec<_>>();
+        assert_eq!(lines, ["file= 10"]);
+
+        // `small` sorts after `dir` by name but before it by data
+        std::fs::write(base.join("small"), "1").unwrap();
+        let file_system = FileSystem::from(base.path()).with_sorter(Natural);
+        let names = file_system
+            .walk_with_by(
+                |entry| match entry.is_dir() {
+                    true => 0,
+                    false => entry.metadata().len(),
+                },
+                |a, b| b.1.cmp(&a.1),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|(entry, _)| entry.file_name().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["small", "dir", "file"]);
+    }
+
+    #[test]
+    fn hidden_dirs() {
+        let base = TempDir::new("walk-hidden");
+        std::fs::create_dir_all(base.join(".github")).unwrap();
+        std::fs::write(base.join(".github").join("ci.yml"), "").unwrap();
+
+        let file_system = FileSystem::from(base.path())
+            .with_sorter(Natural)
+            .with_filter(());
+        let names = |file_system: &FileSystem| {
+            file_system
+                .walk()
+                .unwrap()
+                .iter()
+                .map(|entry| entry.file_name().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&file_system), [".github"]);
+        assert_eq!(
+            names(&file_system.with_hidden_dirs(true)),
+            [".github", "ci.yml"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn dereference_links() {
+        let base = TempDir::new("walk-links");
+        std::fs::create_dir_all(base.join("real")).unwrap();
+        std::fs::write(base.join("real").join("file"), "12345").unwrap();
+        std::os::unix::fs::symlink("real", base.join("link")).unwrap();
+        std::os::unix::fs::symlink("..", base.join("real").join("up")).unwrap();
+
+        let names = |entries: Vec<crate::Entry>| {
+            entries
+                .iter()
+                .map(|entry| {
+                    entry
+                        .path()
+                        .strip_prefix(&base)
+                        .unwrap()
+                        .display()
+                        .to_string()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let links = FileSystem::from(base.path()).with_sorter(Natural);
+        assert_eq!(
+            names(links.walk().unwrap()),
+            ["link", "real", "real/file", "real/up"]
+        );
+        assert!(!links.entries().unwrap()[0].is_dir());
+
+        // Links back to an ancestor are listed but not followed
+        let followed = links.with_dereference(true);
+        let walked = names(followed.walk().unwrap());
+        assert_eq!(
+            walked,
+            [
+                "link",
+                "link/file",
+                "link/up",
+                "real",
+                "real/file",
+                "real/up"
+            ]
+        );
+        assert_eq!(walked, names(followed.walk_parallel().unwrap()));
+        let link = &followed.entries().unwrap()[0];
+        assert!(link.is_dir() && !link.metadata().is_symlink());
+    }
+}
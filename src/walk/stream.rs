@@ -0,0 +1,104 @@
+use std::{io, path::PathBuf};
+
+use tokio::sync::mpsc;
+use tokio_stream::{wrappers::ReceiverStream, Stream};
+
+use crate::{Entry, FileSystem};
+
+impl FileSystem {
+    /// Recursively walk the file system without blocking an async runtime
+    ///
+    /// Entries are yielded depth first in the same order as the tree format,
+    /// with the filters, sorter, and max depth applied to every directory. Directories that
+    /// can't be read yield an error and are skipped. Must be called from within
+    /// a tokio runtime.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use tokio_stream::StreamExt;
+    /// use xf::FileSystem;
+    ///
+    /// # async fn run() {
+    /// let mut entries = FileSystem::from("src").walk_async();
+    /// while let Some(entry) = entries.next().await {
+    ///     println!("{}", entry.unwrap().path().display());
+    /// }
+    /// # }
+    /// ```
+    pub fn walk_async(&self) -> impl Stream<Item = io::Result<Entry>> + Send + 'static {
+        let (sender, receiver) = mpsc::channel(64);
+        let file_system = self.clone();
+
+        tokio::spawn(async move {
+            let mut stack = match read(&file_system, file_system.path.clone()).await {
+                Ok(entries) => vec![entries.into_iter()],
+                Err(err) => {
+                    let _ = sender.send(Err(err)).await;
+                    return;
+                }
+            };
+
+            while let Some(entries) = stack.last_mut() {
+                let Some(entry) = entries.next() else {
+                    stack.pop();
+                    continue;
+                };
+
+                let dir = file_system
+                    .descends_into(&entry)
+                    .then(|| entry.path().to_path_buf());
+                // The receiver was dropped so nobody is listening anymore
+                if sender.send(Ok(entry)).await.is_err() {
+                    return;
+                }
+
+                let descend = file_system.max_depth().is_none_or(|max| stack.len() < max);
+                if let Some(dir) = dir.filter(|_| descend) {
+                    match read(&file_system, dir).await {
+                        Ok(entries) => stack.push(entries.into_iter()),
+                        Err(err) => {
+                            if sender.send(Err(err)).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                }
+            }
+        });
+
+        ReceiverStream::new(receiver)
+    }
+}
+
+/// Read a directory with `tokio::fs`, then build, filter, and sort its entries
+/// on the blocking pool since filters may read file contents
+async fn read(file_system: &FileSystem, path: PathBuf) -> io::Result<Vec<Entry>> {
+    let mut dir = tokio::fs::read_dir(&path).await?;
+    let mut children = Vec::new();
+    while let Some(child) = dir.next_entry().await? {
+        match child.metadata().await {
+            Ok(meta) => children.push((child.path(), meta)),
+            Err(err) if file_system.strict() => return Err(err),
+            Err(_) => {}
+        }
+    }
+
+    let file_system = file_system.clone();
+    tokio::task::spawn_blocking(move || {
+        let mut entries = Vec::with_capacity(children.len());
+        for (path, meta) in children {
+            match Entry::with_metadata(path.clone(), meta) {
+                Ok(entry) if file_system.dereference() => entries.push(entry.dereference()),
+                Ok(entry) => entries.push(entry),
+                Err(err) if file_system.strict() => {
+                    return Err(io::Error::other(format!("{}: {err}", path.display())))
+                }
+                Err(_) => {}
+            }
+        }
+        Ok(file_system.filter_and_sort(entries))
+    })
+    .await
+    .map_err(io::Error::other)?
+}